@@ -1,11 +1,40 @@
-use chrono::{DateTime, Days, Local, NaiveDate};
-use cr_project_planner::project::{Project, ProjectDisplayAction};
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
+use cr_project_planner::attachment::{self, AttachmentManager};
+use cr_project_planner::calendar::{CalendarWindow, WorkCalendar};
+use cr_project_planner::command::{
+    Command, CommandPalette, Keymap, KeymapWindow, PaletteChoice, TaskCursor,
+};
+use cr_project_planner::dashboard::{show_dashboard, DashboardAction};
+use cr_project_planner::folder::{self, FolderAction, FolderTree};
+use cr_project_planner::history::UndoHistory;
+use cr_project_planner::people::{AssigneeFilter, PeopleDirectory, PeopleManager};
+use cr_project_planner::project::bulk::BulkAction;
+use cr_project_planner::project::reorder;
+use cr_project_planner::project::restructure::RestructureWindow;
+use cr_project_planner::project::{task_shown, Project, ProjectContext, ProjectDisplayAction};
+use cr_project_planner::project_list::ProjectListSettings;
+use cr_project_planner::quick_add::QuickAddBox;
+use cr_project_planner::reminder::{
+    pending_notifications, NotificationCenter, NotificationCenterAction,
+};
+use cr_project_planner::search::{self, show_search_results, SearchIndex};
+use cr_project_planner::settings::{self, Preferences, SettingsWindow, PREFERENCES_KEY};
+use cr_project_planner::tag::{TagFilter, TagManager, TagRegistry};
+use cr_project_planner::template::{
+    fill_placeholders, ProjectTemplate, TemplatePicker, TemplatePickerAction,
+};
+use cr_project_planner::time_tracking::{stop_other_timers, Timesheet};
+use cr_project_planner::timezone::TimeZoneSettings;
+use cr_project_planner::transfer::{self, TaskClipboard, TaskRef, TransferKind, TransferWarning};
+use cr_project_planner::workload::WorkloadWindow;
 use eframe::{Frame, Storage};
 use egui::scroll_area::ScrollBarVisibility;
-use egui::style::{ScrollStyle, Spacing};
-use egui::{Color32, Context, Layout, ScrollArea, Style, ViewportCommand};
+use egui::style::ScrollStyle;
+use egui::{Color32, Context, Layout, ScrollArea, ViewportCommand};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 use std::ops::Not;
+use std::path::PathBuf;
 use std::thread;
 use std::thread::JoinHandle;
 use self_update::cargo_crate_version;
@@ -34,7 +63,7 @@ pub struct ProjectPlanner {
     #[serde(skip)]
     add_project_open: bool,
 
-    last_open: DateTime<Local>,
+    last_open: DateTime<Utc>,
 
     #[serde(skip)]
     first_run: bool,
@@ -52,15 +81,326 @@ pub struct ProjectPlanner {
 
     #[serde(skip)]
     showing_about_page: bool,
+
+    current_view: PlannerView,
+
+    #[serde(skip)]
+    search_query: String,
+
+    #[serde(skip)]
+    search_index: SearchIndex,
+
+    tag_registry: TagRegistry,
+
+    #[serde(skip)]
+    tag_filter: TagFilter,
+
+    #[serde(skip)]
+    tag_manager: TagManager,
+
+    #[serde(skip)]
+    timesheet: Timesheet,
+
+    #[serde(skip)]
+    notification_center: NotificationCenter,
+
+    desktop_notifications: bool,
+
+    work_calendar: WorkCalendar,
+
+    #[serde(skip)]
+    calendar_window: CalendarWindow,
+
+    time_zones: TimeZoneSettings,
+
+    #[serde(skip)]
+    time_zone_window_open: bool,
+
+    templates: Vec<ProjectTemplate>,
+
+    #[serde(skip)]
+    template_picker: TemplatePicker,
+
+    #[serde(skip)]
+    restructure_window: RestructureWindow,
+
+    #[serde(skip)]
+    task_clipboard: TaskClipboard,
+
+    #[serde(skip)]
+    transfer_warnings: Vec<TransferWarning>,
+
+    #[serde(skip)]
+    history: UndoHistory,
+
+    #[serde(skip)]
+    attachments_dir: Option<PathBuf>,
+
+    #[serde(skip)]
+    attachment_manager: AttachmentManager,
+
+    people: PeopleDirectory,
+
+    #[serde(skip)]
+    people_manager: PeopleManager,
+
+    #[serde(skip)]
+    assignee_filter: AssigneeFilter,
+
+    #[serde(skip)]
+    workload_window: WorkloadWindow,
+
+    folders: FolderTree,
+
+    project_list: ProjectListSettings,
+
+    /// Saved separately under [`PREFERENCES_KEY`]
+    #[serde(skip)]
+    preferences: Preferences,
+
+    /// Shortcuts as they were saved with the project data before moving into the preferences,
+    /// only read to carry them over
+    #[serde(skip_serializing)]
+    keymap: Option<Keymap>,
+
+    #[serde(skip)]
+    settings_window: SettingsWindow,
+
+    /// Light or dark mode of the operating system the theme was last applied with
+    #[serde(skip)]
+    system_theme: Option<eframe::Theme>,
+
+    #[serde(skip)]
+    keymap_window: KeymapWindow,
+
+    #[serde(skip)]
+    command_palette: CommandPalette,
+
+    #[serde(skip)]
+    task_cursor: TaskCursor,
+
+    /// Every task shown in the project list last frame, in display order
+    #[serde(skip)]
+    shown_tasks: Vec<TaskRef>,
+
+    #[serde(skip)]
+    quick_add: QuickAddBox,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+enum PlannerView {
+    Dashboard,
+    Projects,
 }
 
 impl ProjectPlanner {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        egui_extras::install_image_loaders(&cc.egui_ctx);
+        cc.egui_ctx
+            .style_mut(|style| style.spacing.scroll = ScrollStyle::solid());
+
+        let preferences: Option<Preferences> = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, PREFERENCES_KEY));
+        // project data is read from the chosen data directory if there is one, and from the app
+        // storage until it has first been saved there
+        let mut planner: Self = preferences
+            .as_ref()
+            .and_then(Preferences::project_file)
+            .and_then(|file| settings::read_data_file(&file))
+            .or_else(|| {
+                cc.storage
+                    .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            })
+            .unwrap_or_default();
+        planner.preferences = preferences.unwrap_or_else(|| Preferences {
+            keymap: planner.keymap.take().unwrap_or_default(),
+            ..Default::default()
+        });
+        planner.system_theme = cc.integration_info.system_theme;
+        planner.apply_preferences(&cc.egui_ctx);
+        planner
+    }
+
+    /// Apply a bulk action to the selected tasks of the project being edited, as one undoable
+    /// operation
+    fn apply_bulk_action(&mut self, bulk_action: BulkAction) {
+        let Some(project_uuid) = self
+            .projects
+            .get(self.editing_project_index)
+            .map(|project| project.get_uuid())
+        else {
+            return;
+        };
+        let selected: Vec<TaskRef> = transfer::selected_tasks(&self.projects)
+            .into_iter()
+            .filter(|task| task.project == project_uuid)
+            .collect();
+        if selected.is_empty() {
+            return;
         }
 
-        Default::default()
+        let label = bulk_action.get_text(selected.len(), &self.time_zones);
+        match bulk_action {
+            BulkAction::MoveTo(destination) => {
+                self.transfer_warnings = self.history.record(
+                    label,
+                    &mut self.projects,
+                    &[project_uuid, destination],
+                    |projects| {
+                        let warnings = transfer::transfer_tasks(
+                            projects,
+                            &selected,
+                            destination,
+                            TransferKind::Move,
+                        );
+                        transfer::clear_selection(projects);
+                        warnings
+                    },
+                );
+            }
+            _ => {
+                let project_index = self.editing_project_index;
+                self.history
+                    .record(label, &mut self.projects, &[project_uuid], |projects| {
+                        bulk_action.apply(&mut projects[project_index]);
+                    });
+            }
+        }
+    }
+
+    /// Apply the theme, display formats and attachments directory of the preferences
+    fn apply_preferences(&mut self, ctx: &Context) {
+        ctx.set_visuals(self.preferences.theme.visuals(self.system_theme));
+        self.time_zones.display_format = self.preferences.display_format();
+        self.attachments_dir = self
+            .preferences
+            .attachments_dir_or(attachment::attachments_dir(crate::APP_NAME));
+    }
+
+    /// Open the add project window with the dates of a new project of the preferred length in
+    /// working days
+    fn open_add_project(&mut self) {
+        self.add_project_open = true;
+        self.start_date_selected = self.time_zones.display_today();
+        self.end_date_selected = self.work_calendar.add_working_days(
+            self.start_date_selected,
+            self.preferences.default_project_days as i64,
+        );
+    }
+
+    fn run_command(&mut self, command: Command) {
+        match command {
+            Command::OpenCommandPalette => self.command_palette.toggle(),
+            Command::NewProject => self.open_add_project(),
+            Command::ShowDashboard => self.current_view = PlannerView::Dashboard,
+            Command::ShowProjects => self.current_view = PlannerView::Projects,
+            Command::Undo => {
+                self.history.undo(&mut self.projects);
+            }
+            Command::Redo => {
+                self.history.redo(&mut self.projects);
+            }
+            Command::MoveTasksUp => self.shift_selected_tasks(true),
+            Command::MoveTasksDown => self.shift_selected_tasks(false),
+            Command::NextTask | Command::PreviousTask => {
+                self.current_view = PlannerView::Projects;
+                self.task_cursor
+                    .step(&self.shown_tasks, command == Command::NextTask);
+            }
+            Command::ToggleTaskCompleted => self.toggle_cursor_task(),
+            Command::EditTask => {
+                let Some(cursor) = self.task_cursor.task else {
+                    return;
+                };
+                if let Some(project_index) = self
+                    .projects
+                    .iter()
+                    .position(|project| project.get_uuid() == cursor.project)
+                {
+                    self.projects[project_index].edit_task(cursor.task);
+                    self.editing_project = true;
+                    self.editing_project_index = project_index;
+                }
+            }
+            Command::OpenPeople => self.people_manager.open = true,
+            Command::OpenWorkload => self.workload_window.open = true,
+            Command::ManageTags => self.tag_manager.open = true,
+            Command::OpenTimesheet => self.timesheet.open = true,
+            Command::OpenWorkCalendar => self.calendar_window.open = true,
+            Command::OpenTimeZones => self.time_zone_window_open = true,
+            Command::OpenAttachments => self.attachment_manager.open = true,
+            Command::OpenNotifications => self.notification_center.open = true,
+            Command::OpenKeymap => self.keymap_window.open = true,
+            Command::OpenSettings => self.settings_window.open = true,
+        }
+    }
+
+    /// Complete or reopen the task under the task cursor, as one undoable operation
+    fn toggle_cursor_task(&mut self) {
+        let Some(cursor) = self.task_cursor.task else {
+            return;
+        };
+        let Some(completed) = self
+            .projects
+            .iter()
+            .filter(|project| project.get_uuid() == cursor.project)
+            .flat_map(|project| project.tasks.iter())
+            .find(|task| task.get_uuid() == cursor.task)
+            .map(|task| task.get_completed())
+        else {
+            return;
+        };
+        let label = if completed {
+            "Reopen task"
+        } else {
+            "Complete task"
+        };
+        self.history
+            .record(label, &mut self.projects, &[cursor.project], |projects| {
+                projects
+                    .iter_mut()
+                    .filter(|project| project.get_uuid() == cursor.project)
+                    .flat_map(|project| project.tasks.iter_mut())
+                    .filter(|task| task.get_uuid() == cursor.task)
+                    .for_each(|task| task.set_completed(!completed));
+            });
+    }
+
+    /// Move a task within its project as one undoable operation
+    fn move_task(&mut self, project_uuid: Uuid, task: Uuid, to: usize) {
+        self.history.record(
+            "Reorder task",
+            &mut self.projects,
+            &[project_uuid],
+            |projects| {
+                if let Some(project) = projects
+                    .iter_mut()
+                    .find(|project| project.get_uuid() == project_uuid)
+                {
+                    project.move_task(task, to);
+                }
+            },
+        );
+    }
+
+    /// Move the selected tasks of every project one place up or down, as one undoable operation
+    fn shift_selected_tasks(&mut self, up: bool) {
+        let touched: Vec<Uuid> = transfer::selected_tasks(&self.projects)
+            .iter()
+            .map(|task| task.project)
+            .collect();
+        let label = if up {
+            "Move tasks up"
+        } else {
+            "Move tasks down"
+        };
+        self.history
+            .record(label, &mut self.projects, &touched, |projects| {
+                projects.iter_mut().for_each(|project| {
+                    reorder::shift_selected(&mut project.tasks, up);
+                });
+            });
     }
 }
 
@@ -78,13 +418,49 @@ impl Default for ProjectPlanner {
             editing_project: false,
             editing_project_index: 0,
             add_project_open: false,
-            last_open: Local::now(),
+            last_open: Utc::now(),
             first_run: true,
             update_available: None,
             auto_update_seen_version: None,
             update_thread: None,
             auto_update_status: None,
             showing_about_page: false,
+            current_view: PlannerView::Dashboard,
+            search_query: "".to_string(),
+            search_index: SearchIndex::default(),
+            tag_registry: TagRegistry::default(),
+            tag_filter: TagFilter::default(),
+            tag_manager: TagManager::default(),
+            timesheet: Timesheet::default(),
+            notification_center: NotificationCenter::default(),
+            desktop_notifications: false,
+            work_calendar: WorkCalendar::default(),
+            calendar_window: CalendarWindow::default(),
+            time_zones: TimeZoneSettings::default(),
+            time_zone_window_open: false,
+            templates: vec![],
+            template_picker: TemplatePicker::default(),
+            restructure_window: RestructureWindow::default(),
+            task_clipboard: TaskClipboard::default(),
+            transfer_warnings: vec![],
+            history: UndoHistory::default(),
+            attachments_dir: attachment::attachments_dir(crate::APP_NAME),
+            attachment_manager: AttachmentManager::default(),
+            people: PeopleDirectory::default(),
+            people_manager: PeopleManager::default(),
+            assignee_filter: AssigneeFilter::default(),
+            workload_window: WorkloadWindow::default(),
+            folders: FolderTree::default(),
+            project_list: ProjectListSettings::default(),
+            preferences: Preferences::default(),
+            keymap: None,
+            settings_window: SettingsWindow::default(),
+            system_theme: None,
+            keymap_window: KeymapWindow::default(),
+            command_palette: CommandPalette::default(),
+            task_cursor: TaskCursor::default(),
+            shown_tasks: vec![],
+            quick_add: QuickAddBox::default(),
         }
     }
 }
@@ -132,63 +508,407 @@ fn update_program() -> JoinHandle<Result<UpdateStatus, Error>> {
 
 
 impl eframe::App for ProjectPlanner {
-    fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
+    fn update(&mut self, ctx: &Context, frame: &mut Frame) {
 
         if self.first_run {
             self.first_run = false;
-            let last_open = Local::now().signed_duration_since(self.last_open).num_hours();
-            if last_open > 1 {
-                match get_release_list() {
-                    Ok(list) => {
-                        if let Some(release) = list.first() {
-                            if let Ok(greater_bump) = self_update::version::bump_is_greater(cargo_crate_version!(),&release.version) {
-                                if greater_bump {
-                                    self.update_available = Some(release.clone());
-                                }
+            let now = Utc::now();
+            if self
+                .preferences
+                .update_check
+                .is_due(self.preferences.last_update_check, now)
+            {
+                self.preferences.last_update_check = Some(now);
+                if let Ok(list) = get_release_list() {
+                    if let Some(release) = list.first() {
+                        if let Ok(greater_bump) = self_update::version::bump_is_greater(cargo_crate_version!(),&release.version) {
+                            if greater_bump {
+                                self.update_available = Some(release.clone());
                             }
                         }
                     }
-                    Err(_) => {}
                 }
             }
 
         }
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.set_style(Style {
-                spacing: Spacing {
-                    scroll: ScrollStyle::solid(),
-                    ..Default::default()
-                },
-                ..Default::default()
-            });
+        // eframe switches the visuals along with the system theme, put the preferred theme back
+        let system_theme = frame.info().system_theme;
+        if system_theme != self.system_theme {
+            self.system_theme = system_theme;
+            self.apply_preferences(ctx);
+        }
+
+        let notifications = pending_notifications(&self.projects, &self.time_zones, Utc::now());
+        let move_targets: Vec<(Uuid, String)> = self
+            .projects
+            .iter()
+            .map(|project| (project.get_uuid(), project.project_name.clone()))
+            .collect();
+        if self.desktop_notifications {
+            self.notification_center
+                .send_desktop_notifications(&notifications);
+        }
+        // keep checking reminders while the app sits idle
+        ctx.request_repaint_after(std::time::Duration::from_secs(60));
+
+        let text_has_focus = ctx.memory(|memory| memory.focus().is_some());
+        if !text_has_focus && !self.keymap_window.is_recording() {
+            self.preferences
+                .keymap
+                .consume_pressed(ctx)
+                .into_iter()
+                .for_each(|command| self.run_command(command));
+        }
 
-            if !self.add_project_open {
-                if ui.button("Create new project").clicked() {
-                    self.add_project_open = true;
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.current_view, PlannerView::Dashboard, "Dashboard");
+                ui.selectable_value(&mut self.current_view, PlannerView::Projects, "Projects");
+                ui.separator();
+                if ui
+                    .button("Commands")
+                    .on_hover_text(
+                        self.preferences
+                            .keymap
+                            .shortcut_text(ctx, Command::OpenCommandPalette),
+                    )
+                    .clicked()
+                {
+                    self.command_palette.toggle();
+                }
+                if ui.button("Shortcuts").clicked() {
+                    self.keymap_window.open = true;
+                }
+                if ui.button("Settings").clicked() {
+                    self.settings_window.open = true;
+                }
+                if !self.add_project_open {
+                    ui.separator();
+                    if ui.button("Create new project").clicked() {
+                        self.open_add_project();
+                    }
                 }
                 ui.separator();
-            }
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.search_query)
+                        .hint_text("Search projects and tasks"),
+                );
+                if !self.search_query.is_empty() && ui.button("Clear").clicked() {
+                    self.search_query.clear();
+                }
+                ui.separator();
+                if let Some((project_index, task)) =
+                    self.quick_add.show(ui, &self.projects, &self.time_zones)
+                {
+                    let project_uuid = self.projects[project_index].get_uuid();
+                    self.history.record(
+                        format!("Add task \"{}\"", task.text),
+                        &mut self.projects,
+                        &[project_uuid],
+                        |projects| projects[project_index].tasks.push(task),
+                    );
+                }
+                ui.separator();
+                self.tag_filter
+                    .show_menu(ui, &self.projects, &self.tag_registry);
+                self.assignee_filter.show_menu(ui, &self.people);
+                if ui.button("People").clicked() {
+                    self.people_manager.open = true;
+                }
+                if ui.button("Workload").clicked() {
+                    self.workload_window.open = true;
+                }
+                if ui.button("Manage tags").clicked() {
+                    self.tag_manager.open = true;
+                }
+                if ui.button("Timesheet").clicked() {
+                    self.timesheet.open = true;
+                }
+                if ui.button("Work calendar").clicked() {
+                    self.calendar_window.open = true;
+                }
+                if ui.button("Time zones").clicked() {
+                    self.time_zone_window_open = true;
+                }
+                if ui.button("Attachments").clicked() {
+                    self.attachment_manager.open = true;
+                }
+                ui.separator();
+                let undo_label = self.history.undo_label().map(|label| label.to_string());
+                if ui
+                    .add_enabled(undo_label.is_some(), egui::Button::new("Undo"))
+                    .on_hover_text(undo_label.unwrap_or_default())
+                    .clicked()
+                {
+                    self.history.undo(&mut self.projects);
+                }
+                let redo_label = self.history.redo_label().map(|label| label.to_string());
+                if ui
+                    .add_enabled(redo_label.is_some(), egui::Button::new("Redo"))
+                    .on_hover_text(redo_label.unwrap_or_default())
+                    .clicked()
+                {
+                    self.history.redo(&mut self.projects);
+                }
+                let selected_count = transfer::selected_tasks(&self.projects).len();
+                if selected_count > 0 {
+                    ui.separator();
+                    if ui.button(format!("Cut {} tasks", selected_count)).clicked() {
+                        let touched: Vec<Uuid> = transfer::selected_tasks(&self.projects)
+                            .iter()
+                            .map(|task| task.project)
+                            .collect();
+                        self.history.record(
+                            format!("Cut {} tasks", selected_count),
+                            &mut self.projects,
+                            &touched,
+                            |projects| self.task_clipboard.cut(projects),
+                        );
+                    }
+                    if ui
+                        .button(format!("Copy {} tasks", selected_count))
+                        .clicked()
+                    {
+                        self.task_clipboard.copy(&self.projects);
+                        transfer::clear_selection(&mut self.projects);
+                    }
+                    if ui.button("Clear selection").clicked() {
+                        transfer::clear_selection(&mut self.projects);
+                    }
+                    ui.separator();
+                }
+                let notifications_text = if notifications.is_empty() {
+                    "Notifications".to_string()
+                } else {
+                    format!("Notifications ({})", notifications.len())
+                };
+                if ui.button(notifications_text).clicked() {
+                    self.notification_center.open = true;
+                }
+            });
+            ui.separator();
 
-            ScrollArea::vertical()
-                .auto_shrink(true)
-                .scroll_bar_visibility(ScrollBarVisibility::VisibleWhenNeeded)
-                .show(ui, |ui| {
-                    self.projects
+            if !self.search_query.trim().is_empty() {
+                self.search_index.sync(&self.projects);
+                let results =
+                    self.search_index
+                        .search(&self.search_query, &self.projects, &self.tag_filter);
+                let activated = ScrollArea::vertical()
+                    .auto_shrink(true)
+                    .scroll_bar_visibility(ScrollBarVisibility::VisibleWhenNeeded)
+                    .show(ui, |ui| {
+                        show_search_results(ui, &results, &self.projects)
+                    })
+                    .inner;
+                if let Some(key) = activated {
+                    if let Some(project) = self
+                        .projects
                         .iter_mut()
-                        .enumerate()
-                        .for_each(|(project_index, project)| {
-                            match project.show(ui) {
-                                ProjectDisplayAction::EditClicked => {
+                        .find(|project| project.get_uuid() == key.project)
+                    {
+                        project.reveal();
+                    }
+                    self.current_view = PlannerView::Projects;
+                    self.search_query.clear();
+                }
+            } else {
+                ScrollArea::vertical()
+                    .auto_shrink(true)
+                    .scroll_bar_visibility(ScrollBarVisibility::VisibleWhenNeeded)
+                    .show(ui, |ui| match self.current_view {
+                        PlannerView::Dashboard => match show_dashboard(
+                            ui,
+                            &self.projects,
+                            &ProjectContext {
+                                tag_registry: &self.tag_registry,
+                                tag_filter: &self.tag_filter,
+                                calendar: &self.work_calendar,
+                                time_zones: &self.time_zones,
+                                task_clipboard: &self.task_clipboard,
+                                move_targets: &move_targets,
+                                attachments_dir: self.attachments_dir.as_deref(),
+                                people: &self.people,
+                                assignee_filter: &self.assignee_filter,
+                                task_cursor: &self.task_cursor,
+                            },
+                        ) {
+                            DashboardAction::EditProject(uuid) => {
+                                if let Some(project_index) = self
+                                    .projects
+                                    .iter()
+                                    .position(|project| project.get_uuid() == uuid)
+                                {
                                     self.editing_project = true;
                                     self.editing_project_index = project_index;
                                 }
-                                ProjectDisplayAction::None => {}
-                                ProjectDisplayAction::CloseEditWindow => {}
                             }
+                            DashboardAction::None => {}
+                        },
+                        PlannerView::Projects => {
+                            let mut started_timer = None;
+                            let mut paste_into = None;
+                            let mut dropped_on = None;
+                            let mut reordered = None;
+                            self.project_list.show(ui, &self.projects, &self.time_zones);
                             ui.separator();
-                        });
-                });
+                            let context = ProjectContext {
+                                tag_registry: &self.tag_registry,
+                                tag_filter: &self.tag_filter,
+                                calendar: &self.work_calendar,
+                                time_zones: &self.time_zones,
+                                task_clipboard: &self.task_clipboard,
+                                move_targets: &move_targets,
+                                attachments_dir: self.attachments_dir.as_deref(),
+                                people: &self.people,
+                                assignee_filter: &self.assignee_filter,
+                                task_cursor: &self.task_cursor,
+                            };
+                            let visible: Vec<usize> = self
+                                .project_list
+                                .order(&self.projects, &self.time_zones)
+                                .into_iter()
+                                .filter(|project_index| {
+                                    let project = &self.projects[*project_index];
+                                    self.tag_filter.matches_project(project)
+                                        && self.assignee_filter.matches_project(project)
+                                })
+                                .collect();
+                            let groups = self.folders.group_projects(&self.projects, &visible);
+                            let folder_stats = self.folders.stats(
+                                &self.projects,
+                                &groups,
+                                self.time_zones.display_today(),
+                            );
+                            let mut shown_tasks = vec![];
+                            let folder_action = self.folders.show(
+                                ui,
+                                &groups,
+                                &folder_stats,
+                                &self.time_zones,
+                                &mut |ui, project_index| {
+                                    let project = &mut self.projects[project_index];
+                                    shown_tasks.extend(
+                                        project
+                                            .tasks
+                                            .iter()
+                                            .filter(|task| {
+                                                task_shown(
+                                                    project,
+                                                    task,
+                                                    &self.tag_filter,
+                                                    &self.assignee_filter,
+                                                )
+                                            })
+                                            .map(|task| TaskRef {
+                                                project: project.get_uuid(),
+                                                task: task.get_uuid(),
+                                            }),
+                                    );
+                                    match project.show(ui, &context) {
+                                        ProjectDisplayAction::EditClicked => {
+                                            self.editing_project = true;
+                                            self.editing_project_index = project_index;
+                                        }
+                                        ProjectDisplayAction::TimerStarted(task_uuid) => {
+                                            started_timer = Some(task_uuid);
+                                        }
+                                        ProjectDisplayAction::None => {}
+                                        ProjectDisplayAction::CloseEditWindow => {}
+                                        ProjectDisplayAction::SaveAsTemplate(_) => {}
+                                        ProjectDisplayAction::Restructure(_) => {}
+                                        ProjectDisplayAction::PasteTasks => {
+                                            paste_into = Some(project.get_uuid());
+                                        }
+                                        ProjectDisplayAction::TasksDropped(kind) => {
+                                            dropped_on = Some((project.get_uuid(), kind));
+                                        }
+                                        ProjectDisplayAction::BulkEdit(_) => {}
+                                        ProjectDisplayAction::ReorderTask { task, to } => {
+                                            reordered = Some((project.get_uuid(), task, to));
+                                        }
+                                    }
+                                    ui.separator();
+                                },
+                            );
+                            self.shown_tasks = shown_tasks;
+                            self.task_cursor.scroll = false;
+                            match folder_action {
+                                FolderAction::MoveProject { project, folder } => {
+                                    self.history.record(
+                                        "Move project to folder",
+                                        &mut self.projects,
+                                        &[project],
+                                        |projects| {
+                                            if let Some(project) = projects
+                                                .iter_mut()
+                                                .find(|other| other.get_uuid() == project)
+                                            {
+                                                project.folder = folder;
+                                            }
+                                        },
+                                    );
+                                }
+                                FolderAction::DeleteFolder(folder) => {
+                                    self.folders.delete(folder, &mut self.projects);
+                                }
+                                FolderAction::None => {}
+                            }
+                            if let Some(task_uuid) = started_timer {
+                                stop_other_timers(&mut self.projects, task_uuid);
+                            }
+                            if let Some((project_uuid, task, to)) = reordered {
+                                self.move_task(project_uuid, task, to);
+                            }
+                            if let Some(uuid) = paste_into {
+                                let (tasks, kind) = self.task_clipboard.paste(&self.projects);
+                                self.transfer_warnings = self.history.record(
+                                    format!("Paste {} tasks", tasks.len()),
+                                    &mut self.projects,
+                                    &[uuid],
+                                    |projects| {
+                                        projects
+                                            .iter_mut()
+                                            .find(|project| project.get_uuid() == uuid)
+                                            .map(|project| {
+                                                transfer::insert_tasks(project, tasks, kind)
+                                            })
+                                            .unwrap_or_default()
+                                    },
+                                );
+                            }
+                            if let Some((destination, kind)) = dropped_on {
+                                if let Some(payload) = transfer::drag_payload(ctx) {
+                                    let tasks = transfer::dragged_tasks(&self.projects, &payload);
+                                    let verb = match kind {
+                                        TransferKind::Move => "Move",
+                                        TransferKind::Copy => "Copy",
+                                    };
+                                    let mut touched: Vec<Uuid> =
+                                        tasks.iter().map(|task| task.project).collect();
+                                    touched.push(destination);
+                                    self.transfer_warnings = self.history.record(
+                                        format!("{} {} tasks", verb, tasks.len()),
+                                        &mut self.projects,
+                                        &touched,
+                                        |projects| {
+                                            let warnings = transfer::transfer_tasks(
+                                                projects,
+                                                &tasks,
+                                                destination,
+                                                kind,
+                                            );
+                                            if kind == TransferKind::Move {
+                                                transfer::clear_selection(projects);
+                                            }
+                                            warnings
+                                        },
+                                    );
+                                }
+                            }
+                        }
+                    });
+            }
 
             self.projects
                 .retain(|project| project.marked_for_deletion.not());
@@ -196,28 +916,64 @@ impl eframe::App for ProjectPlanner {
             ui.with_layout(Layout::bottom_up(egui::Align::BOTTOM), |ui| {
                 ui.horizontal(|ui| {
                     if ui.button("Save & quit").clicked() {
-                        if let Some(storage) = _frame.storage_mut() {
+                        if let Some(storage) = frame.storage_mut() {
                             self.save(storage);
                             ctx.send_viewport_cmd(ViewportCommand::Close);
                         }
                     }
-                    if !self.showing_about_page {
-                        if ui.button("About").clicked() {
-                            self.showing_about_page = true;
-                        }
+                    if !self.showing_about_page && ui.button("About").clicked() {
+                        self.showing_about_page = true;
                     }
                 });
 
             });
         });
 
+        let mut restructure = None;
+        let mut bulk_edit = None;
+        let mut reordered = None;
         if self.editing_project {
             if let Some(project) = self.projects.get_mut(self.editing_project_index) {
-                match project.show_edit_window(ctx) {
+                let context = ProjectContext {
+                    tag_registry: &self.tag_registry,
+                    tag_filter: &self.tag_filter,
+                    calendar: &self.work_calendar,
+                    time_zones: &self.time_zones,
+                    task_clipboard: &self.task_clipboard,
+                    move_targets: &move_targets,
+                    attachments_dir: self.attachments_dir.as_deref(),
+                    people: &self.people,
+                    assignee_filter: &self.assignee_filter,
+                    task_cursor: &self.task_cursor,
+                };
+                match project.show_edit_window(ctx, &context) {
                     ProjectDisplayAction::EditClicked => {}
                     ProjectDisplayAction::CloseEditWindow => {
                         self.editing_project = false;
                     }
+                    ProjectDisplayAction::TimerStarted(_) => {}
+                    ProjectDisplayAction::SaveAsTemplate(name) => {
+                        let template = ProjectTemplate::from_project(name, project);
+                        match self
+                            .templates
+                            .iter_mut()
+                            .find(|existing| existing.name == template.name)
+                        {
+                            Some(existing) => *existing = template,
+                            None => self.templates.push(template),
+                        }
+                    }
+                    ProjectDisplayAction::Restructure(mode) => {
+                        restructure = Some(mode);
+                    }
+                    ProjectDisplayAction::PasteTasks => {}
+                    ProjectDisplayAction::TasksDropped(_) => {}
+                    ProjectDisplayAction::BulkEdit(bulk_action) => {
+                        bulk_edit = Some(bulk_action);
+                    }
+                    ProjectDisplayAction::ReorderTask { task, to } => {
+                        reordered = Some((project.get_uuid(), task, to));
+                    }
                     ProjectDisplayAction::None => {}
                 }
             } else {
@@ -226,8 +982,186 @@ impl eframe::App for ProjectPlanner {
             }
         }
 
+        if let Some(bulk_action) = bulk_edit {
+            self.apply_bulk_action(bulk_action);
+        }
+        if let Some((project_uuid, task, to)) = reordered {
+            self.move_task(project_uuid, task, to);
+        }
+
+        if let Some(payload) = transfer::drag_payload(ctx) {
+            transfer::show_drag_preview(
+                ctx,
+                transfer::dragged_tasks(&self.projects, &payload).len(),
+            );
+        }
+        transfer::end_drag_if_released(ctx);
+        if let Some(project) = folder::project_drag_payload(ctx).and_then(|uuid| {
+            self.projects
+                .iter()
+                .find(|project| project.get_uuid() == uuid)
+        }) {
+            folder::show_project_drag_preview(ctx, &project.project_name);
+        }
+        folder::end_project_drag_if_released(ctx);
+
+        if !self.transfer_warnings.is_empty() {
+            let mut open = true;
+            egui::Window::new("Tasks outside project dates")
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    self.transfer_warnings.iter().for_each(|warning| {
+                        ui.label(format!(
+                            "\"{}\" is due outside the dates of \"{}\"",
+                            warning.task_text, warning.project_name
+                        ));
+                    });
+                });
+            if !open {
+                self.transfer_warnings.clear();
+            }
+        }
+
+        if let Some(mode) = restructure {
+            if let Some(project) = self.projects.get(self.editing_project_index) {
+                self.restructure_window.open(mode, project, &self.projects);
+            }
+        }
+        // merging removes a project, so find the project being edited again afterwards
+        let editing_uuid = self
+            .projects
+            .get(self.editing_project_index)
+            .map(|project| project.get_uuid());
+        self.restructure_window
+            .show(ctx, &mut self.projects, &mut self.history);
+        if let Some(project_index) = editing_uuid.and_then(|uuid| {
+            self.projects
+                .iter()
+                .position(|project| project.get_uuid() == uuid)
+        }) {
+            self.editing_project_index = project_index;
+        }
+
+        if let Some(choice) =
+            self.command_palette
+                .show(ctx, &self.preferences.keymap, &self.projects)
+        {
+            match choice {
+                PaletteChoice::Command(command) => self.run_command(command),
+                PaletteChoice::Project(uuid) => {
+                    if let Some(project) = self
+                        .projects
+                        .iter_mut()
+                        .find(|project| project.get_uuid() == uuid)
+                    {
+                        project.reveal();
+                        self.current_view = PlannerView::Projects;
+                        self.search_query.clear();
+                    }
+                }
+            }
+        }
+        self.keymap_window.show(ctx, &mut self.preferences.keymap);
+        let default_attachments_dir = attachment::attachments_dir(crate::APP_NAME);
+        let data_dir = self.preferences.data_dir.clone();
+        if self.settings_window.show(
+            ctx,
+            &mut self.preferences,
+            default_attachments_dir.as_deref(),
+            &self.time_zones,
+        ) {
+            self.apply_preferences(ctx);
+            // move the project data over right away instead of at the next autosave
+            if self.preferences.data_dir != data_dir {
+                if let Some(storage) = frame.storage_mut() {
+                    self.save(storage);
+                }
+            }
+        }
+        self.tag_manager
+            .show(ctx, &mut self.tag_registry, &mut self.projects);
+        self.timesheet.show(ctx, &self.projects, &self.time_zones);
+        self.calendar_window
+            .show(ctx, &mut self.work_calendar, &self.time_zones);
+        self.attachment_manager
+            .show(ctx, &self.projects, self.attachments_dir.as_deref());
+        self.people_manager.show(
+            ctx,
+            &mut self.people,
+            &mut self.projects,
+            self.time_zones.display_today(),
+        );
+        if let Some(suggestion) = self.workload_window.show(
+            ctx,
+            &self.projects,
+            &self.people,
+            &self.work_calendar,
+            &self.time_zones,
+        ) {
+            self.history.record(
+                format!(
+                    "Move \"{}\" to {}",
+                    suggestion.task_text,
+                    self.time_zones.format_date(suggestion.to)
+                ),
+                &mut self.projects,
+                &[suggestion.task.project],
+                |projects| {
+                    if let Some(task) = projects
+                        .iter_mut()
+                        .filter(|project| project.get_uuid() == suggestion.task.project)
+                        .flat_map(|project| project.tasks.iter_mut())
+                        .find(|task| task.get_uuid() == suggestion.task.task)
+                    {
+                        task.set_due_date(Some(suggestion.to));
+                    }
+                },
+            );
+        }
+        egui::Window::new("Time zones")
+            .open(&mut self.time_zone_window_open)
+            .show(ctx, |ui| {
+                ui.label("Dates and times are stored in UTC and shown in the display time zone.");
+                self.time_zones.show_editor(ui);
+            });
+        match self.notification_center.show(
+            ctx,
+            &mut self.projects,
+            &notifications,
+            &mut self.desktop_notifications,
+        ) {
+            NotificationCenterAction::GoToProject(uuid) => {
+                if let Some(project) = self
+                    .projects
+                    .iter_mut()
+                    .find(|project| project.get_uuid() == uuid)
+                {
+                    project.reveal();
+                    self.current_view = PlannerView::Projects;
+                    self.search_query.clear();
+                }
+            }
+            NotificationCenterAction::None => {}
+        }
+
         if self.add_project_open {
             egui::Window::new("Add new project").show(ctx, |ui| {
+                if let TemplatePickerAction::Selected(index) = self.template_picker.show(
+                    ui,
+                    &mut self.templates,
+                    &[
+                        &self.project_name_selected,
+                        &self.project_description_selected,
+                    ],
+                ) {
+                    if let Some(template) = self.templates.get(index) {
+                        self.project_name_selected = template.project_name.clone();
+                        self.project_description_selected = template.description.clone();
+                        self.end_date_selected =
+                            template.end_date(self.start_date_selected, &self.work_calendar);
+                    }
+                }
+                ui.separator();
                 ui.horizontal(|ui| {
                     ui.label("Project name:");
                     ui.text_edit_singleline(&mut self.project_name_selected);
@@ -258,20 +1192,32 @@ impl eframe::App for ProjectPlanner {
                 match Project::validity_check_new(
                     self.start_date_selected,
                     self.end_date_selected,
-                    self.project_name_selected.clone(),
+                    fill_placeholders(&self.project_name_selected, &self.template_picker.values),
                     &self.projects,
-                    self.project_description_selected.clone(),
+                    fill_placeholders(
+                        &self.project_description_selected,
+                        &self.template_picker.values,
+                    ),
                 ) {
-                    Ok(project) => {
-                        if ui.button("Add project").clicked() {
-                            if !self
+                    Ok(mut project) => {
+                        if ui.button("Add project").clicked()
+                            && !self
                                 .projects
                                 .iter()
                                 .any(|any_project| any_project.project_name == project.project_name)
+                        {
+                            if let Some(template) =
+                                self.template_picker.selected_template(&self.templates)
                             {
-                                self.projects.push(project);
-                                self.project_name_selected.clear();
+                                template.apply_to(
+                                    &mut project,
+                                    &self.template_picker.values,
+                                    &self.work_calendar,
+                                );
                             }
+                            self.projects.push(project);
+                            self.project_name_selected.clear();
+                            self.template_picker = TemplatePicker::default();
                         }
                     }
                     Err(validity_error) => {
@@ -291,7 +1237,7 @@ impl eframe::App for ProjectPlanner {
                     true
                 }
                 Some(seen_version) => {
-                    self_update::version::bump_is_greater(&update.version,&seen_version).unwrap_or(false)
+                    self_update::version::bump_is_greater(&update.version,seen_version).unwrap_or(false)
                 }
             };
 
@@ -391,7 +1337,10 @@ impl eframe::App for ProjectPlanner {
                 ui.separator();
                 ui.label(format!("Cargo crate version: {}", cargo_crate_version!()));
                 ui.separator();
-                ui.label(format!("Last open date: {}", self.last_open));
+                ui.label(format!(
+                    "Last open date: {}",
+                    self.time_zones.format(&self.last_open, "%Y-%m-%d %H:%M:%S %:z")
+                ));
                 ui.label(format!(
                     "Auto update seen version: {}",
                     self.auto_update_seen_version.clone().unwrap_or_default()
@@ -408,10 +1357,28 @@ impl eframe::App for ProjectPlanner {
                 }
             });
         }
+
+        // edits only happen in response to input, the search index and workload catch up on the
+        // next frame
+        if ctx.input(|input| input.events.iter().any(search::is_editing_input)) {
+            self.search_index.mark_dirty();
+            self.workload_window.mark_dirty();
+            if !self.search_query.trim().is_empty() || self.workload_window.open {
+                ctx.request_repaint();
+            }
+        }
     }
 
     fn save(&mut self, storage: &mut dyn Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
+        // fall back to the app storage if the data directory cannot be written to
+        let saved_to_data_dir = self
+            .preferences
+            .project_file()
+            .is_some_and(|file| settings::write_data_file(&file, self).is_ok());
+        if !saved_to_data_dir {
+            eframe::set_value(storage, eframe::APP_KEY, self);
+        }
+        eframe::set_value(storage, PREFERENCES_KEY, &self.preferences);
     }
 
     fn persist_egui_memory(&self) -> bool {
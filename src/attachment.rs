@@ -0,0 +1,464 @@
+use crate::project::Project;
+use chrono::{DateTime, Utc};
+use egui::{Color32, Ui};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "bmp", "webp"];
+
+/// Where an attachment points to
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub enum AttachmentSource {
+    /// A file copied into the attachments directory, stored under its content hash
+    File {
+        hash: String,
+        extension: String,
+    },
+    Url(String),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Attachment {
+    /// Original file name or link text
+    pub name: String,
+    pub source: AttachmentSource,
+    pub added: DateTime<Utc>,
+}
+
+impl Attachment {
+    pub fn url(name: String, url: String) -> Self {
+        Self {
+            name,
+            source: AttachmentSource::Url(url),
+            added: Utc::now(),
+        }
+    }
+
+    /// Copy a file into the attachments directory. Files with the same content share one copy.
+    pub fn import_file(attachments_dir: &Path, file: &Path) -> std::io::Result<Self> {
+        let contents = fs::read(file)?;
+        let hash = Sha256::digest(&contents)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        let extension = file
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let source = AttachmentSource::File { hash, extension };
+        let stored = stored_file_name(&source).map(|name| attachments_dir.join(name));
+        if let Some(stored) = stored.filter(|stored| !stored.exists()) {
+            fs::create_dir_all(attachments_dir)?;
+            fs::write(stored, contents)?;
+        }
+        Ok(Self {
+            name: file
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| file.display().to_string()),
+            source,
+            added: Utc::now(),
+        })
+    }
+
+    /// Path of the stored copy of a file attachment
+    pub fn path(&self, attachments_dir: &Path) -> Option<PathBuf> {
+        stored_file_name(&self.source).map(|name| attachments_dir.join(name))
+    }
+
+    pub fn is_image(&self) -> bool {
+        match &self.source {
+            AttachmentSource::File { extension, .. } => {
+                IMAGE_EXTENSIONS.contains(&extension.as_str())
+            }
+            AttachmentSource::Url(_) => false,
+        }
+    }
+}
+
+/// Name of the stored copy of a file attachment, its content hash with the original extension
+fn stored_file_name(source: &AttachmentSource) -> Option<String> {
+    match source {
+        AttachmentSource::File { hash, extension } if extension.is_empty() => Some(hash.clone()),
+        AttachmentSource::File { hash, extension } => Some(format!("{}.{}", hash, extension)),
+        AttachmentSource::Url(_) => None,
+    }
+}
+
+/// Whether a file name is one `stored_file_name` gives out: a hex SHA-256 hash, optionally
+/// followed by an extension
+fn is_stored_file_name(name: &str) -> bool {
+    let (hash, extension) = name.split_once('.').unwrap_or((name, ""));
+    hash.len() == 64
+        && hash
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase())
+        && !extension.contains('.')
+}
+
+/// The attachments directory inside the data directory of the app
+pub fn attachments_dir(app_id: &str) -> Option<PathBuf> {
+    eframe::storage_dir(app_id).map(|dir| dir.join("attachments"))
+}
+
+/// Copy every stored attachment file from one attachments directory to another, so attachments
+/// still resolve after switching directories. Other files and files already in the new directory
+/// are left alone. Returns how many files were copied.
+pub fn copy_attachments(from: &Path, to: &Path) -> std::io::Result<usize> {
+    fs::create_dir_all(to)?;
+    let Ok(entries) = fs::read_dir(from) else {
+        return Ok(0);
+    };
+    let mut copied = 0;
+    for entry in entries {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if is_stored_file_name(&entry.file_name().to_string_lossy())
+            && entry.path().is_file()
+            && !target.exists()
+        {
+            fs::copy(entry.path(), target)?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// Open a file with the default app of the operating system
+pub fn open_in_default_app(path: &Path) -> std::io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = Command::new("xdg-open");
+    command.arg(path).spawn().map(|_| ())
+}
+
+/// Every attachment of every project and task
+fn all_attachments(projects: &[Project]) -> impl Iterator<Item = &Attachment> {
+    projects.iter().flat_map(|project| {
+        project.attachments.iter().chain(
+            project
+                .tasks
+                .iter()
+                .flat_map(|task| task.attachments.iter()),
+        )
+    })
+}
+
+/// Stored attachment files in the attachments directory that no project or task refers to
+/// anymore. Files the app did not store are never included.
+pub fn orphaned_files(attachments_dir: &Path, projects: &[Project]) -> Vec<PathBuf> {
+    let referenced: HashSet<String> = all_attachments(projects)
+        .filter_map(|attachment| stored_file_name(&attachment.source))
+        .collect();
+    let Ok(entries) = fs::read_dir(attachments_dir) else {
+        return vec![];
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            is_stored_file_name(&name) && !referenced.contains(&name)
+        })
+        .map(|entry| entry.path())
+        .collect()
+}
+
+/// Inputs for adding attachments
+#[derive(Default, Clone)]
+pub struct AttachmentForm {
+    file_path: String,
+    url: String,
+    url_name: String,
+    status: Option<Result<String, String>>,
+}
+
+impl AttachmentForm {
+    /// Show the attachments with buttons to remove them, and inputs to attach a file or link
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        attachments: &mut Vec<Attachment>,
+        attachments_dir: Option<&Path>,
+    ) {
+        let mut removed = None;
+        attachments
+            .iter()
+            .enumerate()
+            .for_each(|(attachment_index, attachment)| {
+                ui.horizontal(|ui| {
+                    if let Some(err) = show_attachment(ui, attachment, attachments_dir) {
+                        self.status = Some(Err(err));
+                    }
+                    if ui.button("Remove").clicked() {
+                        removed = Some(attachment_index);
+                    }
+                });
+            });
+        if let Some(attachment_index) = removed {
+            attachments.remove(attachment_index);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.file_path)
+                    .hint_text("Path to a file")
+                    .desired_width(200.0),
+            );
+            if ui
+                .add_enabled(
+                    attachments_dir.is_some() && !self.file_path.trim().is_empty(),
+                    egui::Button::new("Attach file"),
+                )
+                .on_disabled_hover_text("Needs a file path and an attachments directory")
+                .clicked()
+            {
+                if let Some(attachments_dir) = attachments_dir {
+                    self.status = Some(
+                        Attachment::import_file(attachments_dir, Path::new(self.file_path.trim()))
+                            .map(|attachment| {
+                                let message = format!("Attached {}", attachment.name);
+                                attachments.push(attachment);
+                                self.file_path.clear();
+                                message
+                            })
+                            .map_err(|err| format!("Attaching failed: {}", err)),
+                    );
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.url)
+                    .hint_text("https://")
+                    .desired_width(200.0),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut self.url_name)
+                    .hint_text("Name (optional)")
+                    .desired_width(100.0),
+            );
+            if ui
+                .add_enabled(
+                    !self.url.trim().is_empty(),
+                    egui::Button::new("Attach link"),
+                )
+                .clicked()
+            {
+                let url = self.url.trim().to_string();
+                let name = if self.url_name.trim().is_empty() {
+                    url.clone()
+                } else {
+                    self.url_name.trim().to_string()
+                };
+                attachments.push(Attachment::url(name, url));
+                self.url.clear();
+                self.url_name.clear();
+                self.status = None;
+            }
+        });
+        self.show_status(ui);
+    }
+
+    /// Show the attachments without a way to edit them, and any error opening one of them
+    pub fn show_list(
+        &mut self,
+        ui: &mut Ui,
+        attachments: &[Attachment],
+        attachments_dir: Option<&Path>,
+    ) {
+        if let Some(err) = show_attachment_list(ui, attachments, attachments_dir) {
+            self.status = Some(Err(err));
+        }
+        if matches!(self.status, Some(Err(_))) {
+            self.show_status(ui);
+        }
+    }
+
+    fn show_status(&self, ui: &mut Ui) {
+        match &self.status {
+            None => {}
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(message)) => {
+                ui.colored_label(Color32::LIGHT_RED, message);
+            }
+        }
+    }
+}
+
+/// Show one attachment, with a thumbnail for images and a way to open it. Returns an error
+/// message if opening the file failed.
+pub fn show_attachment(
+    ui: &mut Ui,
+    attachment: &Attachment,
+    attachments_dir: Option<&Path>,
+) -> Option<String> {
+    match &attachment.source {
+        AttachmentSource::Url(url) => {
+            ui.hyperlink_to(&attachment.name, url);
+        }
+        AttachmentSource::File { .. } => {
+            let Some(path) = attachments_dir.and_then(|dir| attachment.path(dir)) else {
+                ui.label(&attachment.name);
+                return None;
+            };
+            if !path.exists() {
+                ui.colored_label(Color32::LIGHT_RED, format!("{} (missing)", attachment.name));
+                return None;
+            }
+            if attachment.is_image() {
+                ui.add(
+                    egui::Image::new(format!("file://{}", path.display()))
+                        .max_height(48.0)
+                        .max_width(64.0),
+                );
+            }
+            if ui
+                .link(&attachment.name)
+                .on_hover_text("Open in the default app")
+                .clicked()
+            {
+                return open_in_default_app(&path)
+                    .err()
+                    .map(|err| format!("Opening {} failed: {}", attachment.name, err));
+            }
+        }
+    }
+    None
+}
+
+/// Show every attachment in a list, without editing. Returns an error message if opening a file
+/// failed.
+pub fn show_attachment_list(
+    ui: &mut Ui,
+    attachments: &[Attachment],
+    attachments_dir: Option<&Path>,
+) -> Option<String> {
+    let mut error = None;
+    attachments.iter().for_each(|attachment| {
+        if let Some(err) = ui
+            .horizontal(|ui| show_attachment(ui, attachment, attachments_dir))
+            .inner
+        {
+            error = Some(err);
+        }
+    });
+    error
+}
+
+/// State for the window listing the attachments directory and removing unused files
+#[derive(Default)]
+pub struct AttachmentManager {
+    pub open: bool,
+    /// Unused files found when the window was opened or rescanned
+    orphans: Option<Vec<PathBuf>>,
+    status: Option<String>,
+}
+
+impl AttachmentManager {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        projects: &[Project],
+        attachments_dir: Option<&Path>,
+    ) {
+        if !self.open {
+            self.orphans = None;
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Attachments")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let Some(attachments_dir) = attachments_dir else {
+                    ui.label("No attachments directory is available on this system.");
+                    return;
+                };
+                ui.label(format!("Stored in {}", attachments_dir.display()));
+                ui.label(format!(
+                    "{} attachments in projects and tasks",
+                    all_attachments(projects).count()
+                ));
+                let orphans = self
+                    .orphans
+                    .get_or_insert_with(|| orphaned_files(attachments_dir, projects));
+                let mut rescan = false;
+                ui.horizontal(|ui| {
+                    if orphans.is_empty() {
+                        ui.weak("No unused files");
+                    } else if ui
+                        .button(format!("Delete {} unused files", orphans.len()))
+                        .on_hover_text(
+                            "Double click to delete the stored files no project or task is \
+                             attached to anymore, undoing a removed attachment afterwards leaves \
+                             it missing",
+                        )
+                        .double_clicked()
+                    {
+                        let removed = orphans
+                            .iter()
+                            .filter(|orphan| fs::remove_file(orphan).is_ok())
+                            .count();
+                        self.status = Some(format!("Deleted {} unused files", removed));
+                        rescan = true;
+                    }
+                    if ui.button("Rescan").clicked() {
+                        rescan = true;
+                    }
+                });
+                if rescan {
+                    self.orphans = None;
+                }
+                if let Some(status) = &self.status {
+                    ui.label(status);
+                }
+            });
+        self.open = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_stored_file_names() {
+        let hash = "0123456789abcdef".repeat(4);
+        assert!(is_stored_file_name(&hash));
+        assert!(is_stored_file_name(&format!("{}.png", hash)));
+        assert!(!is_stored_file_name(&hash.to_uppercase()));
+        assert!(!is_stored_file_name(&format!("{}.tar.gz", hash)));
+        assert!(!is_stored_file_name(&hash[1..]));
+        assert!(!is_stored_file_name("notes.txt"));
+    }
+
+    #[test]
+    fn only_unreferenced_stored_files_are_orphans() {
+        let dir = std::env::temp_dir().join(format!("attachments-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("photo.png");
+        fs::write(&source, b"photo").unwrap();
+        let attachment = Attachment::import_file(&dir, &source).unwrap();
+        let orphan = dir.join(format!("{}.txt", "f".repeat(64)));
+        fs::write(&orphan, b"old").unwrap();
+
+        let mut project = Project::default();
+        project.attachments.push(attachment);
+        assert_eq!(orphaned_files(&dir, &[project]), vec![orphan]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,328 @@
+use crate::ical;
+use crate::timezone::TimeZoneSettings;
+use chrono::{Datelike, Days, Local, NaiveDate, Weekday};
+use egui::{Color32, Ui};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+/// Which days of the week are worked and which dates are holidays, used for all business day
+/// arithmetic
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct WorkCalendar {
+    /// Working days of the week, starting with monday
+    pub workweek: [bool; 7],
+    /// Non working dates, kept sorted by date
+    pub holidays: Vec<Holiday>,
+}
+
+impl Default for WorkCalendar {
+    fn default() -> Self {
+        Self {
+            workweek: [true, true, true, true, true, false, false],
+            holidays: vec![],
+        }
+    }
+}
+
+impl WorkCalendar {
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        self.workweek[date.weekday().num_days_from_monday() as usize]
+            && self
+                .holidays
+                .binary_search_by_key(&date, |holiday| holiday.date)
+                .is_err()
+    }
+
+    /// Number of working days from `from` to `to`, both inclusive, zero if `to` is before `from`
+    pub fn count_working_days(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        if to < from || !self.workweek.contains(&true) {
+            return 0;
+        }
+        let days = (to - from).num_days() + 1;
+        let full_weeks = days / 7;
+        let working_per_week = self.workweek.iter().filter(|worked| **worked).count() as i64;
+        let remainder = from
+            .iter_days()
+            .skip((full_weeks * 7) as usize)
+            .take((days % 7) as usize)
+            .filter(|day| self.workweek[day.weekday().num_days_from_monday() as usize])
+            .count() as i64;
+        let holidays = self
+            .holidays
+            .iter()
+            .filter(|holiday| holiday.date >= from && holiday.date <= to)
+            .filter(|holiday| self.workweek[holiday.date.weekday().num_days_from_monday() as usize])
+            .count() as i64;
+        full_weeks * working_per_week + remainder - holidays
+    }
+
+    /// Working days left until `due`, counting from the day after `today` up to and including the
+    /// due date. Negative when the due date has passed.
+    pub fn working_days_until(&self, today: NaiveDate, due: NaiveDate) -> i64 {
+        if due >= today {
+            today
+                .succ_opt()
+                .map(|tomorrow| self.count_working_days(tomorrow, due))
+                .unwrap_or_default()
+        } else {
+            -due.succ_opt()
+                .map(|after_due| self.count_working_days(after_due, today))
+                .unwrap_or_default()
+        }
+    }
+
+    /// Move a date forward (or backward for negative amounts) by a number of working days
+    pub fn add_working_days(&self, date: NaiveDate, working_days: i64) -> NaiveDate {
+        if !self.workweek.contains(&true) {
+            return date;
+        }
+        let mut current = date;
+        let mut remaining = working_days.abs();
+        while remaining > 0 {
+            let next = if working_days > 0 {
+                current.succ_opt()
+            } else {
+                current.pred_opt()
+            };
+            match next {
+                None => break,
+                Some(next) => current = next,
+            }
+            if self.is_working_day(current) {
+                remaining -= 1;
+            }
+        }
+        current
+    }
+
+    pub fn add_holiday(&mut self, date: NaiveDate, name: String) {
+        match self
+            .holidays
+            .binary_search_by_key(&date, |holiday| holiday.date)
+        {
+            Ok(index) => self.holidays[index].name = name,
+            Err(index) => self.holidays.insert(index, Holiday { date, name }),
+        }
+    }
+
+    /// Add every event in an iCalendar file as a holiday, returns how many days were added
+    pub fn import_ics(&mut self, ics: &str) -> usize {
+        let mut added = 0;
+        ical::import_events(ics).into_iter().for_each(|event| {
+            event
+                .start
+                .iter_days()
+                .take_while(|day| *day < event.end)
+                .for_each(|day| {
+                    self.add_holiday(day, event.summary.clone());
+                    added += 1;
+                });
+        });
+        added
+    }
+
+    pub fn show_editor(
+        &mut self,
+        ui: &mut Ui,
+        form: &mut HolidayForm,
+        time_zones: &TimeZoneSettings,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("Workweek:");
+            WEEKDAYS.iter().enumerate().for_each(|(index, weekday)| {
+                ui.checkbox(&mut self.workweek[index], weekday.to_string());
+            });
+        });
+
+        ui.collapsing(format!("Holidays ({})", self.holidays.len()), |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(150.0)
+                .show(ui, |ui| {
+                    let mut removed = None;
+                    self.holidays
+                        .iter()
+                        .enumerate()
+                        .for_each(|(index, holiday)| {
+                            ui.horizontal(|ui| {
+                                ui.label(time_zones.format_date(holiday.date));
+                                ui.label(&holiday.name);
+                                if ui.small_button("x").clicked() {
+                                    removed = Some(index);
+                                }
+                            });
+                        });
+                    if let Some(index) = removed {
+                        self.holidays.remove(index);
+                    }
+                });
+
+            ui.horizontal(|ui| {
+                ui.push_id("holiday_date", |ui| {
+                    ui.add(egui_extras::DatePickerButton::new(&mut form.date));
+                });
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.name)
+                        .hint_text("Holiday name")
+                        .desired_width(120.0),
+                );
+                if ui.button("Add holiday").clicked() {
+                    self.add_holiday(form.date, form.name.trim().to_string());
+                    form.name.clear();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.import_path)
+                        .hint_text("holidays.ics")
+                        .desired_width(160.0),
+                );
+                if ui.button("Import .ics").clicked() {
+                    form.import_status = Some(
+                        fs::read_to_string(&form.import_path)
+                            .map(|ics| format!("Imported {} holidays", self.import_ics(&ics)))
+                            .map_err(|err| format!("Import failed: {}", err)),
+                    );
+                }
+            });
+            match &form.import_status {
+                None => {}
+                Some(Ok(message)) => {
+                    ui.label(message);
+                }
+                Some(Err(message)) => {
+                    ui.colored_label(Color32::LIGHT_RED, message);
+                }
+            }
+        });
+    }
+}
+
+/// Editing state for adding and importing holidays
+#[derive(Clone)]
+pub struct HolidayForm {
+    date: NaiveDate,
+    name: String,
+    import_path: String,
+    import_status: Option<Result<String, String>>,
+}
+
+impl Default for HolidayForm {
+    fn default() -> Self {
+        Self {
+            date: Local::now().date_naive(),
+            name: "".to_string(),
+            import_path: "".to_string(),
+            import_status: None,
+        }
+    }
+}
+
+/// State for the workspace calendar window
+#[derive(Default)]
+pub struct CalendarWindow {
+    pub open: bool,
+    form: HolidayForm,
+}
+
+impl CalendarWindow {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        calendar: &mut WorkCalendar,
+        time_zones: &TimeZoneSettings,
+    ) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Work calendar")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label("Used for working day counts in every project without its own calendar.");
+                calendar.show_editor(ui, &mut self.form, time_zones);
+                let today = time_zones.display_today();
+                let next_week = today.checked_add_days(Days::new(7)).unwrap_or(today);
+                ui.weak(format!(
+                    "{} working days in the next 7 days",
+                    calendar.working_days_until(today, next_week)
+                ));
+            });
+        self.open = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn holidays_on_weekends_are_not_counted_twice() {
+        let mut calendar = WorkCalendar::default();
+        // Saturday
+        calendar.add_holiday(date(2024, 12, 28), "Weekend holiday".to_string());
+        assert_eq!(
+            calendar.count_working_days(date(2024, 12, 23), date(2024, 12, 29)),
+            5
+        );
+        assert_eq!(
+            calendar.working_days_until(date(2024, 12, 27), date(2024, 12, 30)),
+            1
+        );
+    }
+
+    #[test]
+    fn weekday_holidays_are_skipped() {
+        let mut calendar = WorkCalendar::default();
+        // Wednesday
+        calendar.add_holiday(date(2024, 12, 25), "Christmas".to_string());
+        assert_eq!(
+            calendar.count_working_days(date(2024, 12, 23), date(2024, 12, 29)),
+            4
+        );
+        assert_eq!(
+            calendar.add_working_days(date(2024, 12, 24), 1),
+            date(2024, 12, 26)
+        );
+        assert_eq!(
+            calendar.add_working_days(date(2024, 12, 26), -1),
+            date(2024, 12, 24)
+        );
+    }
+
+    #[test]
+    fn overdue_days_are_negative() {
+        let calendar = WorkCalendar::default();
+        assert_eq!(
+            calendar.working_days_until(date(2024, 12, 23), date(2024, 12, 19)),
+            -2
+        );
+        // Due friday, seen on saturday: no working day has passed since
+        assert_eq!(
+            calendar.working_days_until(date(2024, 12, 21), date(2024, 12, 20)),
+            0
+        );
+    }
+}
@@ -0,0 +1,506 @@
+use crate::project::Project;
+use crate::transfer::TaskRef;
+use egui::{Event, Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// An action that can be run from the command palette or bound to a keyboard shortcut
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Command {
+    OpenCommandPalette,
+    NewProject,
+    ShowDashboard,
+    ShowProjects,
+    Undo,
+    Redo,
+    /// Move the selected tasks one place up
+    MoveTasksUp,
+    /// Move the selected tasks one place down
+    MoveTasksDown,
+    /// Move the task cursor to the next task in the list
+    NextTask,
+    /// Move the task cursor to the previous task in the list
+    PreviousTask,
+    /// Complete or reopen the task under the cursor
+    ToggleTaskCompleted,
+    /// Open the task under the cursor in the project editor
+    EditTask,
+    OpenPeople,
+    OpenWorkload,
+    ManageTags,
+    OpenTimesheet,
+    OpenWorkCalendar,
+    OpenTimeZones,
+    OpenAttachments,
+    OpenNotifications,
+    OpenKeymap,
+    OpenSettings,
+}
+
+impl Command {
+    pub const ALL: [Command; 22] = [
+        Command::OpenCommandPalette,
+        Command::NewProject,
+        Command::ShowDashboard,
+        Command::ShowProjects,
+        Command::Undo,
+        Command::Redo,
+        Command::MoveTasksUp,
+        Command::MoveTasksDown,
+        Command::NextTask,
+        Command::PreviousTask,
+        Command::ToggleTaskCompleted,
+        Command::EditTask,
+        Command::OpenPeople,
+        Command::OpenWorkload,
+        Command::ManageTags,
+        Command::OpenTimesheet,
+        Command::OpenWorkCalendar,
+        Command::OpenTimeZones,
+        Command::OpenAttachments,
+        Command::OpenNotifications,
+        Command::OpenKeymap,
+        Command::OpenSettings,
+    ];
+
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            Command::OpenCommandPalette => "Command palette",
+            Command::NewProject => "Create new project",
+            Command::ShowDashboard => "Show dashboard",
+            Command::ShowProjects => "Show projects",
+            Command::Undo => "Undo",
+            Command::Redo => "Redo",
+            Command::MoveTasksUp => "Move selected tasks up",
+            Command::MoveTasksDown => "Move selected tasks down",
+            Command::NextTask => "Next task",
+            Command::PreviousTask => "Previous task",
+            Command::ToggleTaskCompleted => "Complete or reopen task",
+            Command::EditTask => "Edit task",
+            Command::OpenPeople => "People",
+            Command::OpenWorkload => "Workload",
+            Command::ManageTags => "Manage tags",
+            Command::OpenTimesheet => "Timesheet",
+            Command::OpenWorkCalendar => "Work calendar",
+            Command::OpenTimeZones => "Time zones",
+            Command::OpenAttachments => "Attachments",
+            Command::OpenNotifications => "Notifications",
+            Command::OpenKeymap => "Keyboard shortcuts",
+            Command::OpenSettings => "Settings",
+        }
+    }
+}
+
+/// A keyboard shortcut running a command
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct Binding {
+    pub command: Command,
+    pub shortcut: KeyboardShortcut,
+}
+
+impl Binding {
+    fn new(command: Command, modifiers: Modifiers, key: Key) -> Self {
+        Self {
+            command,
+            shortcut: KeyboardShortcut::new(modifiers, key),
+        }
+    }
+}
+
+/// Number of modifiers a shortcut needs held down
+/// Consume a press of the key with no modifier held. `consume_shortcut` ignores extra Shift and
+/// Alt, which would make a bare J binding also fire on Shift+J.
+fn consume_unmodified_key(input: &mut egui::InputState, key: Key) -> bool {
+    let mut pressed = false;
+    input.events.retain(|event| {
+        let is_match = matches!(
+            event,
+            Event::Key {
+                key: event_key,
+                modifiers,
+                pressed: true,
+                ..
+            } if *event_key == key && modifiers.is_none()
+        );
+        pressed |= is_match;
+        !is_match
+    });
+    pressed
+}
+
+fn modifier_count(modifiers: Modifiers) -> usize {
+    [
+        modifiers.alt,
+        modifiers.ctrl,
+        modifiers.shift,
+        modifiers.mac_cmd,
+        modifiers.command && !modifiers.ctrl && !modifiers.mac_cmd,
+    ]
+    .iter()
+    .filter(|held| **held)
+    .count()
+}
+
+/// Every keyboard shortcut, a command can have several
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Keymap {
+    pub bindings: Vec<Binding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: vec![
+                Binding::new(Command::OpenCommandPalette, Modifiers::COMMAND, Key::K),
+                Binding::new(Command::NewProject, Modifiers::COMMAND, Key::N),
+                Binding::new(Command::Undo, Modifiers::COMMAND, Key::Z),
+                Binding::new(Command::Redo, Modifiers::COMMAND | Modifiers::SHIFT, Key::Z),
+                Binding::new(Command::Redo, Modifiers::COMMAND, Key::Y),
+                Binding::new(Command::MoveTasksUp, Modifiers::ALT, Key::ArrowUp),
+                Binding::new(Command::MoveTasksDown, Modifiers::ALT, Key::ArrowDown),
+                Binding::new(Command::NextTask, Modifiers::NONE, Key::J),
+                Binding::new(Command::PreviousTask, Modifiers::NONE, Key::K),
+                Binding::new(Command::ToggleTaskCompleted, Modifiers::NONE, Key::X),
+                Binding::new(Command::EditTask, Modifiers::NONE, Key::E),
+            ],
+        }
+    }
+}
+
+impl Keymap {
+    pub fn shortcuts(&self, command: Command) -> impl Iterator<Item = &KeyboardShortcut> {
+        self.bindings
+            .iter()
+            .filter(move |binding| binding.command == command)
+            .map(|binding| &binding.shortcut)
+    }
+
+    /// The shortcuts of a command as text, empty if it has none
+    pub fn shortcut_text(&self, ctx: &egui::Context, command: Command) -> String {
+        self.shortcuts(command)
+            .map(|shortcut| ctx.format_shortcut(shortcut))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Bind a shortcut to a command, taking it away from any other command
+    pub fn bind(&mut self, command: Command, shortcut: KeyboardShortcut) {
+        self.bindings.retain(|binding| binding.shortcut != shortcut);
+        self.bindings.push(Binding { command, shortcut });
+    }
+
+    /// Commands whose shortcut was pressed this frame, consuming the key presses. Shortcuts
+    /// needing more modifiers are checked first so Ctrl+Shift+Z does not also count as Ctrl+Z.
+    pub fn consume_pressed(&self, ctx: &egui::Context) -> Vec<Command> {
+        let mut bindings: Vec<&Binding> = self.bindings.iter().collect();
+        bindings
+            .sort_by_key(|binding| std::cmp::Reverse(modifier_count(binding.shortcut.modifiers)));
+        ctx.input_mut(|input| {
+            bindings
+                .into_iter()
+                .filter(|binding| {
+                    if binding.shortcut.modifiers.is_none() {
+                        consume_unmodified_key(input, binding.shortcut.logical_key)
+                    } else {
+                        input.consume_shortcut(&binding.shortcut)
+                    }
+                })
+                .map(|binding| binding.command)
+                .collect()
+        })
+    }
+}
+
+/// Window listing every command with its shortcuts, for rebinding them
+#[derive(Default)]
+pub struct KeymapWindow {
+    pub open: bool,
+    /// The command waiting for the next key press to bind to it
+    recording: Option<Command>,
+}
+
+impl KeymapWindow {
+    /// Whether key presses go to recording a shortcut instead of running commands
+    pub fn is_recording(&self) -> bool {
+        self.open && self.recording.is_some()
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, keymap: &mut Keymap) {
+        if let Some(command) = self.recording {
+            let pressed = ctx.input(|input| {
+                input.events.iter().find_map(|event| match event {
+                    Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } => Some(KeyboardShortcut::new(*modifiers, *key)),
+                    _ => None,
+                })
+            });
+            if let Some(shortcut) = pressed {
+                if shortcut.logical_key != Key::Escape {
+                    keymap.bind(command, shortcut);
+                }
+                self.recording = None;
+            }
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Keyboard shortcuts")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label("Shortcuts without modifiers only work while no text field is focused");
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("keymap_grid").striped(true).show(ui, |ui| {
+                            Command::ALL.iter().for_each(|command| {
+                                ui.label(command.get_text());
+                                ui.horizontal(|ui| {
+                                    let mut removed = None;
+                                    keymap
+                                        .bindings
+                                        .iter()
+                                        .enumerate()
+                                        .filter(|(_, binding)| binding.command == *command)
+                                        .for_each(|(index, binding)| {
+                                            if ui
+                                                .button(ctx.format_shortcut(&binding.shortcut))
+                                                .on_hover_text("Click to remove")
+                                                .clicked()
+                                            {
+                                                removed = Some(index);
+                                            }
+                                        });
+                                    if let Some(index) = removed {
+                                        keymap.bindings.remove(index);
+                                    }
+                                    if self.recording == Some(*command) {
+                                        ui.weak("Press a key, Escape to cancel");
+                                    } else if ui.button("Add").clicked() {
+                                        self.recording = Some(*command);
+                                    }
+                                });
+                                ui.end_row();
+                            });
+                        });
+                    });
+                ui.separator();
+                if ui.button("Reset to defaults").clicked() {
+                    *keymap = Keymap::default();
+                    self.recording = None;
+                }
+            });
+        self.open = open;
+        if !self.open {
+            self.recording = None;
+        }
+    }
+}
+
+/// How well `query` fuzzily matches `text`, higher is better. None if the characters of the query
+/// do not all appear in the text in order.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match = None;
+    for query_char in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (position..text.len()).find(|index| text[*index] == query_char)?;
+        score += 1;
+        if previous_match.is_some_and(|previous| previous + 1 == found) {
+            score += 3;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 2;
+        }
+        score -= (found - position).min(3) as i32;
+        previous_match = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// Something picked in the command palette
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PaletteChoice {
+    Command(Command),
+    /// Jump to the project with this uuid
+    Project(Uuid),
+}
+
+/// Searchable list of every command and project
+#[derive(Default)]
+pub struct CommandPalette {
+    pub open: bool,
+    query: String,
+    selected: usize,
+    focus_requested: bool,
+}
+
+impl CommandPalette {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.query.clear();
+        self.selected = 0;
+        self.focus_requested = self.open;
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        keymap: &Keymap,
+        projects: &[Project],
+    ) -> Option<PaletteChoice> {
+        if !self.open {
+            return None;
+        }
+        let entries = Command::ALL
+            .iter()
+            .filter(|command| **command != Command::OpenCommandPalette)
+            .map(|command| {
+                let shortcut = keymap.shortcut_text(ctx, *command);
+                (
+                    PaletteChoice::Command(*command),
+                    command.get_text().to_string(),
+                    shortcut,
+                )
+            })
+            .chain(projects.iter().map(|project| {
+                (
+                    PaletteChoice::Project(project.get_uuid()),
+                    format!("Go to project: {}", project.project_name),
+                    String::new(),
+                )
+            }));
+        let mut matches: Vec<(i32, (PaletteChoice, String, String))> = entries
+            .filter_map(|entry| fuzzy_score(&self.query, &entry.1).map(|score| (score, entry)))
+            .collect();
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+
+        let (up, down, enter, escape) = ctx.input_mut(|input| {
+            (
+                input.consume_key(Modifiers::NONE, Key::ArrowUp),
+                input.consume_key(Modifiers::NONE, Key::ArrowDown),
+                input.consume_key(Modifiers::NONE, Key::Enter),
+                input.consume_key(Modifiers::NONE, Key::Escape),
+            )
+        });
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down && self.selected + 1 < matches.len() {
+            self.selected += 1;
+        }
+
+        let mut choice = None;
+        egui::Window::new("Command palette")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .show(ctx, |ui| {
+                let query = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Type a command or project name")
+                        .desired_width(400.0),
+                );
+                if self.focus_requested {
+                    query.request_focus();
+                    self.focus_requested = false;
+                }
+                if query.changed() {
+                    self.selected = 0;
+                }
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        if matches.is_empty() {
+                            ui.weak("Nothing matches");
+                        }
+                        matches.iter().enumerate().for_each(
+                            |(index, (_, (entry, text, shortcut)))| {
+                                ui.horizontal(|ui| {
+                                    let response =
+                                        ui.selectable_label(index == self.selected, text);
+                                    if index == self.selected && (up || down) {
+                                        response.scroll_to_me(None);
+                                    }
+                                    if response.clicked() {
+                                        choice = Some(*entry);
+                                    }
+                                    if !shortcut.is_empty() {
+                                        ui.weak(shortcut);
+                                    }
+                                });
+                            },
+                        );
+                    });
+            });
+
+        if enter {
+            choice = matches.get(self.selected).map(|(_, (entry, _, _))| *entry);
+        }
+        if choice.is_some() || escape {
+            self.open = false;
+        }
+        choice
+    }
+}
+
+/// The task highlighted by keyboard navigation in the project list
+#[derive(Default, Clone, Copy)]
+pub struct TaskCursor {
+    pub task: Option<TaskRef>,
+    /// Scroll the task into view on the next frame
+    pub scroll: bool,
+}
+
+impl TaskCursor {
+    pub fn is_on(&self, task: Uuid) -> bool {
+        self.task.is_some_and(|cursor| cursor.task == task)
+    }
+
+    /// Move to the next or previous task in `order`, starting at the first or last task when the
+    /// cursor is on no task that is shown
+    pub fn step(&mut self, order: &[TaskRef], forward: bool) {
+        let position = self
+            .task
+            .and_then(|cursor| order.iter().position(|task| *task == cursor));
+        let next = match (position, forward) {
+            (Some(position), true) => (position + 1).min(order.len().saturating_sub(1)),
+            (Some(position), false) => position.saturating_sub(1),
+            (None, true) => 0,
+            (None, false) => order.len().saturating_sub(1),
+        };
+        if let Some(task) = order.get(next) {
+            self.task = Some(*task);
+            self.scroll = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_needs_every_character_in_order() {
+        assert!(fuzzy_score("prj", "New project").is_some());
+        assert!(fuzzy_score("PROJ", "new project").is_some());
+        assert_eq!(fuzzy_score("jp", "New project"), None);
+        assert_eq!(fuzzy_score("projects", "New project"), None);
+        assert_eq!(fuzzy_score("", "New project"), Some(0));
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_word_starts_and_runs() {
+        let score = |query| fuzzy_score(query, "Open time zones").unwrap();
+        assert!(score("tz") > score("ie"));
+        assert!(score("time") > score("tmez"));
+        assert!(fuzzy_score("open", "Open time zones") > fuzzy_score("open", "Reopen tasks"));
+    }
+}
@@ -0,0 +1,315 @@
+use crate::people::AssigneeFilter;
+use crate::project::task::Task;
+use crate::project::{task_shown, Project, ProjectContext};
+use crate::tag::TagFilter;
+use crate::timezone::TimeZoneSettings;
+use chrono::{Days, NaiveDate};
+use egui::{Color32, RichText, Sense, Ui, Vec2};
+use uuid::Uuid;
+
+/// How many days ahead of today a project counts as due this week
+const DUE_SOON_DAYS: i64 = 7;
+
+/// How many entries the recently completed and most open task lists show
+const DASHBOARD_LIST_LENGTH: usize = 10;
+
+/// How many days back the completed tasks chart covers
+const COMPLETION_HISTORY_DAYS: u64 = 30;
+
+pub enum DashboardAction {
+    EditProject(Uuid),
+    None,
+}
+
+/// Projects that are due today or within the next week, soonest first
+pub fn projects_due_this_week<'a>(
+    projects: &[&'a Project],
+    time_zones: &TimeZoneSettings,
+) -> Vec<&'a Project> {
+    let mut due: Vec<&Project> = projects
+        .iter()
+        .filter(|project| (0..DUE_SOON_DAYS).contains(&project.days_until_due(time_zones)))
+        .copied()
+        .collect();
+    due.sort_by_key(|project| project.end_date);
+    due
+}
+
+/// Projects whose end date has passed, most overdue first
+pub fn overdue_projects<'a>(
+    projects: &[&'a Project],
+    time_zones: &TimeZoneSettings,
+) -> Vec<&'a Project> {
+    let mut overdue: Vec<&Project> = projects
+        .iter()
+        .filter(|project| project.days_until_due(time_zones) < 0)
+        .copied()
+        .collect();
+    overdue.sort_by_key(|project| project.end_date);
+    overdue
+}
+
+/// The most recently completed tasks across every project, newest first
+pub fn recently_completed_tasks<'a>(
+    projects: &[&'a Project],
+    tag_filter: &TagFilter,
+    assignee_filter: &AssigneeFilter,
+) -> Vec<(&'a Project, &'a Task)> {
+    let mut completed: Vec<(&Project, &Task)> = projects
+        .iter()
+        .flat_map(|project| project.tasks.iter().map(move |task| (*project, task)))
+        .filter(|(project, task)| {
+            task.completion_dates().next().is_some()
+                && task_shown(project, task, tag_filter, assignee_filter)
+        })
+        .collect();
+    completed.sort_by(|(_, a), (_, b)| b.completion_dates().max().cmp(&a.completion_dates().max()));
+    completed.truncate(DASHBOARD_LIST_LENGTH);
+    completed
+}
+
+/// Number of tasks completed on each of the last `days` days in the display time zone, oldest day
+/// first
+pub fn tasks_completed_per_day(
+    projects: &[&Project],
+    tag_filter: &TagFilter,
+    assignee_filter: &AssigneeFilter,
+    time_zones: &TimeZoneSettings,
+    days: u64,
+) -> Vec<(NaiveDate, usize)> {
+    let today = time_zones.display_today();
+    let first_day = today.checked_sub_days(Days::new(days - 1)).unwrap_or(today);
+
+    let mut counts: Vec<(NaiveDate, usize)> = first_day
+        .iter_days()
+        .take(days as usize)
+        .map(|day| (day, 0))
+        .collect();
+
+    projects
+        .iter()
+        .flat_map(|project| project.tasks.iter().map(move |task| (*project, task)))
+        .filter(|(project, task)| task_shown(project, task, tag_filter, assignee_filter))
+        .flat_map(|(_, task)| task.completion_dates())
+        .for_each(|complete_date| {
+            let day = time_zones.display_date(complete_date);
+            if let Some((_, count)) = counts.iter_mut().find(|(date, _)| *date == day) {
+                *count += 1;
+            }
+        });
+
+    counts
+}
+
+/// Projects sorted by their number of incomplete tasks, projects with no open tasks are excluded
+pub fn projects_with_most_open_tasks<'a>(
+    projects: &[&'a Project],
+    tag_filter: &TagFilter,
+    assignee_filter: &AssigneeFilter,
+) -> Vec<(&'a Project, usize)> {
+    let mut open: Vec<(&Project, usize)> = projects
+        .iter()
+        .map(|project| {
+            let open_count = project
+                .tasks
+                .iter()
+                .filter(|task| {
+                    !task.get_completed() && task_shown(project, task, tag_filter, assignee_filter)
+                })
+                .count();
+            (*project, open_count)
+        })
+        .filter(|(_, open_count)| *open_count > 0)
+        .collect();
+    open.sort_by_key(|(_, open_count)| std::cmp::Reverse(*open_count));
+    open.truncate(DASHBOARD_LIST_LENGTH);
+    open
+}
+
+pub fn show_dashboard(
+    ui: &mut Ui,
+    projects: &[Project],
+    context: &ProjectContext,
+) -> DashboardAction {
+    let mut action = DashboardAction::None;
+    let tag_filter = context.tag_filter;
+    let assignee_filter = context.assignee_filter;
+    let projects: Vec<&Project> = projects
+        .iter()
+        .filter(|project| {
+            tag_filter.matches_project(project) && assignee_filter.matches_project(project)
+        })
+        .collect();
+
+    ui.heading("Due this week");
+    let due = projects_due_this_week(&projects, context.time_zones);
+    if due.is_empty() {
+        ui.label("Nothing is due in the next week.");
+    }
+    due.iter().for_each(|project| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(&project.project_name).strong());
+            ui.label(format!(
+                "Due {} ({} working days)",
+                context.time_zones.format_date(project.end_date),
+                project.working_days_until_due(context.calendar, context.time_zones)
+            ));
+            if ui.button("Edit").clicked() {
+                action = DashboardAction::EditProject(project.get_uuid());
+            }
+        });
+    });
+    ui.separator();
+
+    ui.heading("Overdue");
+    let overdue = overdue_projects(&projects, context.time_zones);
+    if overdue.is_empty() {
+        ui.label("No overdue projects.");
+    }
+    overdue.iter().for_each(|project| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(&project.project_name).strong());
+            ui.colored_label(
+                Color32::LIGHT_RED,
+                format!(
+                    "Was due {} ({} working days ago)",
+                    context.time_zones.format_date(project.end_date),
+                    // A project due on the last working day before a weekend counts none
+                    (-project.working_days_until_due(context.calendar, context.time_zones)).max(1)
+                ),
+            );
+            if ui.button("Edit").clicked() {
+                action = DashboardAction::EditProject(project.get_uuid());
+            }
+        });
+    });
+    ui.separator();
+
+    ui.heading("Recently completed");
+    let completed = recently_completed_tasks(&projects, tag_filter, assignee_filter);
+    if completed.is_empty() {
+        ui.label("No tasks have been completed yet.");
+    }
+    completed.iter().for_each(|(project, task)| {
+        ui.horizontal(|ui| {
+            ui.label(&task.text);
+            ui.label(RichText::new(&project.project_name).weak());
+            if let Some(complete_date) = task.completion_dates().max() {
+                ui.label(context.time_zones.format_date_time(complete_date));
+            }
+        });
+    });
+    ui.separator();
+
+    ui.heading(format!(
+        "Tasks completed in the last {} days",
+        COMPLETION_HISTORY_DAYS
+    ));
+    show_completion_chart(
+        ui,
+        &tasks_completed_per_day(
+            &projects,
+            tag_filter,
+            assignee_filter,
+            context.time_zones,
+            COMPLETION_HISTORY_DAYS,
+        ),
+        context.time_zones,
+    );
+    ui.separator();
+
+    ui.heading("Most open tasks");
+    let most_open = projects_with_most_open_tasks(&projects, tag_filter, assignee_filter);
+    if most_open.is_empty() {
+        ui.label("Every task is finished.");
+    }
+    most_open.iter().for_each(|(project, open_count)| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(&project.project_name).strong());
+            ui.label(format!("{} open tasks", open_count));
+            if ui.button("Edit").clicked() {
+                action = DashboardAction::EditProject(project.get_uuid());
+            }
+        });
+    });
+
+    action
+}
+
+fn show_completion_chart(
+    ui: &mut Ui,
+    counts: &[(NaiveDate, usize)],
+    time_zones: &TimeZoneSettings,
+) {
+    let bar_size = Vec2::new(12.0, 80.0);
+    let max_count = counts
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let bar_color = ui.visuals().selection.bg_fill;
+    let empty_color = ui.visuals().faint_bg_color;
+
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 2.0;
+        counts.iter().for_each(|(date, count)| {
+            let (rect, response) = ui.allocate_exact_size(bar_size, Sense::hover());
+            ui.painter().rect_filled(rect, 1.0, empty_color);
+            let height = rect.height() * (*count as f32 / max_count as f32);
+            let mut bar = rect;
+            bar.set_top(rect.bottom() - height);
+            ui.painter().rect_filled(bar, 1.0, bar_color);
+            response.on_hover_text(format!(
+                "{}: {} completed",
+                time_zones.format_date(*date),
+                count
+            ));
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(text: &str, assignee: Option<Uuid>, completed: bool) -> Task {
+        let mut task = Task::new(text.to_string(), "".to_string());
+        task.assignees = assignee.into_iter().collect();
+        task.set_completed(completed);
+        task
+    }
+
+    #[test]
+    fn assignee_filter_narrows_the_task_lists() {
+        let me = Uuid::new_v4();
+        let mut mine = Project::default();
+        mine.tasks = vec![
+            task("Mine open", Some(me), false),
+            task("Mine done", Some(me), true),
+            task("Theirs open", None, false),
+        ];
+        let mut theirs = Project::default();
+        theirs.tasks = vec![task("Theirs done", None, true)];
+        let projects = vec![&mine, &theirs];
+        let tag_filter = TagFilter::default();
+        let assignee_filter = AssigneeFilter { person: Some(me) };
+
+        let completed = recently_completed_tasks(&projects, &tag_filter, &assignee_filter);
+        let texts: Vec<&str> = completed
+            .iter()
+            .map(|(_, task)| task.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["Mine done"]);
+
+        let open = projects_with_most_open_tasks(&projects, &tag_filter, &assignee_filter);
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].1, 1);
+
+        let everyone = AssigneeFilter::default();
+        assert_eq!(
+            recently_completed_tasks(&projects, &tag_filter, &everyone).len(),
+            2
+        );
+    }
+}
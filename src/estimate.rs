@@ -0,0 +1,184 @@
+use crate::calendar::WorkCalendar;
+use crate::project::Project;
+use crate::timezone::{to_zone, TimeZoneSettings};
+use chrono::Days;
+use egui::{Color32, Ui};
+use serde::{Deserialize, Serialize};
+
+/// Hours of work assumed to be available on each working day
+pub const WORKING_HOURS_PER_DAY: f32 = 8.0;
+
+/// How many days of completion history story point velocity is measured over
+const VELOCITY_WINDOW_DAYS: u64 = 14;
+
+/// The unit that task effort estimates in a project are given in
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Default, Debug)]
+pub enum EstimateUnit {
+    #[default]
+    Hours,
+    StoryPoints,
+}
+
+impl EstimateUnit {
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            EstimateUnit::Hours => "Hours",
+            EstimateUnit::StoryPoints => "Story points",
+        }
+    }
+
+    pub fn format(&self, amount: f32) -> String {
+        match self {
+            EstimateUnit::Hours => format!("{:.1}h", amount),
+            EstimateUnit::StoryPoints => format!("{:.1} pts", amount),
+        }
+    }
+}
+
+/// Estimate versus actual summary for a single project
+pub struct EstimateReport {
+    pub unit: EstimateUnit,
+    /// Sum of the estimates of every task
+    pub total_estimate: f32,
+    /// Estimated work left on incomplete tasks, for hours this subtracts time already tracked
+    pub remaining_estimate: f32,
+    /// Actual tracked hours divided by estimated hours over completed tasks with both recorded
+    pub accuracy: Option<f32>,
+    /// Story points completed per day over the velocity window
+    pub velocity: Option<f32>,
+    /// How much work fits before the project end date, in the projects estimate unit
+    pub capacity_left: Option<f32>,
+}
+
+impl EstimateReport {
+    pub fn for_project(
+        project: &Project,
+        workspace_calendar: &WorkCalendar,
+        time_zones: &TimeZoneSettings,
+    ) -> Self {
+        let unit = project.estimate_unit;
+        let estimated_tasks = project
+            .tasks
+            .iter()
+            .filter_map(|task| task.estimate.map(|estimate| (task, estimate)));
+
+        let total_estimate = estimated_tasks.clone().map(|(_, estimate)| estimate).sum();
+
+        let remaining_estimate = estimated_tasks
+            .clone()
+            .filter(|(task, _)| !task.get_completed())
+            .map(|(task, estimate)| match unit {
+                EstimateUnit::Hours => {
+                    (estimate - task.tracked_time().num_minutes() as f32 / 60.0).max(0.0)
+                }
+                EstimateUnit::StoryPoints => estimate,
+            })
+            .sum();
+
+        let (estimated_hours, actual_hours) = estimated_tasks
+            .clone()
+            .filter(|(task, _)| task.get_completed())
+            .map(|(task, estimate)| (estimate, task.tracked_time().num_minutes() as f32 / 60.0))
+            .filter(|(_, actual)| *actual > 0.0)
+            .fold(
+                (0.0, 0.0),
+                |(estimated, actual), (task_estimate, task_actual)| {
+                    (estimated + task_estimate, actual + task_actual)
+                },
+            );
+        let accuracy = (unit == EstimateUnit::Hours && estimated_hours > 0.0)
+            .then(|| actual_hours / estimated_hours);
+
+        let zone = project.zone(time_zones);
+        let today = project.today(time_zones);
+        let velocity = match unit {
+            EstimateUnit::Hours => None,
+            EstimateUnit::StoryPoints => {
+                let window_start = today
+                    .checked_sub_days(Days::new(VELOCITY_WINDOW_DAYS))
+                    .unwrap_or(today);
+                let completed_points: f32 = estimated_tasks
+                    .clone()
+                    .filter(|(task, _)| {
+                        task.get_complete_date()
+                            .is_some_and(|date| to_zone(date, zone).date_naive() > window_start)
+                    })
+                    .map(|(_, estimate)| estimate)
+                    .sum();
+                (completed_points > 0.0).then(|| completed_points / VELOCITY_WINDOW_DAYS as f32)
+            }
+        };
+
+        let days_left = project
+            .calendar(workspace_calendar)
+            .count_working_days(today, project.end_date) as f32;
+        let capacity_left = match unit {
+            EstimateUnit::Hours => Some(days_left * WORKING_HOURS_PER_DAY),
+            EstimateUnit::StoryPoints => velocity.map(|velocity| velocity * days_left),
+        };
+
+        Self {
+            unit,
+            total_estimate,
+            remaining_estimate,
+            accuracy,
+            velocity,
+            capacity_left,
+        }
+    }
+
+    /// True when the remaining estimate does not fit in the working time left before the end date
+    pub fn is_over_capacity(&self) -> bool {
+        self.capacity_left
+            .is_some_and(|capacity| self.remaining_estimate > capacity)
+    }
+
+    pub fn show(&self, ui: &mut Ui) {
+        ui.label(format!(
+            "Total estimate: {}",
+            self.unit.format(self.total_estimate)
+        ));
+        ui.label(format!(
+            "Remaining estimate: {}",
+            self.unit.format(self.remaining_estimate)
+        ));
+        match self.accuracy {
+            None => {
+                if self.unit == EstimateUnit::Hours {
+                    ui.label("Estimate accuracy: no completed tasks with tracked time");
+                }
+            }
+            Some(accuracy) => {
+                ui.label(format!(
+                    "Estimate accuracy: actual time was {:.0}% of the estimate",
+                    accuracy * 100.0
+                ));
+            }
+        }
+        if self.unit == EstimateUnit::StoryPoints {
+            match self.velocity {
+                None => {
+                    ui.label(format!(
+                        "Velocity: no points completed in the last {} days",
+                        VELOCITY_WINDOW_DAYS
+                    ));
+                }
+                Some(velocity) => {
+                    ui.label(format!("Velocity: {:.2} pts per day", velocity));
+                }
+            }
+        }
+        if let Some(capacity) = self.capacity_left {
+            ui.label(format!(
+                "Capacity before end date: {}",
+                self.unit.format(capacity)
+            ));
+        }
+        if self.is_over_capacity() {
+            ui.colored_label(
+                Color32::LIGHT_RED,
+                "Remaining estimate exceeds the working time left before the end date",
+            );
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use crate::project::Project;
+use crate::timezone::TimeZoneSettings;
+use chrono::NaiveDate;
+use egui::{Id, Order, Stroke, Ui};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A named group of projects, folders can be nested in other folders
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Folder {
+    uuid: Uuid,
+    pub name: String,
+    pub parent: Option<Uuid>,
+}
+
+impl Folder {
+    pub fn get_uuid(&self) -> Uuid {
+        self.uuid
+    }
+}
+
+/// Totals over every project in a folder and its subfolders
+#[derive(Default, Clone, Debug)]
+pub struct FolderStats {
+    pub projects: usize,
+    pub open_tasks: usize,
+    /// The earliest upcoming project end date or task due date, with the project it is in
+    pub next_deadline: Option<(NaiveDate, String)>,
+}
+
+impl FolderStats {
+    /// The totals as text, for the folder header
+    fn summary(&self, time_zones: &TimeZoneSettings) -> String {
+        let mut summary = format!("{} projects, {} open tasks", self.projects, self.open_tasks);
+        if let Some((date, project_name)) = &self.next_deadline {
+            summary = format!(
+                "{}, next deadline {} ({})",
+                summary,
+                time_zones.format_date(*date),
+                project_name
+            );
+        }
+        summary
+    }
+
+    fn add(&mut self, other: &FolderStats) {
+        self.projects += other.projects;
+        self.open_tasks += other.open_tasks;
+        if let Some((date, name)) = &other.next_deadline {
+            if self
+                .next_deadline
+                .as_ref()
+                .is_none_or(|(next, _)| date < next)
+            {
+                self.next_deadline = Some((*date, name.clone()));
+            }
+        }
+    }
+
+    fn for_project(project: &Project, today: NaiveDate) -> Self {
+        let open_tasks = project
+            .tasks
+            .iter()
+            .filter(|task| !task.get_completed())
+            .count();
+        let finished = !project.tasks.is_empty() && open_tasks == 0;
+        let next_deadline = project
+            .tasks
+            .iter()
+            .filter(|task| !task.get_completed())
+            .filter_map(|task| task.due_date)
+            .chain((!finished).then_some(project.end_date))
+            .filter(|date| *date >= today)
+            .min()
+            .map(|date| (date, project.project_name.clone()));
+        Self {
+            projects: 1,
+            open_tasks,
+            next_deadline,
+        }
+    }
+}
+
+pub enum FolderAction {
+    /// A project was dropped onto a folder, or out of every folder
+    MoveProject {
+        project: Uuid,
+        folder: Option<Uuid>,
+    },
+    /// Delete the folder, its projects and subfolders move up into its parent
+    DeleteFolder(Uuid),
+    None,
+}
+
+fn project_drag_id() -> Id {
+    Id::new("project_drag_payload")
+}
+
+pub fn start_project_drag(ctx: &egui::Context, project: Uuid) {
+    ctx.data_mut(|data| data.insert_temp(project_drag_id(), project));
+}
+
+/// The project being dragged onto a folder, if any
+pub fn project_drag_payload(ctx: &egui::Context) -> Option<Uuid> {
+    ctx.data(|data| data.get_temp(project_drag_id()))
+}
+
+/// Forget the dragged project once the pointer is released, call after every folder was shown
+pub fn end_project_drag_if_released(ctx: &egui::Context) {
+    if ctx.input(|input| input.pointer.any_released()) {
+        ctx.data_mut(|data| data.remove::<Uuid>(project_drag_id()));
+    }
+}
+
+/// Label following the pointer while a project is dragged
+pub fn show_project_drag_preview(ctx: &egui::Context, project_name: &str) {
+    let Some(pointer) = ctx.pointer_hover_pos() else {
+        return;
+    };
+    egui::Area::new("project_drag_preview")
+        .order(Order::Tooltip)
+        .interactable(false)
+        .fixed_pos(pointer + egui::vec2(12.0, 12.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(format!("Moving {}", project_name));
+            });
+        });
+}
+
+/// The dragged project when it is released over `rect`, highlights the rect while hovered
+fn drop_target(ui: &Ui, rect: egui::Rect) -> Option<Uuid> {
+    let project = project_drag_payload(ui.ctx())?;
+    let hovered = ui
+        .ctx()
+        .pointer_hover_pos()
+        .is_some_and(|pointer| rect.contains(pointer));
+    if !hovered {
+        return None;
+    }
+    ui.painter().rect_stroke(
+        rect.expand(2.0),
+        4.0,
+        Stroke::new(2.0, ui.visuals().selection.bg_fill),
+    );
+    ui.input(|input| input.pointer.any_released())
+        .then_some(project)
+}
+
+/// Every folder, kept flat with parent links
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct FolderTree {
+    pub folders: Vec<Folder>,
+}
+
+impl FolderTree {
+    pub fn get(&self, uuid: Uuid) -> Option<&Folder> {
+        self.folders.iter().find(|folder| folder.uuid == uuid)
+    }
+
+    pub fn add(&mut self, name: String, parent: Option<Uuid>) -> Uuid {
+        let uuid = Uuid::new_v4();
+        self.folders.push(Folder { uuid, name, parent });
+        uuid
+    }
+
+    fn children(&self, parent: Option<Uuid>) -> Vec<Uuid> {
+        let mut children: Vec<&Folder> = self
+            .folders
+            .iter()
+            .filter(|folder| folder.parent == parent)
+            .collect();
+        children.sort_by_key(|folder| folder.name.to_lowercase());
+        children.iter().map(|folder| folder.uuid).collect()
+    }
+
+    /// Delete a folder, moving its projects and subfolders into its parent
+    pub fn delete(&mut self, uuid: Uuid, projects: &mut [Project]) {
+        let Some(parent) = self.get(uuid).map(|folder| folder.parent) else {
+            return;
+        };
+        self.folders.retain(|folder| folder.uuid != uuid);
+        self.folders
+            .iter_mut()
+            .filter(|folder| folder.parent == Some(uuid))
+            .for_each(|folder| folder.parent = parent);
+        projects
+            .iter_mut()
+            .filter(|project| project.folder == Some(uuid))
+            .for_each(|project| project.folder = parent);
+    }
+
+    /// Indices of the given projects grouped by the folder they are in, in the given order.
+    /// Projects in a folder that no longer exists count as not being in any folder.
+    pub fn group_projects(
+        &self,
+        projects: &[Project],
+        indices: &[usize],
+    ) -> HashMap<Option<Uuid>, Vec<usize>> {
+        let mut groups: HashMap<Option<Uuid>, Vec<usize>> = HashMap::new();
+        indices.iter().for_each(|index| {
+            let folder = projects[*index]
+                .folder
+                .filter(|folder| self.get(*folder).is_some());
+            groups.entry(folder).or_default().push(*index);
+        });
+        groups
+    }
+
+    /// Stats for every folder over the grouped projects, including those in subfolders
+    pub fn stats(
+        &self,
+        projects: &[Project],
+        groups: &HashMap<Option<Uuid>, Vec<usize>>,
+        today: NaiveDate,
+    ) -> HashMap<Uuid, FolderStats> {
+        let mut stats = HashMap::new();
+        self.children(None).into_iter().for_each(|folder| {
+            self.collect_stats(folder, projects, groups, today, &mut stats);
+        });
+        stats
+    }
+
+    fn collect_stats(
+        &self,
+        folder: Uuid,
+        projects: &[Project],
+        groups: &HashMap<Option<Uuid>, Vec<usize>>,
+        today: NaiveDate,
+        stats: &mut HashMap<Uuid, FolderStats>,
+    ) -> FolderStats {
+        let mut folder_stats = FolderStats::default();
+        groups
+            .get(&Some(folder))
+            .into_iter()
+            .flatten()
+            .for_each(|index| {
+                folder_stats.add(&FolderStats::for_project(&projects[*index], today));
+            });
+        self.children(Some(folder)).into_iter().for_each(|child| {
+            let child_stats = self.collect_stats(child, projects, groups, today, stats);
+            folder_stats.add(&child_stats);
+        });
+        stats.insert(folder, folder_stats.clone());
+        folder_stats
+    }
+
+    /// Show the folders as collapsible groups, calling `show_project` for every project in them
+    /// and then for every project outside of any folder
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        groups: &HashMap<Option<Uuid>, Vec<usize>>,
+        stats: &HashMap<Uuid, FolderStats>,
+        time_zones: &TimeZoneSettings,
+        show_project: &mut dyn FnMut(&mut Ui, usize),
+    ) -> FolderAction {
+        let mut action = FolderAction::None;
+        ui.horizontal(|ui| {
+            if ui.button("New folder").clicked() {
+                self.add("New folder".to_string(), None);
+            }
+            if project_drag_payload(ui.ctx()).is_some() {
+                let response = ui.label("Drop here to take the project out of its folder");
+                if let Some(project) = drop_target(ui, response.rect) {
+                    action = FolderAction::MoveProject {
+                        project,
+                        folder: None,
+                    };
+                }
+            }
+        });
+        ui.separator();
+        let summaries: HashMap<Uuid, String> = stats
+            .iter()
+            .map(|(folder, stats)| (*folder, stats.summary(time_zones)))
+            .collect();
+        self.children(None).into_iter().for_each(|folder| {
+            self.show_folder(ui, folder, groups, &summaries, show_project, &mut action);
+        });
+        groups
+            .get(&None)
+            .into_iter()
+            .flatten()
+            .for_each(|index| show_project(ui, *index));
+        action
+    }
+
+    fn show_folder(
+        &mut self,
+        ui: &mut Ui,
+        folder: Uuid,
+        groups: &HashMap<Option<Uuid>, Vec<usize>>,
+        summaries: &HashMap<Uuid, String>,
+        show_project: &mut dyn FnMut(&mut Ui, usize),
+        action: &mut FolderAction,
+    ) {
+        let Some(name) = self.get(folder).map(|folder| folder.name.clone()) else {
+            return;
+        };
+        let header = match summaries.get(&folder) {
+            Some(summary) => format!("{}    {}", name, summary),
+            None => name,
+        };
+
+        let response = egui::CollapsingHeader::new(header)
+            .id_source(folder)
+            .show(ui, |ui| {
+                self.children(Some(folder)).into_iter().for_each(|child| {
+                    self.show_folder(ui, child, groups, summaries, show_project, action);
+                });
+                let projects = groups.get(&Some(folder));
+                if projects.is_none() && self.children(Some(folder)).is_empty() {
+                    ui.weak("Empty, drag projects here by their handle");
+                }
+                projects
+                    .into_iter()
+                    .flatten()
+                    .for_each(|index| show_project(ui, *index));
+            });
+
+        if let Some(project) = drop_target(ui, response.header_response.rect) {
+            *action = FolderAction::MoveProject {
+                project,
+                folder: Some(folder),
+            };
+        }
+        response
+            .header_response
+            .on_hover_text("Right click to rename, add a subfolder or delete")
+            .context_menu(|ui| {
+                if let Some(folder) = self.folders.iter_mut().find(|other| other.uuid == folder) {
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut folder.name);
+                    });
+                }
+                if ui.button("New subfolder").clicked() {
+                    self.add("New folder".to_string(), Some(folder));
+                    ui.close_menu();
+                }
+                if ui
+                    .button("Delete folder")
+                    .on_hover_text("Projects and subfolders move up into the parent folder")
+                    .clicked()
+                {
+                    *action = FolderAction::DeleteFolder(folder);
+                    ui.close_menu();
+                }
+            });
+    }
+}
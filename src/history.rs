@@ -0,0 +1,399 @@
+use crate::project::task::Task;
+use crate::project::Project;
+use uuid::Uuid;
+
+/// How many operations can be undone
+const MAX_UNDO_STEPS: usize = 50;
+
+/// A task as it was before and after an operation, None if it did not exist
+struct TaskChange {
+    task: Uuid,
+    before: Option<Task>,
+    after: Option<Task>,
+}
+
+/// What an operation changed in one project
+struct ProjectChange {
+    project: Uuid,
+    /// Only the tasks that were added, removed or edited
+    tasks: Vec<TaskChange>,
+    /// Task order before and after, if tasks were added, removed or reordered
+    order: Option<(Vec<Uuid>, Vec<Uuid>)>,
+    /// Folder before and after, if the project moved to another folder
+    folder: Option<(Option<Uuid>, Option<Uuid>)>,
+}
+
+impl ProjectChange {
+    fn between(before: &Project, after: &Project) -> Self {
+        let mut tasks: Vec<TaskChange> = before
+            .tasks
+            .iter()
+            .filter_map(|old| {
+                let new = after
+                    .tasks
+                    .iter()
+                    .find(|task| task.get_uuid() == old.get_uuid());
+                if new.is_some_and(|new| same_task(old, new)) {
+                    return None;
+                }
+                Some(TaskChange {
+                    task: old.get_uuid(),
+                    before: Some(old.clone()),
+                    after: new.cloned(),
+                })
+            })
+            .collect();
+        after
+            .tasks
+            .iter()
+            .filter(|new| {
+                before
+                    .tasks
+                    .iter()
+                    .all(|old| old.get_uuid() != new.get_uuid())
+            })
+            .for_each(|new| {
+                tasks.push(TaskChange {
+                    task: new.get_uuid(),
+                    before: None,
+                    after: Some(new.clone()),
+                })
+            });
+
+        let order_before = task_order(&before.tasks);
+        let order_after = task_order(&after.tasks);
+        Self {
+            project: before.get_uuid(),
+            tasks,
+            order: (order_before != order_after).then_some((order_before, order_after)),
+            folder: (before.folder != after.folder).then_some((before.folder, after.folder)),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tasks.is_empty() && self.order.is_none() && self.folder.is_none()
+    }
+
+    /// Put the touched tasks and fields back to how they were before the operation, or after it
+    /// when redoing. Everything else in the project is left as it is.
+    fn apply(&self, project: &mut Project, redo: bool) {
+        self.tasks.iter().for_each(|change| {
+            let target = if redo { &change.after } else { &change.before };
+            let current = project
+                .tasks
+                .iter()
+                .position(|task| task.get_uuid() == change.task);
+            match (current, target) {
+                (Some(index), Some(task)) => project.tasks[index] = task.clone(),
+                (Some(index), None) => {
+                    project.tasks.remove(index);
+                }
+                (None, Some(task)) => project.tasks.push(task.clone()),
+                (None, None) => {}
+            }
+        });
+        if let Some((before, after)) = &self.order {
+            let order = if redo { after } else { before };
+            // Tasks added since the operation are not in the recorded order and stay at the end
+            project.tasks.sort_by_key(|task| {
+                order
+                    .iter()
+                    .position(|uuid| *uuid == task.get_uuid())
+                    .unwrap_or(usize::MAX)
+            });
+        }
+        if let Some((before, after)) = self.folder {
+            project.folder = if redo { after } else { before };
+        }
+    }
+}
+
+fn task_order(tasks: &[Task]) -> Vec<Uuid> {
+    tasks.iter().map(|task| task.get_uuid()).collect()
+}
+
+/// Compare two tasks by their saved content, ignoring transient state like the selection
+fn same_task(first: &Task, second: &Task) -> bool {
+    match (serde_json::to_value(first), serde_json::to_value(second)) {
+        (Ok(first), Ok(second)) => first == second,
+        _ => false,
+    }
+}
+
+/// The changes one operation made
+struct Change {
+    label: String,
+    projects: Vec<ProjectChange>,
+    /// Projects the operation created, as they were when last removed by undoing it
+    added: Vec<Project>,
+    /// Projects the operation removed with their position in the project list, as they were
+    /// when last removed
+    removed: Vec<(usize, Project)>,
+}
+
+impl Change {
+    fn is_empty(&self) -> bool {
+        self.projects.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Take a project out of the list, None if it is not in it anymore
+    fn take(projects: &mut Vec<Project>, uuid: Uuid) -> Option<(usize, Project)> {
+        let index = projects
+            .iter()
+            .position(|project| project.get_uuid() == uuid)?;
+        Some((index, projects.remove(index)))
+    }
+
+    fn apply(&mut self, projects: &mut Vec<Project>, redo: bool) {
+        // the latest version of a project is kept when taking it out, so edits made to it after
+        // the operation come back when it is restored
+        if redo {
+            self.removed.iter_mut().for_each(|(index, removed)| {
+                if let Some((current_index, project)) = Self::take(projects, removed.get_uuid()) {
+                    *index = current_index;
+                    *removed = project;
+                }
+            });
+            projects.extend(self.added.iter().cloned());
+        } else {
+            self.added.iter_mut().for_each(|added| {
+                if let Some((_, project)) = Self::take(projects, added.get_uuid()) {
+                    *added = project;
+                }
+            });
+            self.removed.iter().for_each(|(index, project)| {
+                projects.insert((*index).min(projects.len()), project.clone());
+            });
+        }
+        self.projects.iter().for_each(|change| {
+            if let Some(project) = projects
+                .iter_mut()
+                .find(|project| project.get_uuid() == change.project)
+            {
+                change.apply(project, redo);
+            }
+        });
+    }
+}
+
+/// Undo and redo for operations on the project list.
+///
+/// Every undoable operation is recorded with the projects it touches. Only the tasks and fields
+/// of those projects the operation changed are kept, so undoing an operation leaves edits made
+/// after it to other tasks and projects alone.
+#[derive(Default)]
+pub struct UndoHistory {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+}
+
+impl UndoHistory {
+    /// Run an operation on the project list as one undoable step with the given label.
+    /// `touched` are the uuids of every project the operation may change or remove, projects it
+    /// adds are found on their own. Nothing is recorded if the operation did not change anything.
+    pub fn record<R>(
+        &mut self,
+        label: impl Into<String>,
+        projects: &mut Vec<Project>,
+        touched: &[Uuid],
+        operation: impl FnOnce(&mut Vec<Project>) -> R,
+    ) -> R {
+        let existing: Vec<Uuid> = projects.iter().map(Project::get_uuid).collect();
+        let before: Vec<(usize, Project)> = projects
+            .iter()
+            .enumerate()
+            .filter(|(_, project)| touched.contains(&project.get_uuid()))
+            .map(|(index, project)| (index, project.clone()))
+            .collect();
+
+        let result = operation(projects);
+
+        let mut change = Change {
+            label: label.into(),
+            projects: vec![],
+            added: projects
+                .iter()
+                .filter(|project| !existing.contains(&project.get_uuid()))
+                .cloned()
+                .collect(),
+            removed: vec![],
+        };
+        before.into_iter().for_each(|(index, old)| {
+            match projects
+                .iter()
+                .find(|project| project.get_uuid() == old.get_uuid())
+            {
+                Some(project) => {
+                    let project_change = ProjectChange::between(&old, project);
+                    if !project_change.is_empty() {
+                        change.projects.push(project_change);
+                    }
+                }
+                None => change.removed.push((index, old)),
+            }
+        });
+        if !change.is_empty() {
+            self.undo.push(change);
+            if self.undo.len() > MAX_UNDO_STEPS {
+                self.undo.remove(0);
+            }
+            self.redo.clear();
+        }
+        result
+    }
+
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|change| change.label.as_str())
+    }
+
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|change| change.label.as_str())
+    }
+
+    /// Revert the changes of the last operation, returns false if there is nothing to undo
+    pub fn undo(&mut self, projects: &mut Vec<Project>) -> bool {
+        let Some(mut change) = self.undo.pop() else {
+            return false;
+        };
+        change.apply(projects, false);
+        self.redo.push(change);
+        true
+    }
+
+    /// Apply the last undone operation again, returns false if there is nothing to redo
+    pub fn redo(&mut self, projects: &mut Vec<Project>) -> bool {
+        let Some(mut change) = self.redo.pop() else {
+            return false;
+        };
+        change.apply(projects, true);
+        self.undo.push(change);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(name: &str) -> Project {
+        let mut project = Project::default();
+        project.project_name = name.to_string();
+        project
+    }
+
+    fn add_task(history: &mut UndoHistory, projects: &mut Vec<Project>, text: &str) {
+        let uuid = projects[0].get_uuid();
+        history.record("Add task", projects, &[uuid], |projects| {
+            projects[0]
+                .tasks
+                .push(Task::new(text.to_string(), "".to_string()))
+        });
+    }
+
+    fn snapshot(projects: &[Project]) -> serde_json::Value {
+        serde_json::to_value(projects).unwrap()
+    }
+
+    #[test]
+    fn undo_then_redo_restores_the_same_state() {
+        let mut history = UndoHistory::default();
+        let mut projects = vec![project("Website")];
+        let initial = snapshot(&projects);
+        add_task(&mut history, &mut projects, "Write copy");
+        let edited = snapshot(&projects);
+
+        assert_eq!(history.undo_label(), Some("Add task"));
+        assert!(history.undo(&mut projects));
+        assert_eq!(snapshot(&projects), initial);
+        assert!(history.redo(&mut projects));
+        assert_eq!(snapshot(&projects), edited);
+        assert!(!history.redo(&mut projects));
+    }
+
+    #[test]
+    fn new_edit_after_undo_clears_redo() {
+        let mut history = UndoHistory::default();
+        let mut projects = vec![project("Website")];
+        add_task(&mut history, &mut projects, "Write copy");
+        history.undo(&mut projects);
+        assert_eq!(history.redo_label(), Some("Add task"));
+
+        add_task(&mut history, &mut projects, "Pick images");
+        assert_eq!(history.redo_label(), None);
+        assert!(!history.redo(&mut projects));
+    }
+
+    #[test]
+    fn operations_without_changes_are_not_recorded() {
+        let mut history = UndoHistory::default();
+        let mut projects = vec![project("Website")];
+        let uuid = projects[0].get_uuid();
+        history.record("Nothing", &mut projects, &[uuid], |_| {});
+        assert_eq!(history.undo_label(), None);
+    }
+
+    #[test]
+    fn only_the_last_steps_are_kept() {
+        let mut history = UndoHistory::default();
+        let mut projects = vec![project("Website")];
+        (0..MAX_UNDO_STEPS + 10)
+            .for_each(|index| add_task(&mut history, &mut projects, &index.to_string()));
+
+        let mut undone = 0;
+        while history.undo(&mut projects) {
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_UNDO_STEPS);
+        assert_eq!(projects[0].tasks.len(), 10);
+    }
+
+    #[test]
+    fn undo_keeps_later_edits_to_other_tasks() {
+        let mut history = UndoHistory::default();
+        let mut projects = vec![project("Website")];
+        add_task(&mut history, &mut projects, "Write copy");
+        projects[0]
+            .tasks
+            .push(Task::new("Pick images".to_string(), "".to_string()));
+
+        history.undo(&mut projects);
+        let texts: Vec<&str> = projects[0]
+            .tasks
+            .iter()
+            .map(|task| task.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["Pick images"]);
+    }
+
+    #[test]
+    fn added_projects_are_removed_and_restored() {
+        let mut history = UndoHistory::default();
+        let mut projects = vec![project("Website")];
+        history.record("Duplicate", &mut projects, &[], |projects| {
+            projects.push(project("Website (copy)"))
+        });
+        let added = projects[1].get_uuid();
+
+        history.undo(&mut projects);
+        assert_eq!(projects.len(), 1);
+        history.redo(&mut projects);
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[1].get_uuid(), added);
+    }
+
+    #[test]
+    fn removed_projects_come_back_in_place() {
+        let mut history = UndoHistory::default();
+        let mut projects = vec![project("Website"), project("Shop"), project("Blog")];
+        let removed = projects[1].get_uuid();
+        history.record("Merge", &mut projects, &[removed], |projects| {
+            projects.remove(1);
+        });
+        assert_eq!(projects.len(), 2);
+
+        history.undo(&mut projects);
+        assert_eq!(projects[1].get_uuid(), removed);
+        history.redo(&mut projects);
+        assert!(projects.iter().all(|project| project.get_uuid() != removed));
+    }
+}
@@ -0,0 +1,350 @@
+use crate::project::task::Task;
+use crate::project::Project;
+use crate::recurrence::Recurrence;
+use crate::tag::add_tag;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+const ICAL_DATE_FORMAT: &str = "%Y%m%d";
+const ICAL_DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Escape text for use in an iCalendar property value
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => unescaped.push('\n'),
+                Some(other) => unescaped.push(other),
+                None => {}
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+/// Fold a content line so that no line is longer than 75 bytes, as the iCalendar spec requires
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut line_length = 0;
+    line.chars().for_each(|c| {
+        if line_length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(c);
+        line_length += c.len_utf8();
+    });
+    folded.push_str("\r\n");
+    folded
+}
+
+fn format_utc(date: &DateTime<Utc>) -> String {
+    date.format(ICAL_DATE_TIME_FORMAT).to_string()
+}
+
+/// Export every task of a project as an iCalendar VTODO, recurring tasks keep their recurrence
+/// rule as an RRULE property. Milestones are exported as all day VEVENTs, with their tasks
+/// pointing at them through RELATED-TO.
+pub fn export_project(project: &Project) -> String {
+    let now = format_utc(&Utc::now());
+    let mut calendar = String::new();
+    calendar.push_str(&fold_line("BEGIN:VCALENDAR"));
+    calendar.push_str(&fold_line("VERSION:2.0"));
+    calendar.push_str(&fold_line("PRODID:-//cr_project_planner//EN"));
+    calendar.push_str(&fold_line(&format!(
+        "X-WR-CALNAME:{}",
+        escape_text(&project.project_name)
+    )));
+
+    project.tasks.iter().for_each(|task| {
+        let mut lines = vec![
+            "BEGIN:VTODO".to_string(),
+            format!("UID:{}", task.get_uuid()),
+            format!("DTSTAMP:{}", now),
+            format!("SUMMARY:{}", escape_text(&task.text)),
+        ];
+        if !task.description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape_text(&task.description)));
+        }
+        if let Some(due_date) = task.due_date {
+            lines.push(format!(
+                "DUE;VALUE=DATE:{}",
+                due_date.format(ICAL_DATE_FORMAT)
+            ));
+        }
+        if let Some(recurrence) = &task.recurrence {
+            lines.push(format!("RRULE:{}", recurrence.to_rrule()));
+        }
+        if !task.tags.is_empty() {
+            let tags: Vec<String> = task.tags.iter().map(|tag| escape_text(tag)).collect();
+            lines.push(format!("CATEGORIES:{}", tags.join(",")));
+        }
+        if let Some(milestone) = task.milestone {
+            lines.push(format!("RELATED-TO:{}", milestone));
+        }
+        match task.get_complete_date() {
+            Some(complete_date) if task.get_completed() => {
+                lines.push("STATUS:COMPLETED".to_string());
+                lines.push(format!("COMPLETED:{}", format_utc(complete_date)));
+            }
+            _ => lines.push("STATUS:NEEDS-ACTION".to_string()),
+        }
+        lines.push("END:VTODO".to_string());
+        lines
+            .iter()
+            .for_each(|line| calendar.push_str(&fold_line(line)));
+    });
+
+    project.milestones.iter().for_each(|milestone| {
+        [
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", milestone.get_uuid()),
+            format!("DTSTAMP:{}", now),
+            format!(
+                "DTSTART;VALUE=DATE:{}",
+                milestone.date.format(ICAL_DATE_FORMAT)
+            ),
+            format!("SUMMARY:{}", escape_text(&milestone.name)),
+            "CATEGORIES:MILESTONE".to_string(),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]
+        .iter()
+        .for_each(|line| calendar.push_str(&fold_line(line)));
+    });
+
+    calendar.push_str(&fold_line("END:VCALENDAR"));
+    calendar
+}
+
+/// Join folded lines back together, a line starting with a space or tab continues the previous line
+fn unfold_lines(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    ics.lines().for_each(|line| {
+        if let Some(continuation) = line.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(continuation);
+            }
+        } else {
+            lines.push(line.to_string());
+        }
+    });
+    lines
+}
+
+/// Split a property value on a separator, ignoring escaped separators
+fn split_unescaped(value: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut escaped = false;
+    value.chars().for_each(|c| {
+        if c == separator && !escaped {
+            parts.push(String::new());
+        } else if let Some(part) = parts.last_mut() {
+            part.push(c);
+        }
+        escaped = c == '\\' && !escaped;
+    });
+    parts
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, ICAL_DATE_FORMAT).ok()
+}
+
+/// Parse a UTC or floating date time, floating times are read in the system time zone
+fn parse_date_time(value: &str) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()?;
+    if value.ends_with('Z') {
+        Some(Utc.from_utc_datetime(&naive))
+    } else {
+        Local
+            .from_local_datetime(&naive)
+            .single()
+            .map(|local| local.with_timezone(&Utc))
+    }
+}
+
+/// Read every VTODO from an iCalendar file as a task. Properties that tasks have no use for are
+/// ignored, as are recurrence rules that can not be represented and components nested inside a
+/// VTODO, like alarms.
+pub fn import_tasks(ics: &str) -> Vec<Task> {
+    let mut tasks = vec![];
+    let mut current: Option<Task> = None;
+    let mut completed: Option<DateTime<Utc>> = None;
+    let mut status_completed = false;
+    // how many components deep inside the current VTODO the line is
+    let mut depth = 0;
+
+    unfold_lines(ics).iter().for_each(|line| {
+        let Some((name_and_params, value)) = line.split_once(':') else {
+            return;
+        };
+        let name = name_and_params
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VTODO") => {
+                current = Some(Task::new("".to_string(), "".to_string()));
+                completed = None;
+                status_completed = false;
+                depth = 0;
+            }
+            ("BEGIN", Some(_)) => depth += 1,
+            ("END", Some(_)) if depth > 0 => depth -= 1,
+            (_, Some(_)) if depth > 0 => {}
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VTODO") => {
+                if let Some(mut task) = current.take() {
+                    if status_completed || completed.is_some() {
+                        task.set_completed_on(completed.unwrap_or_else(Utc::now));
+                    }
+                    tasks.push(task);
+                }
+            }
+            ("SUMMARY", Some(task)) => task.text = unescape_text(value),
+            ("DESCRIPTION", Some(task)) => task.description = unescape_text(value),
+            ("DUE", Some(task)) => task.due_date = parse_date(value),
+            ("RRULE", Some(task)) => task.recurrence = Recurrence::from_rrule(value).ok(),
+            ("CATEGORIES", Some(task)) => {
+                split_unescaped(value, ',')
+                    .iter()
+                    .map(|tag| unescape_text(tag))
+                    .filter(|tag| !tag.trim().is_empty())
+                    .for_each(|tag| {
+                        add_tag(&mut task.tags, &tag);
+                    });
+            }
+            ("STATUS", Some(_)) => status_completed = value.eq_ignore_ascii_case("COMPLETED"),
+            ("COMPLETED", Some(_)) => completed = parse_date_time(value),
+            _ => {}
+        }
+    });
+
+    tasks
+}
+
+/// An all day span read from a VEVENT
+pub struct CalendarEvent {
+    pub start: NaiveDate,
+    /// First day after the event, exclusive like the iCalendar DTEND of all day events
+    pub end: NaiveDate,
+    pub summary: String,
+}
+
+/// Read every VEVENT from an iCalendar file as an all day event, events without a start date are
+/// skipped. Components nested inside a VEVENT, like alarms, are ignored.
+pub fn import_events(ics: &str) -> Vec<CalendarEvent> {
+    let mut events = vec![];
+    let mut in_event = false;
+    // how many components deep inside the current VEVENT the line is
+    let mut depth = 0;
+    let mut start: Option<NaiveDate> = None;
+    let mut end: Option<NaiveDate> = None;
+    let mut summary = String::new();
+
+    unfold_lines(ics).iter().for_each(|line| {
+        let Some((name_and_params, value)) = line.split_once(':') else {
+            return;
+        };
+        let name = name_and_params
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+
+        match name.as_str() {
+            "BEGIN" if in_event => depth += 1,
+            "END" if depth > 0 => depth -= 1,
+            _ if depth > 0 => {}
+            "BEGIN" if value.eq_ignore_ascii_case("VEVENT") => {
+                in_event = true;
+                depth = 0;
+                start = None;
+                end = None;
+                summary.clear();
+            }
+            "END" if in_event && value.eq_ignore_ascii_case("VEVENT") => {
+                in_event = false;
+                if let Some(start) = start {
+                    let end = end
+                        .filter(|end| *end > start)
+                        .or_else(|| start.succ_opt())
+                        .unwrap_or(start);
+                    events.push(CalendarEvent {
+                        start,
+                        end,
+                        summary: summary.clone(),
+                    });
+                }
+            }
+            "DTSTART" if in_event => start = parse_date(value),
+            "DTEND" if in_event => end = parse_date(value),
+            "SUMMARY" if in_event => summary = unescape_text(value),
+            _ => {}
+        }
+    });
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tasks_survive_export_and_import() {
+        let mut project = Project::default();
+        let mut task = Task::new(
+            "Water the plants; twice, if hot".to_string(),
+            "Front and\nback".to_string(),
+        );
+        task.due_date = NaiveDate::from_ymd_opt(2024, 5, 17);
+        task.recurrence =
+            Some(Recurrence::from_rrule("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;COUNT=10").unwrap());
+        task.tags = vec!["home".to_string(), "garden, back".to_string()];
+        project.tasks.push(task.clone());
+        let mut monthly = Task::new("Pay rent".to_string(), String::new());
+        monthly.recurrence =
+            Some(Recurrence::from_rrule("FREQ=MONTHLY;BYDAY=-1FR;UNTIL=20241231").unwrap());
+        project.tasks.push(monthly.clone());
+
+        let imported = import_tasks(&export_project(&project));
+        assert_eq!(imported.len(), 2);
+        assert_eq!(imported[0].text, task.text);
+        assert_eq!(imported[0].description, task.description);
+        assert_eq!(imported[0].due_date, task.due_date);
+        assert_eq!(imported[0].tags, task.tags);
+        assert_eq!(imported[0].recurrence, task.recurrence);
+        assert_eq!(imported[1].recurrence, monthly.recurrence);
+    }
+
+    #[test]
+    fn nested_components_do_not_overwrite_the_task() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nSUMMARY:Renew passport\r\n\
+                   BEGIN:VALARM\r\nACTION:DISPLAY\r\nSUMMARY:Reminder\r\n\
+                   DESCRIPTION:Alarm text\r\nEND:VALARM\r\nDUE;VALUE=DATE:20240601\r\n\
+                   END:VTODO\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20240101\r\n\
+                   SUMMARY:New year\r\nBEGIN:VALARM\r\nSUMMARY:Alarm\r\nEND:VALARM\r\n\
+                   END:VEVENT\r\nEND:VCALENDAR\r\n";
+        let tasks = import_tasks(ics);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].text, "Renew passport");
+        assert_eq!(tasks[0].description, "");
+        assert_eq!(tasks[0].due_date, NaiveDate::from_ymd_opt(2024, 6, 1));
+        let events = import_events(ics);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary, "New year");
+    }
+}
@@ -1 +1,23 @@
+pub mod attachment;
+pub mod calendar;
+pub mod command;
+pub mod dashboard;
+pub mod estimate;
+pub mod folder;
+pub mod history;
+pub mod ical;
+pub mod markdown;
+pub mod people;
 pub mod project;
+pub mod project_list;
+pub mod quick_add;
+pub mod recurrence;
+pub mod reminder;
+pub mod search;
+pub mod settings;
+pub mod tag;
+pub mod template;
+pub mod time_tracking;
+pub mod timezone;
+pub mod transfer;
+pub mod workload;
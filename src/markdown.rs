@@ -0,0 +1,495 @@
+use egui::{RichText, Ui};
+
+/// A piece of inline text with its formatting
+#[derive(PartialEq, Debug)]
+enum Inline {
+    Text {
+        text: String,
+        bold: bool,
+        italic: bool,
+        strikethrough: bool,
+        code: bool,
+    },
+    Link {
+        text: String,
+        url: String,
+    },
+}
+
+#[derive(PartialEq, Debug)]
+enum ListMarker {
+    Bullet,
+    Number(u32),
+    /// A checklist item, with whether it is checked
+    Check(bool),
+}
+
+#[derive(PartialEq, Debug)]
+enum Block {
+    Heading {
+        level: usize,
+        text: String,
+    },
+    Paragraph(String),
+    ListItem {
+        indent: usize,
+        marker: ListMarker,
+        text: String,
+        /// Line of the source the item is on, used to toggle checklist items
+        line: usize,
+    },
+    Quote(String),
+    Code(String),
+    Rule,
+}
+
+/// Split a list item line into its indentation, marker and text
+fn parse_list_item(line: &str) -> Option<(usize, ListMarker, &str)> {
+    let indent = line.len() - line.trim_start().len();
+    let trimmed = line.trim_start();
+    let rest = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| trimmed.strip_prefix(bullet));
+    if let Some(rest) = rest {
+        for (prefix, checked) in [("[ ] ", false), ("[x] ", true), ("[X] ", true)] {
+            if let Some(text) = rest.strip_prefix(prefix) {
+                return Some((indent, ListMarker::Check(checked), text));
+            }
+        }
+        return Some((indent, ListMarker::Bullet, rest));
+    }
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(text) = trimmed[digits..].strip_prefix(". ") {
+            let number = trimmed[..digits].parse().ok()?;
+            return Some((indent, ListMarker::Number(number), text));
+        }
+    }
+    None
+}
+
+fn is_rule(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.len() >= 3
+        && ["-", "*", "_"]
+            .iter()
+            .any(|marker| trimmed.chars().all(|c| c.to_string() == *marker))
+}
+
+fn parse_blocks(source: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    let mut paragraph: Vec<&str> = vec![];
+    let mut code: Option<Vec<&str>> = None;
+
+    let flush_paragraph = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(paragraph.join(" ")));
+            paragraph.clear();
+        }
+    };
+
+    source.lines().enumerate().for_each(|(line_index, line)| {
+        if let Some(code_lines) = code.as_mut() {
+            if line.trim_start().starts_with("```") {
+                blocks.push(Block::Code(code_lines.join("\n")));
+                code = None;
+            } else {
+                code_lines.push(line);
+            }
+            return;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            code = Some(vec![]);
+        } else if trimmed.is_empty() {
+            flush_paragraph(&mut paragraph, &mut blocks);
+        } else if is_rule(trimmed) {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            blocks.push(Block::Rule);
+        } else if let Some((level, text)) = trimmed
+            .split_once(' ')
+            .filter(|(hashes, _)| {
+                !hashes.is_empty() && hashes.len() <= 6 && hashes.chars().all(|c| c == '#')
+            })
+            .map(|(hashes, text)| (hashes.len(), text))
+        {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading {
+                level,
+                text: text.trim().to_string(),
+            });
+        } else if let Some((indent, marker, text)) = parse_list_item(line) {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            blocks.push(Block::ListItem {
+                indent,
+                marker,
+                text: text.to_string(),
+                line: line_index,
+            });
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            blocks.push(Block::Quote(quote.trim().to_string()));
+        } else {
+            paragraph.push(trimmed);
+        }
+    });
+
+    if let Some(code_lines) = code {
+        blocks.push(Block::Code(code_lines.join("\n")));
+    }
+    flush_paragraph(&mut paragraph, &mut blocks);
+    blocks
+}
+
+/// Whether a `*` between `before` and `after` starts or ends emphasis. Opening needs text right
+/// after it and closing text right before it, so `2 * 3 * 4` stays as it is.
+fn is_emphasis_edge(before: &str, after: &str, closing: bool) -> bool {
+    let neighbour = if closing {
+        before.chars().next_back()
+    } else {
+        after.chars().next()
+    };
+    neighbour.is_some_and(|c| !c.is_whitespace())
+}
+
+/// Parse emphasis, strikethrough, inline code, links and bare urls
+fn parse_inline(text: &str) -> Vec<Inline> {
+    let mut inlines = vec![];
+    let mut current = String::new();
+    let (mut bold, mut italic, mut strikethrough) = (false, false, false);
+    let mut rest = text;
+
+    let flush = |current: &mut String,
+                 inlines: &mut Vec<Inline>,
+                 bold: bool,
+                 italic: bool,
+                 strikethrough: bool| {
+        if !current.is_empty() {
+            inlines.push(Inline::Text {
+                text: std::mem::take(current),
+                bold,
+                italic,
+                strikethrough,
+                code: false,
+            });
+        }
+    };
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**") {
+            flush(&mut current, &mut inlines, bold, italic, strikethrough);
+            bold = !bold;
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("~~") {
+            flush(&mut current, &mut inlines, bold, italic, strikethrough);
+            strikethrough = !strikethrough;
+            rest = after;
+        } else if let Some(after) = rest
+            .strip_prefix('*')
+            .filter(|after| is_emphasis_edge(&text[..text.len() - rest.len()], after, italic))
+        {
+            flush(&mut current, &mut inlines, bold, italic, strikethrough);
+            italic = !italic;
+            rest = after;
+        } else if let Some((code, after)) = rest
+            .strip_prefix('`')
+            .and_then(|after| after.split_once('`'))
+        {
+            flush(&mut current, &mut inlines, bold, italic, strikethrough);
+            inlines.push(Inline::Text {
+                text: code.to_string(),
+                bold: false,
+                italic: false,
+                strikethrough: false,
+                code: true,
+            });
+            rest = after;
+        } else if let Some((link_text, url, after)) = rest.strip_prefix('[').and_then(|after| {
+            let (link_text, after) = after.split_once("](")?;
+            let (url, after) = after.split_once(')')?;
+            Some((link_text, url, after))
+        }) {
+            flush(&mut current, &mut inlines, bold, italic, strikethrough);
+            inlines.push(Inline::Link {
+                text: link_text.to_string(),
+                url: url.trim().to_string(),
+            });
+            rest = after;
+        } else if rest.starts_with("https://") || rest.starts_with("http://") {
+            flush(&mut current, &mut inlines, bold, italic, strikethrough);
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let url = rest[..end].trim_end_matches(['.', ',', ')', ';']);
+            inlines.push(Inline::Link {
+                text: url.to_string(),
+                url: url.to_string(),
+            });
+            rest = &rest[url.len()..];
+        } else {
+            current.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    flush(&mut current, &mut inlines, bold, italic, strikethrough);
+    inlines
+}
+
+fn show_inline(ui: &mut Ui, text: &str, size: Option<f32>) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        parse_inline(text)
+            .into_iter()
+            .for_each(|inline| match inline {
+                Inline::Text {
+                    text,
+                    bold,
+                    italic,
+                    strikethrough,
+                    code,
+                } => {
+                    let mut rich_text = RichText::new(text);
+                    if let Some(size) = size {
+                        rich_text = rich_text.size(size).strong();
+                    }
+                    if bold {
+                        rich_text = rich_text.strong();
+                    }
+                    if italic {
+                        rich_text = rich_text.italics();
+                    }
+                    if strikethrough {
+                        rich_text = rich_text.strikethrough();
+                    }
+                    if code {
+                        rich_text = rich_text.code();
+                    }
+                    ui.label(rich_text);
+                }
+                Inline::Link { text, url } => {
+                    ui.hyperlink_to(text, url);
+                }
+            });
+    });
+}
+
+/// Flip the checklist item on a line of the source between checked and unchecked. Only the
+/// marker right after the list bullet changes, line endings are kept as they are.
+fn toggle_checklist_item(source: &mut String, line_index: usize) {
+    let Some((line_start, line)) = source
+        .split_inclusive('\n')
+        .scan(0, |start, line| {
+            let line_start = *start;
+            *start += line.len();
+            Some((line_start, line))
+        })
+        .nth(line_index)
+    else {
+        return;
+    };
+    let line = line.trim_end_matches(['\n', '\r']);
+    let Some((_, ListMarker::Check(checked), text)) = parse_list_item(line) else {
+        return;
+    };
+    // the marker is the "[ ] " right before the item text
+    let marker_start = line_start + line.len() - text.len() - "[ ] ".len();
+    let marker = if checked { "[ ]" } else { "[x]" };
+    source.replace_range(marker_start..marker_start + marker.len(), marker);
+}
+
+/// Render Markdown text. Checklist items can be ticked, which updates the source text, returns
+/// true if that happened.
+pub fn show_markdown(ui: &mut Ui, source: &mut String) -> bool {
+    let mut toggled_line = None;
+    ui.vertical(|ui| {
+        parse_blocks(source)
+            .into_iter()
+            .for_each(|block| match block {
+                Block::Heading { level, text } => {
+                    let size = match level {
+                        1 => 22.0,
+                        2 => 19.0,
+                        3 => 17.0,
+                        _ => 15.0,
+                    };
+                    show_inline(ui, &text, Some(size));
+                }
+                Block::Paragraph(text) => show_inline(ui, &text, None),
+                Block::ListItem {
+                    indent,
+                    marker,
+                    text,
+                    line,
+                } => {
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0 + indent as f32 * 6.0);
+                        match marker {
+                            ListMarker::Bullet => {
+                                ui.label("•");
+                            }
+                            ListMarker::Number(number) => {
+                                ui.label(format!("{}.", number));
+                            }
+                            ListMarker::Check(checked) => {
+                                let mut checked = checked;
+                                if ui.checkbox(&mut checked, "").changed() {
+                                    toggled_line = Some(line);
+                                }
+                            }
+                        }
+                        show_inline(ui, &text, None);
+                    });
+                }
+                Block::Quote(text) => {
+                    ui.horizontal(|ui| {
+                        ui.separator();
+                        ui.add_enabled_ui(false, |ui| show_inline(ui, &text, None));
+                    });
+                }
+                Block::Code(code) => {
+                    egui::Frame::group(ui.style())
+                        .fill(ui.visuals().extreme_bg_color)
+                        .show(ui, |ui| {
+                            ui.label(RichText::new(code).monospace());
+                        });
+                }
+                Block::Rule => {
+                    ui.separator();
+                }
+            });
+    });
+    if let Some(line) = toggled_line {
+        toggle_checklist_item(source, line);
+    }
+    toggled_line.is_some()
+}
+
+/// Markdown text input with a toggle between editing the source and previewing the result
+pub fn show_markdown_editor(ui: &mut Ui, source: &mut String, preview: &mut bool, hint: &str) {
+    ui.horizontal(|ui| {
+        ui.selectable_value(preview, false, "Edit");
+        ui.selectable_value(preview, true, "Preview");
+    });
+    if *preview {
+        if source.trim().is_empty() {
+            ui.weak("Nothing to preview");
+        } else {
+            show_markdown(ui, source);
+        }
+    } else {
+        ui.add(
+            egui::TextEdit::multiline(source)
+                .hint_text(hint)
+                .desired_rows(3),
+        )
+        .on_hover_text("Supports Markdown: # headings, - lists, - [ ] checklists, **bold**, *italic*, `code`, ``` code blocks and [links](https://example.com)");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str, bold: bool, italic: bool) -> Inline {
+        Inline::Text {
+            text: text.to_string(),
+            bold,
+            italic,
+            strikethrough: false,
+            code: false,
+        }
+    }
+
+    #[test]
+    fn parses_blocks() {
+        let source = "# Title\nfirst line\nsecond line\n\n- [ ] open\n  - [x] done\n2. two\n\
+                      > quoted\n---\n```\nlet a = 1;\n```";
+        assert_eq!(
+            parse_blocks(source),
+            vec![
+                Block::Heading {
+                    level: 1,
+                    text: "Title".to_string(),
+                },
+                Block::Paragraph("first line second line".to_string()),
+                Block::ListItem {
+                    indent: 0,
+                    marker: ListMarker::Check(false),
+                    text: "open".to_string(),
+                    line: 4,
+                },
+                Block::ListItem {
+                    indent: 2,
+                    marker: ListMarker::Check(true),
+                    text: "done".to_string(),
+                    line: 5,
+                },
+                Block::ListItem {
+                    indent: 0,
+                    marker: ListMarker::Number(2),
+                    text: "two".to_string(),
+                    line: 6,
+                },
+                Block::Quote("quoted".to_string()),
+                Block::Rule,
+                Block::Code("let a = 1;".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_code_block_runs_to_the_end() {
+        assert_eq!(
+            parse_blocks("```\n# not a heading"),
+            vec![Block::Code("# not a heading".to_string())]
+        );
+    }
+
+    #[test]
+    fn parses_emphasis() {
+        assert_eq!(
+            parse_inline("**bold** and *italic*"),
+            vec![
+                text("bold", true, false),
+                text(" and ", false, false),
+                text("italic", false, true),
+            ]
+        );
+        assert_eq!(
+            parse_inline("2 * 3 * 4"),
+            vec![text("2 * 3 * 4", false, false)]
+        );
+    }
+
+    #[test]
+    fn parses_links() {
+        assert_eq!(
+            parse_inline("[docs](https://example.com) or https://example.org."),
+            vec![
+                Inline::Link {
+                    text: "docs".to_string(),
+                    url: "https://example.com".to_string(),
+                },
+                text(" or ", false, false),
+                Inline::Link {
+                    text: "https://example.org".to_string(),
+                    url: "https://example.org".to_string(),
+                },
+                text(".", false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn toggles_only_the_checklist_marker() {
+        let mut source = "Intro\r\n- [ ] first [ ] \r\n- [x] second".to_string();
+        toggle_checklist_item(&mut source, 1);
+        assert_eq!(source, "Intro\r\n- [x] first [ ] \r\n- [x] second");
+        toggle_checklist_item(&mut source, 2);
+        assert_eq!(source, "Intro\r\n- [x] first [ ] \r\n- [ ] second");
+        // lines that are not checklist items are left alone
+        toggle_checklist_item(&mut source, 0);
+        toggle_checklist_item(&mut source, 5);
+        assert_eq!(source, "Intro\r\n- [x] first [ ] \r\n- [ ] second");
+    }
+}
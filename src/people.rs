@@ -0,0 +1,392 @@
+use crate::estimate::EstimateUnit;
+use crate::project::task::Task;
+use crate::project::Project;
+use crate::tag::default_tag_color;
+use chrono::NaiveDate;
+use egui::{Color32, Response, RichText, Ui};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Hours per week a new person is assumed to be available
+const DEFAULT_WEEKLY_CAPACITY: f32 = 40.0;
+
+/// Someone tasks can be assigned to
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Person {
+    uuid: Uuid,
+    pub name: String,
+    /// Short text shown on avatar chips
+    pub initials: String,
+    pub color: [u8; 3],
+    /// Hours of work available per week
+    pub weekly_capacity: f32,
+}
+
+impl Person {
+    pub fn new(name: &str) -> Self {
+        let name = name.trim();
+        let color = default_tag_color(name);
+        Self {
+            uuid: Uuid::new_v4(),
+            name: name.to_string(),
+            initials: initials_of(name),
+            color: [color.r(), color.g(), color.b()],
+            weekly_capacity: DEFAULT_WEEKLY_CAPACITY,
+        }
+    }
+
+    pub fn get_uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    pub fn get_color(&self) -> Color32 {
+        let [r, g, b] = self.color;
+        Color32::from_rgb(r, g, b)
+    }
+}
+
+/// The first letter of up to the first two words of a name
+fn initials_of(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Everyone in the workspace, along with which of them is the user of this planner
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PeopleDirectory {
+    pub people: Vec<Person>,
+    /// The person "my tasks" refers to
+    pub me: Option<Uuid>,
+}
+
+impl PeopleDirectory {
+    pub fn get(&self, uuid: Uuid) -> Option<&Person> {
+        self.people.iter().find(|person| person.uuid == uuid)
+    }
+
+    /// Remove a person from the directory and unassign them from every task
+    pub fn delete(&mut self, projects: &mut [Project], uuid: Uuid) {
+        self.people.retain(|person| person.uuid != uuid);
+        if self.me == Some(uuid) {
+            self.me = None;
+        }
+        projects
+            .iter_mut()
+            .flat_map(|project| project.tasks.iter_mut())
+            .for_each(|task| task.assignees.retain(|assignee| *assignee != uuid));
+    }
+}
+
+pub fn avatar_chip(ui: &mut Ui, person: &Person) -> Response {
+    ui.add(
+        egui::Button::new(
+            RichText::new(&person.initials)
+                .small()
+                .strong()
+                .color(Color32::WHITE),
+        )
+        .fill(person.get_color())
+        .rounding(10.0),
+    )
+    .on_hover_text(&person.name)
+}
+
+/// Avatar chips for every assignee that is still in the directory
+pub fn show_avatar_chips(ui: &mut Ui, assignees: &[Uuid], people: &PeopleDirectory) {
+    assignees
+        .iter()
+        .filter_map(|assignee| people.get(*assignee))
+        .for_each(|person| {
+            avatar_chip(ui, person);
+        });
+}
+
+/// Editable list of assignees, chips can be clicked to unassign
+pub fn show_assignee_editor(ui: &mut Ui, assignees: &mut Vec<Uuid>, people: &PeopleDirectory) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Assignees:");
+        let mut removed = None;
+        assignees
+            .iter()
+            .enumerate()
+            .filter_map(|(index, assignee)| people.get(*assignee).map(|person| (index, person)))
+            .for_each(|(index, person)| {
+                if avatar_chip(ui, person)
+                    .on_hover_text("Click to unassign")
+                    .clicked()
+                {
+                    removed = Some(index);
+                }
+            });
+        if let Some(index) = removed {
+            assignees.remove(index);
+        }
+
+        let unassigned: Vec<&Person> = people
+            .people
+            .iter()
+            .filter(|person| !assignees.contains(&person.uuid))
+            .collect();
+        if people.people.is_empty() {
+            ui.weak("Add people from the people window to assign tasks");
+        } else if !unassigned.is_empty() {
+            ui.menu_button("Assign", |ui| {
+                unassigned.iter().for_each(|person| {
+                    if ui.button(&person.name).clicked() {
+                        assignees.push(person.uuid);
+                        ui.close_menu();
+                    }
+                });
+            });
+        }
+    });
+}
+
+/// Narrows visible tasks down to those assigned to one person
+#[derive(Default, Clone)]
+pub struct AssigneeFilter {
+    pub person: Option<Uuid>,
+}
+
+impl AssigneeFilter {
+    pub fn is_active(&self) -> bool {
+        self.person.is_some()
+    }
+
+    pub fn matches_task(&self, task: &Task) -> bool {
+        self.person
+            .is_none_or(|person| task.assignees.contains(&person))
+    }
+
+    pub fn matches_project(&self, project: &Project) -> bool {
+        !self.is_active() || project.tasks.iter().any(|task| self.matches_task(task))
+    }
+
+    pub fn show_menu(&mut self, ui: &mut Ui, people: &PeopleDirectory) {
+        let title = match self.person {
+            None => "Assignee".to_string(),
+            Some(person) if Some(person) == people.me => "My tasks".to_string(),
+            Some(person) => people
+                .get(person)
+                .map(|person| format!("Assignee: {}", person.name))
+                .unwrap_or("Assignee".to_string()),
+        };
+        ui.menu_button(title, |ui| {
+            ui.selectable_value(&mut self.person, None, "Everyone");
+            match people.me {
+                Some(me) => {
+                    ui.selectable_value(&mut self.person, Some(me), "My tasks");
+                }
+                None => {
+                    ui.add_enabled(false, egui::Button::new("My tasks"))
+                        .on_disabled_hover_text("Pick who you are in the people window");
+                }
+            }
+            ui.separator();
+            people.people.iter().for_each(|person| {
+                ui.selectable_value(&mut self.person, Some(person.uuid), &person.name);
+            });
+        });
+        // drop people from the filter that no longer exist
+        if self
+            .person
+            .is_some_and(|person| people.get(person).is_none())
+        {
+            self.person = None;
+        }
+    }
+}
+
+/// Open work assigned to one person. Work on a task with several assignees is split evenly.
+pub struct WorkloadSummary {
+    pub open_tasks: usize,
+    pub overdue_tasks: usize,
+    /// Remaining estimated hours, after subtracting time already tracked
+    pub remaining_hours: f32,
+    pub remaining_story_points: f32,
+    /// Open tasks without an estimate
+    pub unestimated_tasks: usize,
+}
+
+impl WorkloadSummary {
+    pub fn for_person(projects: &[Project], person: Uuid, today: NaiveDate) -> Self {
+        let mut summary = Self {
+            open_tasks: 0,
+            overdue_tasks: 0,
+            remaining_hours: 0.0,
+            remaining_story_points: 0.0,
+            unestimated_tasks: 0,
+        };
+        projects.iter().for_each(|project| {
+            project
+                .tasks
+                .iter()
+                .filter(|task| !task.get_completed() && task.assignees.contains(&person))
+                .for_each(|task| {
+                    summary.open_tasks += 1;
+                    if task.due_date.is_some_and(|due_date| due_date < today) {
+                        summary.overdue_tasks += 1;
+                    }
+                    match project.estimate_unit {
+                        EstimateUnit::Hours => match remaining_hours_per_assignee(task) {
+                            Some(hours) => summary.remaining_hours += hours,
+                            None => summary.unestimated_tasks += 1,
+                        },
+                        EstimateUnit::StoryPoints => match task.estimate {
+                            Some(points) => {
+                                summary.remaining_story_points +=
+                                    points / task.assignees.len() as f32
+                            }
+                            None => summary.unestimated_tasks += 1,
+                        },
+                    }
+                });
+        });
+        summary
+    }
+
+    /// Weeks of work the remaining hours add up to at the given weekly capacity
+    pub fn weeks_of_work(&self, weekly_capacity: f32) -> Option<f32> {
+        (weekly_capacity > 0.0).then(|| self.remaining_hours / weekly_capacity)
+    }
+}
+
+/// Hours left on a task for each of its assignees, none for tasks without an estimate
+pub fn remaining_hours_per_assignee(task: &Task) -> Option<f32> {
+    let estimate = task.estimate?;
+    let tracked = task.tracked_time().num_minutes() as f32 / 60.0;
+    Some((estimate - tracked).max(0.0) / task.assignees.len().max(1) as f32)
+}
+
+/// State for the people window
+#[derive(Default)]
+pub struct PeopleManager {
+    pub open: bool,
+    next_name: String,
+}
+
+impl PeopleManager {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        people: &mut PeopleDirectory,
+        projects: &mut [Project],
+        today: NaiveDate,
+    ) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("People").open(&mut open).show(ctx, |ui| {
+            if people.people.is_empty() {
+                ui.label("Nobody here yet, add the people on your team to assign them tasks.");
+            }
+
+            let mut delete = None;
+            egui::Grid::new("people_grid").striped(true).show(ui, |ui| {
+                ui.label("");
+                ui.label("Name");
+                ui.label("Initials");
+                ui.label("Color");
+                ui.label("Hours per week");
+                ui.label("Me");
+                ui.end_row();
+                people.people.iter_mut().for_each(|person| {
+                    avatar_chip(ui, person);
+                    ui.add(egui::TextEdit::singleline(&mut person.name).desired_width(120.0));
+                    ui.add(egui::TextEdit::singleline(&mut person.initials).desired_width(30.0));
+                    ui.color_edit_button_srgb(&mut person.color);
+                    ui.add(
+                        egui::DragValue::new(&mut person.weekly_capacity)
+                            .speed(0.5)
+                            .clamp_range(0.0..=168.0)
+                            .suffix("h"),
+                    );
+                    ui.radio_value(&mut people.me, Some(person.uuid), "");
+                    if ui
+                        .button("Delete")
+                        .on_hover_text("Double click to delete and unassign this person everywhere")
+                        .double_clicked()
+                    {
+                        delete = Some(person.uuid);
+                    }
+                    ui.end_row();
+                });
+            });
+            if let Some(uuid) = delete {
+                people.delete(projects, uuid);
+            }
+
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.next_name)
+                        .hint_text("Name")
+                        .desired_width(120.0),
+                );
+                let submitted =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if (ui.button("Add person").clicked() || submitted)
+                    && !self.next_name.trim().is_empty()
+                {
+                    people.people.push(Person::new(&self.next_name));
+                    self.next_name.clear();
+                }
+            });
+
+            if people.people.is_empty() {
+                return;
+            }
+            ui.separator();
+            ui.heading("Workload");
+            egui::Grid::new("workload_summary_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.label("Open tasks");
+                    ui.label("Overdue");
+                    ui.label("Remaining");
+                    ui.label("Weeks of work");
+                    ui.end_row();
+                    people.people.iter().for_each(|person| {
+                        let summary = WorkloadSummary::for_person(projects, person.uuid, today);
+                        ui.horizontal(|ui| {
+                            avatar_chip(ui, person);
+                            ui.label(&person.name);
+                        });
+                        ui.label(summary.open_tasks.to_string());
+                        if summary.overdue_tasks > 0 {
+                            ui.colored_label(Color32::LIGHT_RED, summary.overdue_tasks.to_string());
+                        } else {
+                            ui.label("0");
+                        }
+                        let mut remaining = EstimateUnit::Hours.format(summary.remaining_hours);
+                        if summary.remaining_story_points > 0.0 {
+                            remaining = format!(
+                                "{}, {}",
+                                remaining,
+                                EstimateUnit::StoryPoints.format(summary.remaining_story_points)
+                            );
+                        }
+                        let remaining_label = ui.label(remaining);
+                        if summary.unestimated_tasks > 0 {
+                            remaining_label.on_hover_text(format!(
+                                "{} open tasks have no estimate",
+                                summary.unestimated_tasks
+                            ));
+                        }
+                        match summary.weeks_of_work(person.weekly_capacity) {
+                            Some(weeks) => ui.label(format!("{:.1}", weeks)),
+                            None => ui.weak("No capacity"),
+                        };
+                        ui.end_row();
+                    });
+                });
+        });
+        self.open = open;
+    }
+}
@@ -0,0 +1,132 @@
+use crate::markdown::show_markdown;
+use crate::timezone::TimeZoneSettings;
+use chrono::{DateTime, NaiveDate, Utc};
+use egui::{RichText, Ui};
+use serde::{Deserialize, Serialize};
+
+/// Something that happened to a task, recorded automatically
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub enum ActivityKind {
+    Created,
+    Renamed {
+        from: String,
+    },
+    Completed,
+    Reopened,
+    MovedToProject {
+        project_name: String,
+    },
+    DueDateChanged {
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    },
+}
+
+impl ActivityKind {
+    pub fn describe(&self, time_zones: &TimeZoneSettings) -> String {
+        match self {
+            ActivityKind::Created => "Created".to_string(),
+            ActivityKind::Renamed { from } => format!("Renamed from \"{}\"", from),
+            ActivityKind::Completed => "Completed".to_string(),
+            ActivityKind::Reopened => "Reopened".to_string(),
+            ActivityKind::MovedToProject { project_name } => {
+                format!("Moved to project \"{}\"", project_name)
+            }
+            ActivityKind::DueDateChanged { from, to } => match (from, to) {
+                (None, Some(to)) => format!("Due date set to {}", time_zones.format_date(*to)),
+                (Some(from), None) => {
+                    format!("Due date {} removed", time_zones.format_date(*from))
+                }
+                (Some(from), Some(to)) => format!(
+                    "Due date changed from {} to {}",
+                    time_zones.format_date(*from),
+                    time_zones.format_date(*to)
+                ),
+                (None, None) => "Due date changed".to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct ActivityEvent {
+    pub time: DateTime<Utc>,
+    pub kind: ActivityKind,
+}
+
+impl ActivityEvent {
+    pub fn new(kind: ActivityKind) -> Self {
+        Self {
+            time: Utc::now(),
+            kind,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Comment {
+    pub time: DateTime<Utc>,
+    /// Markdown text of the comment
+    pub text: String,
+}
+
+/// Show the comment thread of a task with an input for a new comment, newest comments last
+pub fn show_comments(
+    ui: &mut Ui,
+    comments: &mut Vec<Comment>,
+    next_comment: &mut String,
+    time_zones: &TimeZoneSettings,
+) {
+    let mut removed_comment = None;
+    comments
+        .iter_mut()
+        .enumerate()
+        .for_each(|(comment_index, comment)| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(time_zones.format_date_time(&comment.time)).weak());
+                if ui
+                    .small_button("Delete")
+                    .on_hover_text("Double click to delete the comment")
+                    .double_clicked()
+                {
+                    removed_comment = Some(comment_index);
+                }
+            });
+            show_markdown(ui, &mut comment.text);
+            ui.separator();
+        });
+    if let Some(comment_index) = removed_comment {
+        comments.remove(comment_index);
+    }
+    ui.add(
+        egui::TextEdit::multiline(next_comment)
+            .hint_text("Write a comment")
+            .desired_rows(2),
+    );
+    if ui
+        .add_enabled(
+            !next_comment.trim().is_empty(),
+            egui::Button::new("Add comment"),
+        )
+        .clicked()
+    {
+        comments.push(Comment {
+            time: Utc::now(),
+            text: next_comment.trim().to_string(),
+        });
+        next_comment.clear();
+    }
+}
+
+/// Show the activity log of a task, newest events first
+pub fn show_activity(ui: &mut Ui, activity: &[ActivityEvent], time_zones: &TimeZoneSettings) {
+    if activity.is_empty() {
+        ui.weak("No activity recorded");
+    }
+    activity.iter().rev().for_each(|event| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(time_zones.format_date_time(&event.time)).weak());
+            ui.label(event.kind.describe(time_zones));
+        });
+    });
+}
@@ -0,0 +1,218 @@
+use crate::project::task::{Priority, Task};
+use crate::project::Project;
+use crate::tag::add_tag;
+use crate::timezone::TimeZoneSettings;
+use chrono::{Local, NaiveDate};
+use egui::Ui;
+use uuid::Uuid;
+
+/// An edit applied to every selected task of a project at once
+#[derive(PartialEq, Clone, Debug)]
+pub enum BulkAction {
+    SetCompleted(bool),
+    Delete,
+    SetPriority(Priority),
+    AddTag(String),
+    RemoveTag(String),
+    SetDueDate(Option<NaiveDate>),
+    /// Move the tasks into the project with this uuid
+    MoveTo(Uuid),
+}
+
+impl BulkAction {
+    /// Description of the action for the undo history
+    pub fn get_text(&self, task_count: usize, time_zones: &TimeZoneSettings) -> String {
+        let action = match self {
+            BulkAction::SetCompleted(true) => "Complete".to_string(),
+            BulkAction::SetCompleted(false) => "Reopen".to_string(),
+            BulkAction::Delete => "Delete".to_string(),
+            BulkAction::SetPriority(priority) => {
+                format!("Set priority {} on", priority.get_text())
+            }
+            BulkAction::AddTag(tag) => format!("Tag #{} on", tag),
+            BulkAction::RemoveTag(tag) => format!("Remove #{} from", tag),
+            BulkAction::SetDueDate(Some(date)) => {
+                format!("Set due date {} on", time_zones.format_date(*date))
+            }
+            BulkAction::SetDueDate(None) => "Clear due date on".to_string(),
+            BulkAction::MoveTo(_) => "Move".to_string(),
+        };
+        format!("{} {} tasks", action, task_count)
+    }
+
+    /// Apply the action to the selected tasks of a project. Moving changes two projects and is
+    /// left to the caller.
+    pub fn apply(&self, project: &mut Project) {
+        let selected = project.tasks.iter_mut().filter(|task| task.selected);
+        match self {
+            BulkAction::SetCompleted(completed) => selected
+                .filter(|task| task.get_completed() != *completed)
+                .for_each(|task| task.set_completed(*completed)),
+            BulkAction::Delete => project.tasks.retain(|task| !task.selected),
+            BulkAction::SetPriority(priority) => {
+                selected.for_each(|task| task.priority = *priority)
+            }
+            BulkAction::AddTag(tag) => selected.for_each(|task| {
+                add_tag(&mut task.tags, tag);
+            }),
+            BulkAction::RemoveTag(tag) => {
+                selected.for_each(|task| task.tags.retain(|task_tag| task_tag != tag))
+            }
+            BulkAction::SetDueDate(due_date) => selected.for_each(|task| {
+                task.set_due_date(*due_date);
+                if due_date.is_none() {
+                    task.reminders.clear();
+                }
+            }),
+            BulkAction::MoveTo(_) => {}
+        }
+    }
+}
+
+/// Selection and bulk action inputs for the task list of the project editor
+#[derive(Clone)]
+pub struct BulkEditForm {
+    priority: Priority,
+    tag: String,
+    due_date: NaiveDate,
+    move_target: Option<Uuid>,
+    /// Row last clicked, the anchor for shift click range selection
+    last_clicked: Option<usize>,
+}
+
+impl Default for BulkEditForm {
+    fn default() -> Self {
+        Self {
+            priority: Priority::High,
+            tag: "".to_string(),
+            due_date: Local::now().date_naive(),
+            move_target: None,
+            last_clicked: None,
+        }
+    }
+}
+
+impl BulkEditForm {
+    /// Handle a click on the selection checkbox of a row. Shift clicking selects or deselects
+    /// every row between the last clicked row and this one.
+    pub fn select_row(&mut self, ui: &Ui, tasks: &mut [Task], index: usize, selected: bool) {
+        match self.last_clicked {
+            Some(anchor) if ui.input(|input| input.modifiers.shift) => {
+                tasks
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(task_index, _)| {
+                        (anchor.min(index)..=anchor.max(index)).contains(task_index)
+                    })
+                    .for_each(|(_, task)| task.selected = selected);
+            }
+            _ => {
+                if let Some(task) = tasks.get_mut(index) {
+                    task.selected = selected;
+                }
+            }
+        }
+        self.last_clicked = Some(index);
+    }
+
+    /// Show the bulk actions for the selected tasks, returns the action the user picked
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        tasks: &mut [Task],
+        project_uuid: Uuid,
+        move_targets: &[(Uuid, String)],
+    ) -> Option<BulkAction> {
+        let selected_count = tasks.iter().filter(|task| task.selected).count();
+        let mut action = None;
+        ui.horizontal(|ui| {
+            let mut all_selected = selected_count == tasks.len() && !tasks.is_empty();
+            if ui.checkbox(&mut all_selected, "Select all").changed() {
+                tasks
+                    .iter_mut()
+                    .for_each(|task| task.selected = all_selected);
+            }
+            ui.label(format!("{} selected", selected_count))
+                .on_hover_text("Shift click to select a range of tasks");
+        });
+        if selected_count == 0 {
+            return None;
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("Complete").clicked() {
+                action = Some(BulkAction::SetCompleted(true));
+            }
+            if ui.button("Reopen").clicked() {
+                action = Some(BulkAction::SetCompleted(false));
+            }
+            if ui
+                .button("Delete")
+                .on_hover_text("Double click to delete the selected tasks")
+                .double_clicked()
+            {
+                action = Some(BulkAction::Delete);
+            }
+        });
+        ui.horizontal(|ui| {
+            self.priority.show_picker(ui, "bulk_priority");
+            if ui.button("Set priority").clicked() {
+                action = Some(BulkAction::SetPriority(self.priority));
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.tag)
+                    .hint_text("Tag")
+                    .desired_width(100.0),
+            );
+            let tag = self.tag.trim().trim_start_matches('#').to_string();
+            if ui
+                .add_enabled(!tag.is_empty(), egui::Button::new("Add tag"))
+                .clicked()
+            {
+                action = Some(BulkAction::AddTag(tag.clone()));
+            }
+            if ui
+                .add_enabled(!tag.is_empty(), egui::Button::new("Remove tag"))
+                .clicked()
+            {
+                action = Some(BulkAction::RemoveTag(tag));
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.push_id("bulk_due_date", |ui| {
+                ui.add(egui_extras::DatePickerButton::new(&mut self.due_date));
+            });
+            if ui.button("Set due date").clicked() {
+                action = Some(BulkAction::SetDueDate(Some(self.due_date)));
+            }
+            if ui.button("Clear due date").clicked() {
+                action = Some(BulkAction::SetDueDate(None));
+            }
+        });
+        ui.horizontal(|ui| {
+            let selected_text = self
+                .move_target
+                .and_then(|target| move_targets.iter().find(|(uuid, _)| *uuid == target))
+                .map(|(_, name)| name.clone())
+                .unwrap_or("Select a project".to_string());
+            egui::ComboBox::from_id_source("bulk_move_target")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    move_targets
+                        .iter()
+                        .filter(|(uuid, _)| *uuid != project_uuid)
+                        .for_each(|(uuid, name)| {
+                            ui.selectable_value(&mut self.move_target, Some(*uuid), name);
+                        });
+                });
+            if let Some(target) = self.move_target {
+                if ui.button("Move to project").clicked() {
+                    action = Some(BulkAction::MoveTo(target));
+                }
+            }
+        });
+        action
+    }
+}
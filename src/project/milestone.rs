@@ -0,0 +1,157 @@
+use crate::project::task::Task;
+use crate::timezone::TimeZoneSettings;
+use chrono::NaiveDate;
+use egui::{Color32, Pos2, Sense, Shape, Stroke, Ui};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A named date in a project that a set of its tasks needs to be done by
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Milestone {
+    uuid: Uuid,
+    pub name: String,
+    pub date: NaiveDate,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MilestoneStatus {
+    /// Every linked task was completed on or before the milestone date
+    Met,
+    /// The date passed with linked tasks still open, or they were completed late
+    Missed,
+    /// A linked task is overdue or due after the milestone date
+    AtRisk,
+    Upcoming,
+}
+
+impl MilestoneStatus {
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            MilestoneStatus::Met => "Met",
+            MilestoneStatus::Missed => "Missed",
+            MilestoneStatus::AtRisk => "At risk",
+            MilestoneStatus::Upcoming => "Upcoming",
+        }
+    }
+
+    pub fn get_color(&self) -> Color32 {
+        match self {
+            MilestoneStatus::Met => Color32::from_rgb(90, 180, 100),
+            MilestoneStatus::Missed => Color32::LIGHT_RED,
+            MilestoneStatus::AtRisk => Color32::from_rgb(255, 170, 60),
+            MilestoneStatus::Upcoming => Color32::LIGHT_BLUE,
+        }
+    }
+}
+
+impl Milestone {
+    pub fn new(name: String, date: NaiveDate) -> Self {
+        Self {
+            uuid: Uuid::new_v4(),
+            name,
+            date,
+        }
+    }
+
+    pub fn get_uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    /// The tasks linked to this milestone
+    pub fn linked_tasks<'a>(&self, tasks: &'a [Task]) -> impl Iterator<Item = &'a Task> {
+        let uuid = self.uuid;
+        tasks
+            .iter()
+            .filter(move |task| task.milestone == Some(uuid))
+    }
+
+    /// Status of the milestone from the state of its linked tasks. A milestone without linked
+    /// tasks is met once its date has passed.
+    pub fn status(&self, tasks: &[Task], today: NaiveDate) -> MilestoneStatus {
+        let linked: Vec<&Task> = self.linked_tasks(tasks).collect();
+        let open: Vec<&&Task> = linked.iter().filter(|task| !task.get_completed()).collect();
+        if open.is_empty() {
+            let completed_late = linked.iter().any(|task| {
+                task.get_complete_date()
+                    .is_some_and(|completed| completed.date_naive() > self.date)
+            });
+            return if completed_late {
+                MilestoneStatus::Missed
+            } else if linked.is_empty() && today <= self.date {
+                MilestoneStatus::Upcoming
+            } else {
+                MilestoneStatus::Met
+            };
+        }
+        if today > self.date {
+            return MilestoneStatus::Missed;
+        }
+        let at_risk = open.iter().any(|task| {
+            task.due_date
+                .is_some_and(|due_date| due_date < today || due_date > self.date)
+        });
+        if at_risk {
+            MilestoneStatus::AtRisk
+        } else {
+            MilestoneStatus::Upcoming
+        }
+    }
+}
+
+/// A diamond marking a milestone on a timeline
+pub fn diamond(center: Pos2, radius: f32, color: Color32) -> Shape {
+    Shape::convex_polygon(
+        vec![
+            center + egui::vec2(0.0, -radius),
+            center + egui::vec2(radius, 0.0),
+            center + egui::vec2(0.0, radius),
+            center + egui::vec2(-radius, 0.0),
+        ],
+        color,
+        Stroke::new(1.0, Color32::BLACK),
+    )
+}
+
+/// A bar spanning the project dates with a line for today and a diamond for every milestone
+pub fn show_timeline(
+    ui: &mut Ui,
+    (start, end): (NaiveDate, NaiveDate),
+    today: NaiveDate,
+    milestones: &[(Milestone, MilestoneStatus)],
+    time_zones: &TimeZoneSettings,
+) {
+    let width = ui.available_width().clamp(200.0, 600.0);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, 24.0), Sense::hover());
+    let painter = ui.painter_at(rect.expand(2.0));
+    let span = (end - start).num_days().max(1) as f32;
+    let x_of = |date: NaiveDate| {
+        let offset = ((date - start).num_days() as f32 / span).clamp(0.0, 1.0);
+        rect.left() + 8.0 + offset * (rect.width() - 16.0)
+    };
+
+    let bar = egui::Rect::from_x_y_ranges(
+        rect.left() + 8.0..=rect.right() - 8.0,
+        rect.center().y - 3.0..=rect.center().y + 3.0,
+    );
+    painter.rect_filled(bar, 3.0, ui.visuals().widgets.inactive.bg_fill);
+    if today >= start && today <= end {
+        let x = x_of(today);
+        painter.line_segment(
+            [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+            Stroke::new(1.5, Color32::LIGHT_RED),
+        );
+    }
+
+    milestones.iter().for_each(|(milestone, status)| {
+        let center = egui::pos2(x_of(milestone.date), rect.center().y);
+        painter.add(diamond(center, 7.0, status.get_color()));
+        let marker = egui::Rect::from_center_size(center, egui::vec2(14.0, 14.0));
+        ui.interact(marker, ui.id().with(milestone.uuid), Sense::hover())
+            .on_hover_text(format!(
+                "{}, {} ({})",
+                milestone.name,
+                time_zones.format_date(milestone.date),
+                status.get_text()
+            ));
+    });
+}
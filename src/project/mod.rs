@@ -1,13 +1,40 @@
-use crate::project::task::Task;
-use chrono::{DateTime, Local, NaiveDate};
+use crate::attachment::{Attachment, AttachmentForm};
+use crate::calendar::{HolidayForm, WorkCalendar};
+use crate::command::TaskCursor;
+use crate::estimate::{EstimateReport, EstimateUnit};
+use crate::folder;
+use crate::ical;
+use crate::markdown::{show_markdown, show_markdown_editor};
+use crate::people::{show_assignee_editor, AssigneeFilter, PeopleDirectory};
+use crate::project::activity::{show_activity, show_comments, ActivityKind};
+use crate::project::bulk::{BulkAction, BulkEditForm};
+use crate::project::milestone::{diamond, show_timeline, Milestone, MilestoneStatus};
+use crate::project::restructure::RestructureMode;
+use crate::project::task::{Task, TaskDisplayAction};
+use crate::recurrence::Recurrence;
+use crate::reminder::{show_reminder_editor, Reminder};
+use crate::tag::{add_tag, show_tag_chips, show_tag_editor, TagFilter, TagRegistry};
+use crate::time_tracking::{format_duration, TimeEntryForm};
+use crate::timezone::{self, time_zone_picker, zone_name, TimeZoneSettings};
+use crate::transfer::{self, TaskClipboard, TaskRef, TransferKind};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
 use egui::scroll_area::ScrollBarVisibility;
-use egui::{ScrollArea, Ui};
+use egui::{Color32, ScrollArea, Sense, Ui};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
 use uuid::Uuid;
 
-mod task;
+pub mod activity;
+pub mod bulk;
+pub mod milestone;
+pub mod reorder;
+pub mod restructure;
+pub mod task;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(default)]
 pub struct Project {
     pub project_name: String,
@@ -24,7 +51,7 @@ pub struct Project {
     #[serde(skip)]
     next_task_description: String,
 
-    creation_date: DateTime<Local>,
+    creation_date: DateTime<Utc>,
 
     #[serde(skip)]
     task_editing: bool,
@@ -34,12 +61,129 @@ pub struct Project {
 
     pub description: String,
 
+    #[serde(skip)]
+    description_preview: bool,
+
+    #[serde(skip)]
+    task_description_preview: bool,
+
+    /// Text of the task being edited from before the user started renaming it
+    #[serde(skip)]
+    task_rename_from: Option<String>,
+
+    #[serde(skip)]
+    next_comment: String,
+
+    pub attachments: Vec<Attachment>,
+
+    #[serde(skip)]
+    attachment_form: AttachmentForm,
+
+    #[serde(skip)]
+    task_attachment_form: AttachmentForm,
+
+    pub milestones: Vec<Milestone>,
+
+    #[serde(skip)]
+    next_milestone_name: String,
+
+    #[serde(skip)]
+    next_milestone_date: NaiveDate,
+
+    /// Uuid of the folder this project is grouped in
+    pub folder: Option<Uuid>,
+
+    /// Pinned projects are listed before every other project
+    pub pinned: bool,
+
     uuid: Uuid,
+
+    #[serde(skip)]
+    reveal_requested: bool,
+
+    pub tags: Vec<String>,
+
+    #[serde(skip)]
+    next_project_tag: String,
+
+    #[serde(skip)]
+    next_task_tag: String,
+
+    #[serde(skip)]
+    time_entry_form: TimeEntryForm,
+
+    pub estimate_unit: EstimateUnit,
+
+    #[serde(skip)]
+    ical_path: String,
+
+    #[serde(skip)]
+    ical_status: Option<Result<String, String>>,
+
+    /// Reminders relative to the project end date
+    pub reminders: Vec<Reminder>,
+
+    /// Work calendar used instead of the workspace calendar for this project
+    pub calendar_override: Option<WorkCalendar>,
+
+    #[serde(skip)]
+    holiday_form: HolidayForm,
+
+    /// Zone the project dates are in, falling back to the workspace time zone
+    pub time_zone: Option<Tz>,
+
+    #[serde(skip)]
+    template_name: String,
+
+    #[serde(skip)]
+    bulk_edit_form: BulkEditForm,
+}
+
+/// Workspace wide state that projects need to display themselves
+pub struct ProjectContext<'a> {
+    pub tag_registry: &'a TagRegistry,
+    pub tag_filter: &'a TagFilter,
+    pub calendar: &'a WorkCalendar,
+    pub time_zones: &'a TimeZoneSettings,
+    pub task_clipboard: &'a TaskClipboard,
+    /// Uuid and name of every project, for choosing where to move tasks
+    pub move_targets: &'a [(Uuid, String)],
+    pub attachments_dir: Option<&'a Path>,
+    pub people: &'a PeopleDirectory,
+    pub assignee_filter: &'a AssigneeFilter,
+    pub task_cursor: &'a TaskCursor,
+}
+
+/// Whether a task of the project passes the tag and assignee filters
+pub fn task_shown(
+    project: &Project,
+    task: &Task,
+    tag_filter: &TagFilter,
+    assignee_filter: &AssigneeFilter,
+) -> bool {
+    tag_filter.matches_task(project, task) && assignee_filter.matches_task(task)
 }
 
 pub enum ProjectDisplayAction {
     EditClicked,
     CloseEditWindow,
+    /// A timer was started on the task with this uuid, any other running timer should stop
+    TimerStarted(Uuid),
+    /// The user asked to save the project as a template with this name
+    SaveAsTemplate(String),
+    /// The user asked to duplicate, split or merge the project
+    Restructure(RestructureMode),
+    /// The user asked to paste the task clipboard into this project
+    PasteTasks,
+    /// Dragged tasks were dropped onto this project
+    TasksDropped(TransferKind),
+    /// The user picked a bulk action for the selected tasks of this project
+    BulkEdit(BulkAction),
+    /// A task was dragged to another place in the task list, see [`reorder::move_task`]
+    ReorderTask {
+        task: Uuid,
+        to: usize,
+    },
     None,
 }
 
@@ -58,38 +202,319 @@ impl Project {
             tasks: vec![],
             next_task_text: "".to_string(),
             next_task_description: "".to_string(),
-            creation_date: Local::now(),
+            creation_date: Utc::now(),
             task_editing: false,
             selected_editing_task: 0,
             description,
+            description_preview: false,
+            task_description_preview: false,
+            task_rename_from: None,
+            next_comment: "".to_string(),
+            attachments: vec![],
+            attachment_form: AttachmentForm::default(),
+            task_attachment_form: AttachmentForm::default(),
+            milestones: vec![],
+            next_milestone_name: "".to_string(),
+            next_milestone_date: end_date,
+            folder: None,
+            pinned: false,
             uuid: Uuid::new_v4(),
+            reveal_requested: false,
+            tags: vec![],
+            next_project_tag: "".to_string(),
+            next_task_tag: "".to_string(),
+            time_entry_form: TimeEntryForm::default(),
+            estimate_unit: EstimateUnit::default(),
+            ical_path: "".to_string(),
+            ical_status: None,
+            reminders: vec![],
+            calendar_override: None,
+            holiday_form: HolidayForm::default(),
+            time_zone: None,
+            template_name: "".to_string(),
+            bulk_edit_form: BulkEditForm::default(),
+        }
+    }
+
+    pub fn get_uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    pub fn get_creation_date(&self) -> &DateTime<Utc> {
+        &self.creation_date
+    }
+
+    /// Move the task with this uuid in front of the task at `to`, see [`reorder::move_task`]
+    pub fn move_task(&mut self, task: Uuid, to: usize) {
+        if let Some(from) = self.tasks.iter().position(|other| other.get_uuid() == task) {
+            reorder::move_task(&mut self.tasks, from, to);
         }
     }
 
-    pub fn show(&mut self, ui: &mut Ui) -> ProjectDisplayAction {
+    /// Open the task with this uuid in the task editor of the project editor
+    pub fn edit_task(&mut self, task: Uuid) {
+        if let Some(task_index) = self.tasks.iter().position(|other| other.get_uuid() == task) {
+            self.task_editing = true;
+            self.selected_editing_task = task_index;
+        }
+    }
+
+    /// Share of tasks completed, from 0 to 1. A project without tasks has made no progress.
+    pub fn progress(&self) -> f32 {
+        if self.tasks.is_empty() {
+            return 0.0;
+        }
+        let completed = self
+            .tasks
+            .iter()
+            .filter(|task| task.get_completed())
+            .count();
+        completed as f32 / self.tasks.len() as f32
+    }
+
+    /// Whether the project has tasks and every one of them is completed
+    pub fn is_completed(&self) -> bool {
+        !self.tasks.is_empty() && self.tasks.iter().all(|task| task.get_completed())
+    }
+
+    /// The time zone of this project, falling back to the workspace time zone. None is the system
+    /// time zone.
+    pub fn zone(&self, time_zones: &TimeZoneSettings) -> Option<Tz> {
+        self.time_zone.or(time_zones.workspace)
+    }
+
+    /// The current date in the time zone of this project
+    pub fn today(&self, time_zones: &TimeZoneSettings) -> NaiveDate {
+        timezone::today_in(self.zone(time_zones))
+    }
+
+    /// The moment the project is due, the end of its end date in the project time zone
+    pub fn deadline(&self, time_zones: &TimeZoneSettings) -> Option<DateTime<Utc>> {
+        timezone::end_of_day(self.end_date, self.zone(time_zones))
+    }
+
+    /// Number of days from today until the project end date, negative if the project is overdue
+    pub fn days_until_due(&self, time_zones: &TimeZoneSettings) -> i64 {
+        self.end_date
+            .signed_duration_since(self.today(time_zones))
+            .num_days()
+    }
+
+    /// List of milestones with inputs to rename, move and delete them, and to add new ones
+    fn show_milestone_editor(&mut self, ui: &mut Ui) {
+        let mut removed = None;
+        self.milestones
+            .iter_mut()
+            .enumerate()
+            .for_each(|(milestone_index, milestone)| {
+                ui.horizontal(|ui| {
+                    ui.push_id(milestone.get_uuid(), |ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut milestone.name).desired_width(120.0),
+                        );
+                        ui.add(egui_extras::DatePickerButton::new(&mut milestone.date));
+                    });
+                    let linked = milestone.linked_tasks(&self.tasks).count();
+                    ui.weak(format!("{} tasks", linked));
+                    if ui
+                        .button("Delete")
+                        .on_hover_text(
+                            "Double click to delete the milestone, its tasks are unlinked",
+                        )
+                        .double_clicked()
+                    {
+                        removed = Some(milestone_index);
+                    }
+                });
+            });
+        if let Some(milestone_index) = removed {
+            let milestone = self.milestones.remove(milestone_index);
+            self.tasks
+                .iter_mut()
+                .filter(|task| task.milestone == Some(milestone.get_uuid()))
+                .for_each(|task| task.milestone = None);
+        }
+
+        if self.next_milestone_date == NaiveDate::default() {
+            self.next_milestone_date = self.end_date;
+        }
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.next_milestone_name)
+                    .hint_text("Milestone name")
+                    .desired_width(120.0),
+            );
+            ui.push_id("next_milestone_date", |ui| {
+                ui.add(egui_extras::DatePickerButton::new(
+                    &mut self.next_milestone_date,
+                ));
+            });
+            if ui
+                .add_enabled(
+                    !self.next_milestone_name.trim().is_empty(),
+                    egui::Button::new("Add milestone"),
+                )
+                .clicked()
+            {
+                self.milestones.push(Milestone::new(
+                    self.next_milestone_name.trim().to_string(),
+                    self.next_milestone_date,
+                ));
+                self.milestones.sort_by_key(|milestone| milestone.date);
+                self.next_milestone_name.clear();
+            }
+        });
+        if self
+            .milestones
+            .iter()
+            .any(|milestone| milestone.date < self.start_date || milestone.date > self.end_date)
+        {
+            ui.colored_label(
+                Color32::LIGHT_RED,
+                "Some milestones are outside the project dates",
+            );
+        }
+    }
+
+    /// The work calendar of this project, falling back to the workspace calendar
+    pub fn calendar<'a>(&'a self, workspace_calendar: &'a WorkCalendar) -> &'a WorkCalendar {
+        self.calendar_override
+            .as_ref()
+            .unwrap_or(workspace_calendar)
+    }
+
+    /// Number of working days left until the project end date, negative if the project is overdue
+    pub fn working_days_until_due(
+        &self,
+        workspace_calendar: &WorkCalendar,
+        time_zones: &TimeZoneSettings,
+    ) -> i64 {
+        self.calendar(workspace_calendar)
+            .working_days_until(self.today(time_zones), self.end_date)
+    }
+
+    /// Number of working days from the start date through the end date
+    pub fn working_days_duration(&self, workspace_calendar: &WorkCalendar) -> i64 {
+        self.calendar(workspace_calendar)
+            .count_working_days(self.start_date, self.end_date)
+    }
+
+    /// Scroll this project into view and expand its task list the next time it is shown
+    pub fn reveal(&mut self) {
+        self.reveal_requested = true;
+    }
+
+    /// Total time tracked across every task in this project
+    pub fn tracked_time(&self) -> Duration {
+        self.tasks
+            .iter()
+            .fold(Duration::zero(), |total, task| total + task.tracked_time())
+    }
+
+    pub fn open_task_count(&self) -> usize {
+        self.tasks
+            .iter()
+            .filter(|task| !task.get_completed())
+            .count()
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, context: &ProjectContext) -> ProjectDisplayAction {
         let mut action = ProjectDisplayAction::None;
+        let tag_registry = context.tag_registry;
+        let tag_filter = context.tag_filter;
+        let has_estimates = self.tasks.iter().any(|task| task.estimate.is_some());
+        let estimate_report =
+            EstimateReport::for_project(self, context.calendar, context.time_zones);
+        let top_left = ui.cursor().min;
         ui.horizontal(|ui| {
             ui.push_id(self.uuid, |ui| {
                 ScrollArea::horizontal()
                     .scroll_bar_visibility(ScrollBarVisibility::VisibleWhenNeeded)
                     .show(ui, |ui| {
-                        ui.heading(&self.project_name);
+                        let handle = ui
+                            .add(egui::Label::new("☰").sense(Sense::drag()))
+                            .on_hover_text("Drag onto a folder to move the project");
+                        if handle.drag_started() {
+                            folder::start_project_drag(ui.ctx(), self.uuid);
+                        }
+                        ui.toggle_value(&mut self.pinned, "Pin")
+                            .on_hover_text("Pinned projects are listed first");
+                        let heading = ui.heading(&self.project_name);
+                        if self.reveal_requested {
+                            heading.scroll_to_me(Some(egui::Align::TOP));
+                        }
                         ui.separator();
-                        if !self.description.is_empty() {
-                            ui.label(&self.description);
+                        if !self.tags.is_empty() {
+                            show_tag_chips(ui, &self.tags, tag_registry);
                             ui.separator();
                         }
-                        ui.label(format!("Start date: {}", self.start_date));
+                        ui.label(format!(
+                            "Start date: {}",
+                            context.time_zones.format_date(self.start_date)
+                        ));
+                        ui.separator();
+                        let end_date = ui.label(format!(
+                            "End date: {}",
+                            context.time_zones.format_date(self.end_date)
+                        ));
+                        if let Some(deadline) = self.deadline(context.time_zones) {
+                            end_date.on_hover_text(format!(
+                                "Due {} ({}), {} in your time zone",
+                                context.time_zones.format_date(self.end_date),
+                                zone_name(self.zone(context.time_zones)),
+                                context.time_zones.format_date_time(&deadline)
+                            ));
+                        }
+                        ui.separator();
+                        ui.label(format!(
+                            "Created: {}",
+                            context
+                                .time_zones
+                                .format_date(context.time_zones.display_date(&self.creation_date))
+                        ))
+                        .on_hover_text(
+                            context.time_zones.format_date_time(&self.creation_date),
+                        );
                         ui.separator();
-                        ui.label(format!("End date: {}", self.end_date));
+                        if !self.tasks.is_empty() {
+                            ui.label(format!("Progress: {:.0}%", self.progress() * 100.0));
+                            ui.separator();
+                        }
+                        ui.label(format!(
+                            "Duration: {} working days",
+                            self.working_days_duration(context.calendar)
+                        ));
                         ui.separator();
-                        let time_left= self.end_date.signed_duration_since(Local::now().date_naive());
-                        ui.label(format!("Days until due: {}", time_left.num_days()));
+                        ui.label(format!(
+                            "Working days until due: {}",
+                            self.working_days_until_due(context.calendar, context.time_zones)
+                        ))
+                        .on_hover_text(format!(
+                            "{} calendar days",
+                            self.days_until_due(context.time_zones)
+                        ));
                         ui.separator();
+                        let tracked = self.tracked_time();
+                        if tracked > Duration::zero() {
+                            ui.label(format!("Tracked: {}", format_duration(tracked)));
+                            ui.separator();
+                        }
+                        if has_estimates && estimate_report.is_over_capacity() {
+                            ui.colored_label(Color32::LIGHT_RED, "Over capacity")
+                                .on_hover_text("Remaining estimate exceeds the working time left before the end date");
+                            ui.separator();
+                        }
                         if ui.button("Edit").clicked() {
                             action = ProjectDisplayAction::EditClicked
                         }
                         ui.separator();
+                        if !context.task_clipboard.is_empty()
+                            && ui
+                                .button(format!("Paste {} tasks", context.task_clipboard.len()))
+                                .clicked()
+                        {
+                            action = ProjectDisplayAction::PasteTasks;
+                        }
                     });
             });
         });
@@ -97,26 +522,207 @@ impl Project {
 
         self.tasks.retain(|task| !task.marked_for_deletion);
 
+        let cursor_here = context.task_cursor.scroll
+            && context
+                .task_cursor
+                .task
+                .is_some_and(|cursor| cursor.project == self.uuid);
+        let open_tasks = (self.reveal_requested || cursor_here).then_some(true);
+        self.reveal_requested = false;
+
         ui.push_id(self.uuid, |ui| {
-            ui.collapsing("Tasks", |ui| {
-                self.tasks.iter_mut().for_each(|task| {
-                    task.show(ui);
+            if !self.milestones.is_empty() {
+                let today = self.today(context.time_zones);
+                let milestones: Vec<(Milestone, MilestoneStatus)> = self
+                    .milestones
+                    .iter()
+                    .map(|milestone| (milestone.clone(), milestone.status(&self.tasks, today)))
+                    .collect();
+                show_timeline(
+                    ui,
+                    (self.start_date, self.end_date),
+                    today,
+                    &milestones,
+                    context.time_zones,
+                );
+                ui.collapsing(format!("Milestones ({})", milestones.len()), |ui| {
+                    milestones.iter().for_each(|(milestone, status)| {
+                        ui.horizontal(|ui| {
+                            let (marker, _) =
+                                ui.allocate_exact_size(egui::vec2(14.0, 14.0), Sense::hover());
+                            ui.painter()
+                                .add(diamond(marker.center(), 6.0, status.get_color()));
+                            ui.label(format!(
+                                "{}: {}",
+                                context.time_zones.format_date(milestone.date),
+                                milestone.name
+                            ));
+                            ui.colored_label(status.get_color(), status.get_text());
+                            let linked: Vec<&Task> = milestone.linked_tasks(&self.tasks).collect();
+                            if !linked.is_empty() {
+                                ui.weak(format!(
+                                    "{}/{} tasks done",
+                                    linked.iter().filter(|task| task.get_completed()).count(),
+                                    linked.len()
+                                ));
+                            }
+                        });
+                    });
+                });
+            }
+            if !self.description.is_empty() {
+                egui::CollapsingHeader::new("Description")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        show_markdown(ui, &mut self.description);
+                    });
+            }
+            if !self.attachments.is_empty() {
+                ui.collapsing(format!("Attachments ({})", self.attachments.len()), |ui| {
+                    self.attachment_form
+                        .show_list(ui, &self.attachments, context.attachments_dir);
+                });
+            }
+            if has_estimates {
+                ui.collapsing("Estimates", |ui| {
+                    estimate_report.show(ui);
+                });
+            }
+            egui::CollapsingHeader::new("Tasks")
+                .open(open_tasks)
+                .show(ui, |ui| {
+                    let shown: Vec<bool> = self
+                        .tasks
+                        .iter()
+                        .map(|task| task_shown(self, task, tag_filter, context.assignee_filter))
+                        .collect();
+                    let project_uuid = self.uuid;
+                    let project_dates = (self.start_date, self.end_date);
+                    let mut rows = vec![];
+                    self.tasks
+                        .iter_mut()
+                        .enumerate()
+                        .filter(|(task_index, _)| shown[*task_index])
+                        .for_each(|(task_index, task)| {
+                            let top = ui.cursor().top();
+                            let task_action = task.show(ui, context, project_dates);
+                            let row = egui::Rect::from_x_y_ranges(
+                                ui.min_rect().x_range(),
+                                top..=ui.cursor().top(),
+                            );
+                            rows.push((task_index, row));
+                            if context.task_cursor.is_on(task.get_uuid()) {
+                                ui.painter().rect_stroke(
+                                    row,
+                                    2.0,
+                                    egui::Stroke::new(1.5, ui.visuals().selection.stroke.color),
+                                );
+                                if context.task_cursor.scroll {
+                                    ui.scroll_to_rect(row, None);
+                                }
+                            }
+                            match task_action {
+                                TaskDisplayAction::TimerStarted => {
+                                    action = ProjectDisplayAction::TimerStarted(task.get_uuid());
+                                }
+                                TaskDisplayAction::DragStarted => {
+                                    transfer::start_drag(
+                                        ui.ctx(),
+                                        TaskRef {
+                                            project: project_uuid,
+                                            task: task.get_uuid(),
+                                        },
+                                    );
+                                }
+                                TaskDisplayAction::None => {}
+                            }
+                        });
+
+                    // tasks dragged within their own project are reordered
+                    let dragged = transfer::drag_payload(ui.ctx())
+                        .filter(|payload| payload.from.project == project_uuid);
+                    let pointer = ui.input(|input| input.pointer.hover_pos());
+                    if let Some((payload, (to, y))) = dragged
+                        .zip(pointer.and_then(|pointer| reorder::drop_position(&rows, pointer)))
+                    {
+                        reorder::paint_insertion_line(ui, ui.min_rect().x_range(), y);
+                        if ui.input(|input| input.pointer.any_released()) {
+                            action = ProjectDisplayAction::ReorderTask {
+                                task: payload.from.task,
+                                to,
+                            };
+                        }
+                    }
                 });
-            });
         });
 
+        // the whole project is a drop target for tasks dragged out of other projects
+        let drop_area = egui::Rect::from_min_max(
+            top_left,
+            egui::pos2(ui.max_rect().right(), ui.cursor().top()),
+        )
+        .intersect(ui.clip_rect());
+        let dragging_in = transfer::drag_payload(ui.ctx())
+            .is_some_and(|payload| payload.from.project != self.uuid);
+        let pointer_inside = ui
+            .input(|input| input.pointer.hover_pos())
+            .is_some_and(|pointer| drop_area.contains(pointer));
+        if dragging_in && pointer_inside {
+            ui.painter().rect_stroke(
+                drop_area,
+                2.0,
+                egui::Stroke::new(2.0, ui.visuals().selection.bg_fill),
+            );
+            if ui.input(|input| input.pointer.any_released()) {
+                let kind = if ui.input(|input| input.modifiers.command) {
+                    TransferKind::Copy
+                } else {
+                    TransferKind::Move
+                };
+                action = ProjectDisplayAction::TasksDropped(kind);
+            }
+        }
+
         action
     }
 
-    pub fn show_edit_window(&mut self, ctx: &egui::Context) -> ProjectDisplayAction {
+    pub fn show_edit_window(
+        &mut self,
+        ctx: &egui::Context,
+        context: &ProjectContext,
+    ) -> ProjectDisplayAction {
         let mut action = ProjectDisplayAction::None;
+        let tag_registry = context.tag_registry;
         egui::Window::new("Project Editor").show(ctx, |ui| {
             ui.text_edit_singleline(&mut self.project_name)
                 .on_hover_text("Project name");
-            ui.text_edit_multiline(&mut self.description)
-                .on_hover_text("Project description");
-            let mut start_date = self.start_date.clone();
-            let mut end_date = self.end_date.clone();
+            show_markdown_editor(
+                ui,
+                &mut self.description,
+                &mut self.description_preview,
+                "Project description",
+            );
+            show_tag_editor(ui, &mut self.tags, &mut self.next_project_tag, tag_registry);
+            show_reminder_editor(ui, &mut self.reminders);
+            ui.collapsing(format!("Attachments ({})", self.attachments.len()), |ui| {
+                self.attachment_form
+                    .show(ui, &mut self.attachments, context.attachments_dir);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Estimate unit:");
+                ui.selectable_value(
+                    &mut self.estimate_unit,
+                    EstimateUnit::Hours,
+                    EstimateUnit::Hours.get_text(),
+                );
+                ui.selectable_value(
+                    &mut self.estimate_unit,
+                    EstimateUnit::StoryPoints,
+                    EstimateUnit::StoryPoints.get_text(),
+                );
+            });
+            let mut start_date = self.start_date;
+            let mut end_date = self.end_date;
             ui.horizontal(|ui| {
                 ui.label("Start date:");
                 ui.push_id(3, |ui| {
@@ -134,6 +740,16 @@ impl Project {
                 self.start_date = start_date;
                 self.end_date = end_date;
             }
+            ui.horizontal(|ui| {
+                ui.label("Time zone:");
+                time_zone_picker(ui, "project_time_zone", &mut self.time_zone);
+            })
+            .response
+            .on_hover_text("The system time zone here means the workspace time zone is used");
+
+            ui.collapsing(format!("Milestones ({})", self.milestones.len()), |ui| {
+                self.show_milestone_editor(ui);
+            });
 
             ui.label("Task name: ");
             ui.text_edit_singleline(&mut self.next_task_text);
@@ -153,20 +769,31 @@ impl Project {
                     .max_height(150.0)
                     .scroll_bar_visibility(ScrollBarVisibility::VisibleWhenNeeded)
                     .show(ui, |ui| {
-                        let (mut re_aranged, mut re_aranged_task_index, mut re_aranged_new_index) =
-                            (false, 0, 0);
-                        let task_length = self.tasks.len();
+                        let mut rows = vec![];
+                        let mut dragged = None;
+                        let mut released = None;
+                        let mut clicked_row = None;
                         self.tasks
                             .iter_mut()
                             .enumerate()
                             .for_each(|(task_index, task)| {
-                                let mut new_index = task_index;
-                                ui.horizontal(|ui| {
-                                    ui.add(
-                                        egui::widgets::DragValue::new(&mut new_index)
-                                            .clamp_range(0..=(task_length - 1)),
-                                    );
-                                    ui.label(format!("{}", task.text));
+                                let row = ui.horizontal(|ui| {
+                                    let mut selected = task.selected;
+                                    if ui.checkbox(&mut selected, "").changed() {
+                                        clicked_row = Some((task_index, selected));
+                                    }
+                                    let handle = ui
+                                        .add(egui::Label::new("☰").sense(Sense::drag()))
+                                        .on_hover_text(
+                                            "Drag to reorder, or select and press Alt+Up/Down",
+                                        );
+                                    if handle.dragged() {
+                                        dragged = Some(task.get_uuid());
+                                    }
+                                    if handle.drag_released() {
+                                        released = Some(task.get_uuid());
+                                    }
+                                    ui.label(&task.text);
                                     if ui.button("Edit").clicked() {
                                         self.task_editing = true;
                                         self.selected_editing_task = task_index;
@@ -175,26 +802,129 @@ impl Project {
                                         task.marked_for_deletion = true;
                                     }
                                 });
-                                if new_index != task_index {
-                                    re_aranged = true;
-                                    re_aranged_task_index = task_index;
-                                    re_aranged_new_index = new_index;
-                                }
+                                rows.push((task_index, row.response.rect));
                             });
-                        if re_aranged {
-                            if let Some(task_clone) = self.tasks.get(re_aranged_task_index).cloned()
-                            {
-                                self.tasks.remove(re_aranged_task_index);
-                                self.tasks.insert(re_aranged_new_index, task_clone.clone());
+                        if let Some((task_index, selected)) = clicked_row {
+                            self.bulk_edit_form
+                                .select_row(ui, &mut self.tasks, task_index, selected);
+                        }
+                        let pointer = ui.input(|input| input.pointer.hover_pos());
+                        if let Some((task, (to, y))) = dragged.or(released).zip(
+                            pointer.and_then(|pointer| reorder::drop_position(&rows, pointer)),
+                        ) {
+                            reorder::paint_insertion_line(ui, ui.min_rect().x_range(), y);
+                            if released.is_some() {
+                                action = ProjectDisplayAction::ReorderTask { task, to };
                             }
                         }
                     });
+                ui.separator();
+                if let Some(bulk_action) = self.bulk_edit_form.show(
+                    ui,
+                    &mut self.tasks,
+                    self.uuid,
+                    context.move_targets,
+                ) {
+                    action = ProjectDisplayAction::BulkEdit(bulk_action);
+                }
+            });
+
+            ui.collapsing("Work calendar", |ui| {
+                let mut custom_calendar = self.calendar_override.is_some();
+                if ui
+                    .checkbox(
+                        &mut custom_calendar,
+                        "Use a custom calendar for this project",
+                    )
+                    .changed()
+                {
+                    self.calendar_override = custom_calendar.then(|| context.calendar.clone());
+                }
+                if let Some(calendar) = self.calendar_override.as_mut() {
+                    calendar.show_editor(ui, &mut self.holiday_form, context.time_zones);
+                }
+                ui.label(format!(
+                    "{} working days between start and end date",
+                    self.working_days_duration(context.calendar)
+                ));
+            });
+
+            ui.collapsing("iCalendar", |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    ui.add(egui::TextEdit::singleline(&mut self.ical_path).hint_text("tasks.ics"));
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Export tasks").clicked() {
+                        self.ical_status = Some(
+                            fs::write(&self.ical_path, ical::export_project(self))
+                                .map(|_| format!("Exported {} tasks", self.tasks.len()))
+                                .map_err(|err| format!("Export failed: {}", err)),
+                        );
+                    }
+                    if ui.button("Import tasks").clicked() {
+                        self.ical_status = Some(
+                            fs::read_to_string(&self.ical_path)
+                                .map(|ics| {
+                                    let imported = ical::import_tasks(&ics);
+                                    let count = imported.len();
+                                    self.tasks.extend(imported);
+                                    format!("Imported {} tasks", count)
+                                })
+                                .map_err(|err| format!("Import failed: {}", err)),
+                        );
+                    }
+                });
+                match &self.ical_status {
+                    None => {}
+                    Some(Ok(message)) => {
+                        ui.label(message);
+                    }
+                    Some(Err(message)) => {
+                        ui.colored_label(Color32::LIGHT_RED, message);
+                    }
+                }
+            });
+
+            ui.collapsing("Save as template", |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.template_name)
+                            .hint_text("Template name"),
+                    );
+                    if ui
+                        .add_enabled(
+                            !self.template_name.trim().is_empty(),
+                            egui::Button::new("Save template"),
+                        )
+                        .on_hover_text(
+                            "Task due dates are saved relative to the start date, use {{name}} in any text for values filled in on creation",
+                        )
+                        .clicked()
+                    {
+                        action =
+                            ProjectDisplayAction::SaveAsTemplate(self.template_name.trim().to_string());
+                        self.template_name.clear();
+                    }
+                });
             });
 
             if ui.button("Remove finished tasks").clicked() {
                 self.tasks.retain(|task| !task.get_completed());
             }
 
+            ui.horizontal(|ui| {
+                if ui.button("Duplicate").clicked() {
+                    action = ProjectDisplayAction::Restructure(RestructureMode::Duplicate);
+                }
+                if ui.button("Split").clicked() {
+                    action = ProjectDisplayAction::Restructure(RestructureMode::Split);
+                }
+                if ui.button("Merge").clicked() {
+                    action = ProjectDisplayAction::Restructure(RestructureMode::Merge);
+                }
+            });
+
             if ui
                 .button("Delete project")
                 .on_hover_text("Double click this button to delete the project")
@@ -209,10 +939,185 @@ impl Project {
         });
 
         if self.task_editing {
+            let today = self.today(context.time_zones);
             if let Some(task_to_edit) = self.tasks.get_mut(self.selected_editing_task) {
                 egui::Window::new("Task Editor").show(ctx, |ui| {
-                    ui.text_edit_singleline(&mut task_to_edit.text);
-                    ui.text_edit_singleline(&mut task_to_edit.description);
+                    let text_before = task_to_edit.text.clone();
+                    let text_input = ui.text_edit_singleline(&mut task_to_edit.text);
+                    if text_input.gained_focus() {
+                        self.task_rename_from = Some(text_before);
+                    }
+                    if text_input.lost_focus() {
+                        if let Some(from) = self.task_rename_from.take() {
+                            task_to_edit.log_rename(from);
+                        }
+                    }
+                    show_markdown_editor(
+                        ui,
+                        &mut task_to_edit.description,
+                        &mut self.task_description_preview,
+                        "Task description",
+                    );
+                    show_tag_editor(
+                        ui,
+                        &mut task_to_edit.tags,
+                        &mut self.next_task_tag,
+                        tag_registry,
+                    );
+                    show_assignee_editor(ui, &mut task_to_edit.assignees, context.people);
+
+                    ui.horizontal(|ui| {
+                        let mut has_due_date = task_to_edit.due_date.is_some();
+                        if ui.checkbox(&mut has_due_date, "Due date:").changed() {
+                            task_to_edit.set_due_date(has_due_date.then_some(today));
+                        }
+                        if let Some(mut due_date) = task_to_edit.due_date {
+                            ui.push_id("task_due_date", |ui| {
+                                ui.add(egui_extras::DatePickerButton::new(&mut due_date));
+                            });
+                            task_to_edit.set_due_date(Some(due_date));
+                        }
+                    });
+
+                    if task_to_edit.due_date.is_some() {
+                        ui.push_id("task_reminders", |ui| {
+                            show_reminder_editor(ui, &mut task_to_edit.reminders);
+                        });
+                    }
+
+                    let mut repeats = task_to_edit.recurrence.is_some();
+                    if ui.checkbox(&mut repeats, "Repeats").changed() {
+                        task_to_edit.recurrence = repeats.then(Recurrence::default);
+                    }
+                    if let Some(recurrence) = task_to_edit.recurrence.as_mut() {
+                        recurrence.show_editor(ui);
+                    }
+                    if !task_to_edit.occurrence_history.is_empty() {
+                        ui.collapsing("Past occurrences", |ui| {
+                            task_to_edit
+                                .occurrence_history
+                                .iter()
+                                .rev()
+                                .for_each(|occurrence| {
+                                    ui.label(format!(
+                                        "Due {}, completed {}",
+                                        occurrence
+                                            .due_date
+                                            .map(|date| context.time_zones.format_date(date))
+                                            .unwrap_or("never".to_string()),
+                                        context
+                                            .time_zones
+                                            .format_date_time(&occurrence.completed_date)
+                                    ));
+                                });
+                        });
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.label("Priority:");
+                        task_to_edit.priority.show_picker(ui, "task_priority");
+                    });
+
+                    if !self.milestones.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.label("Milestone:");
+                            let selected_text = task_to_edit
+                                .milestone
+                                .and_then(|uuid| {
+                                    self.milestones
+                                        .iter()
+                                        .find(|milestone| milestone.get_uuid() == uuid)
+                                })
+                                .map(|milestone| milestone.name.clone())
+                                .unwrap_or("None".to_string());
+                            egui::ComboBox::from_id_source("task_milestone")
+                                .selected_text(selected_text)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut task_to_edit.milestone, None, "None");
+                                    self.milestones.iter().for_each(|milestone| {
+                                        ui.selectable_value(
+                                            &mut task_to_edit.milestone,
+                                            Some(milestone.get_uuid()),
+                                            format!(
+                                                "{} ({})",
+                                                milestone.name,
+                                                context.time_zones.format_date(milestone.date)
+                                            ),
+                                        );
+                                    });
+                                });
+                        });
+                    }
+
+                    ui.horizontal(|ui| {
+                        let mut has_estimate = task_to_edit.estimate.is_some();
+                        if ui.checkbox(&mut has_estimate, "Estimate:").changed() {
+                            task_to_edit.estimate = has_estimate.then_some(1.0);
+                        }
+                        if let Some(estimate) = task_to_edit.estimate.as_mut() {
+                            ui.add(
+                                egui::DragValue::new(estimate)
+                                    .speed(0.25)
+                                    .clamp_range(0.0..=f32::MAX),
+                            );
+                            ui.label(self.estimate_unit.get_text());
+                        }
+                    });
+
+                    ui.collapsing("Time entries", |ui| {
+                        let mut removed_entry = None;
+                        task_to_edit.time_entries.iter().enumerate().for_each(
+                            |(entry_index, entry)| {
+                                ui.horizontal(|ui| {
+                                    ui.label(format!(
+                                        "{} - {}",
+                                        context.time_zones.format_date_time(&entry.start),
+                                        context.time_zones.format_time(&entry.end)
+                                    ));
+                                    ui.label(format_duration(entry.duration()));
+                                    if !entry.note.is_empty() {
+                                        ui.label(&entry.note);
+                                    }
+                                    if ui.button("Delete").clicked() {
+                                        removed_entry = Some(entry_index);
+                                    }
+                                });
+                            },
+                        );
+                        if let Some(entry_index) = removed_entry {
+                            task_to_edit.time_entries.remove(entry_index);
+                        }
+                        if let Some(entry) = self.time_entry_form.show(ui, context.time_zones) {
+                            task_to_edit.time_entries.push(entry);
+                        }
+                    });
+
+                    ui.collapsing(
+                        format!("Attachments ({})", task_to_edit.attachments.len()),
+                        |ui| {
+                            self.task_attachment_form.show(
+                                ui,
+                                &mut task_to_edit.attachments,
+                                context.attachments_dir,
+                            );
+                        },
+                    );
+
+                    ui.collapsing(
+                        format!("Comments ({})", task_to_edit.comments.len()),
+                        |ui| {
+                            show_comments(
+                                ui,
+                                &mut task_to_edit.comments,
+                                &mut self.next_comment,
+                                context.time_zones,
+                            );
+                        },
+                    );
+
+                    ui.collapsing("Activity", |ui| {
+                        show_activity(ui, &task_to_edit.activity, context.time_zones);
+                    });
 
                     if ui.button("Close task editor").clicked() {
                         self.task_editing = false;
@@ -226,6 +1131,115 @@ impl Project {
         action
     }
 
+    /// The given name if no project uses it yet, otherwise the name with the lowest free number
+    /// appended, e.g. "Release (2)"
+    pub fn unique_name(project_name: &str, projects: &[Project]) -> String {
+        let taken = |name: &str| projects.iter().any(|project| project.project_name == name);
+        if !taken(project_name) {
+            return project_name.to_string();
+        }
+        (2..)
+            .map(|number| format!("{} ({})", project_name, number))
+            .find(|name| !taken(name))
+            .unwrap_or_default()
+    }
+
+    /// A new project with the same dates and settings as this one but none of its tasks
+    fn derive_new(
+        &self,
+        project_name: String,
+        projects: &[Project],
+    ) -> Result<Self, ProjectValidityError> {
+        let mut project = Self::validity_check_new(
+            self.start_date,
+            self.end_date,
+            project_name,
+            projects,
+            self.description.clone(),
+        )?;
+        project.tags = self.tags.clone();
+        project.estimate_unit = self.estimate_unit;
+        project.reminders = self
+            .reminders
+            .iter()
+            .map(|reminder| Reminder::new(reminder.offset))
+            .collect();
+        project.calendar_override = self.calendar_override.clone();
+        project.time_zone = self.time_zone;
+        project.attachments = self.attachments.clone();
+        project.milestones = self.milestones.clone();
+        project.folder = self.folder;
+        Ok(project)
+    }
+
+    /// A copy of this project under a new name and uuid, every task is copied as incomplete
+    pub fn duplicate(
+        &self,
+        project_name: String,
+        projects: &[Project],
+    ) -> Result<Self, ProjectValidityError> {
+        let mut project = self.derive_new(project_name, projects)?;
+        project.tasks = self.tasks.iter().map(Task::duplicate).collect();
+        Ok(project)
+    }
+
+    /// A new project holding the tasks with the given uuids, the tasks keep their state and log
+    /// the move. The caller is responsible for removing the tasks from this project.
+    pub fn split(
+        &self,
+        task_uuids: &HashSet<Uuid>,
+        project_name: String,
+        projects: &[Project],
+    ) -> Result<Self, ProjectValidityError> {
+        let mut project = self.derive_new(project_name, projects)?;
+        project.tasks = self
+            .tasks
+            .iter()
+            .filter(|task| task_uuids.contains(&task.get_uuid()))
+            .cloned()
+            .map(|mut task| {
+                task.log_activity(ActivityKind::MovedToProject {
+                    project_name: project.project_name.clone(),
+                });
+                task
+            })
+            .collect();
+        Ok(project)
+    }
+
+    /// Move the tasks and tags of another project into this one, widening the project dates to
+    /// cover both. Moved tasks log the move. Tasks equal to one already in this project are
+    /// dropped, returns how many were.
+    pub fn merge(&mut self, other: Project) -> usize {
+        let mut duplicates = 0;
+        other.tasks.into_iter().for_each(|mut task| {
+            if self.tasks.contains(&task) {
+                duplicates += 1;
+            } else {
+                task.log_activity(ActivityKind::MovedToProject {
+                    project_name: self.project_name.clone(),
+                });
+                self.tasks.push(task);
+            }
+        });
+        other.tags.iter().for_each(|tag| {
+            add_tag(&mut self.tags, tag);
+        });
+        other.milestones.into_iter().for_each(|milestone| {
+            if !self.milestones.contains(&milestone) {
+                self.milestones.push(milestone);
+            }
+        });
+        other.attachments.into_iter().for_each(|attachment| {
+            if !self.attachments.contains(&attachment) {
+                self.attachments.push(attachment);
+            }
+        });
+        self.start_date = self.start_date.min(other.start_date);
+        self.end_date = self.end_date.max(other.end_date);
+        duplicates
+    }
+
     pub fn validity_check_new(
         start_date: NaiveDate,
         end_date: NaiveDate,
@@ -279,11 +1293,37 @@ impl Default for Project {
             tasks: vec![],
             next_task_text: "".to_string(),
             next_task_description: "".to_string(),
-            creation_date: Local::now(),
+            creation_date: Utc::now(),
             task_editing: false,
             selected_editing_task: 0,
             description: "".to_string(),
+            description_preview: false,
+            task_description_preview: false,
+            task_rename_from: None,
+            next_comment: "".to_string(),
+            attachments: vec![],
+            attachment_form: AttachmentForm::default(),
+            task_attachment_form: AttachmentForm::default(),
+            milestones: vec![],
+            next_milestone_name: "".to_string(),
+            next_milestone_date: Default::default(),
+            folder: None,
+            pinned: false,
             uuid: Uuid::new_v4(),
+            reveal_requested: false,
+            tags: vec![],
+            next_project_tag: "".to_string(),
+            next_task_tag: "".to_string(),
+            time_entry_form: TimeEntryForm::default(),
+            estimate_unit: EstimateUnit::default(),
+            ical_path: "".to_string(),
+            ical_status: None,
+            reminders: vec![],
+            calendar_override: None,
+            holiday_form: HolidayForm::default(),
+            time_zone: None,
+            template_name: "".to_string(),
+            bulk_edit_form: BulkEditForm::default(),
         }
     }
 }
@@ -0,0 +1,111 @@
+use crate::project::task::Task;
+use egui::{Pos2, Rangef, Rect, Stroke, Ui};
+
+/// Move the task at `from` so it ends up in front of the task that was at `to` before the move,
+/// `to` being the task count moves it to the end
+pub fn move_task(tasks: &mut Vec<Task>, from: usize, to: usize) {
+    if from >= tasks.len() || to > tasks.len() || to == from || to == from + 1 {
+        return;
+    }
+    let task = tasks.remove(from);
+    let to = if to > from { to - 1 } else { to };
+    tasks.insert(to, task);
+}
+
+/// Move every selected task one place up or down, past the next task that is not selected.
+/// Returns false if nothing could move.
+pub fn shift_selected(tasks: &mut [Task], up: bool) -> bool {
+    let mut moved = false;
+    if up {
+        (1..tasks.len()).for_each(|index| {
+            if tasks[index].selected && !tasks[index - 1].selected {
+                tasks.swap(index, index - 1);
+                moved = true;
+            }
+        });
+    } else {
+        (0..tasks.len().saturating_sub(1)).rev().for_each(|index| {
+            if tasks[index].selected && !tasks[index + 1].selected {
+                tasks.swap(index, index + 1);
+                moved = true;
+            }
+        });
+    }
+    moved
+}
+
+/// Where a dragged task would be dropped among the shown task rows, as the index to move it to
+/// and the height to draw the insertion line at. `rows` holds the index of every shown task with
+/// the rect of its row, in display order.
+pub fn drop_position(rows: &[(usize, Rect)], pointer: Pos2) -> Option<(usize, f32)> {
+    let first = rows.first()?.1;
+    let last = rows.last()?.1;
+    if pointer.y < first.top() - first.height() / 2.0
+        || pointer.y > last.bottom() + last.height() / 2.0
+        || pointer.x < first.left()
+        || pointer.x > first.right()
+    {
+        return None;
+    }
+    let position = rows
+        .iter()
+        .find(|(_, rect)| pointer.y < rect.center().y)
+        .map(|(index, rect)| (*index, rect.top()))
+        .unwrap_or((rows.last()?.0 + 1, last.bottom()));
+    Some(position)
+}
+
+/// Line between two task rows showing where a dragged task will be dropped
+pub fn paint_insertion_line(ui: &Ui, x_range: Rangef, y: f32) {
+    ui.painter()
+        .hline(x_range, y, Stroke::new(2.0, ui.visuals().selection.bg_fill));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tasks(names: &[&str]) -> Vec<Task> {
+        names
+            .iter()
+            .map(|name| Task::new(name.to_string(), String::new()))
+            .collect()
+    }
+
+    fn names(tasks: &[Task]) -> Vec<&str> {
+        tasks.iter().map(|task| task.text.as_str()).collect()
+    }
+
+    #[test]
+    fn moves_task_in_front_of_target() {
+        let mut list = tasks(&["a", "b", "c", "d"]);
+        move_task(&mut list, 0, 2);
+        assert_eq!(names(&list), ["b", "a", "c", "d"]);
+        move_task(&mut list, 3, 0);
+        assert_eq!(names(&list), ["d", "b", "a", "c"]);
+        move_task(&mut list, 1, 4);
+        assert_eq!(names(&list), ["d", "a", "c", "b"]);
+    }
+
+    #[test]
+    fn ignores_moves_that_change_nothing() {
+        let mut list = tasks(&["a", "b", "c"]);
+        move_task(&mut list, 1, 1);
+        move_task(&mut list, 1, 2);
+        move_task(&mut list, 3, 0);
+        move_task(&mut list, 0, 4);
+        assert_eq!(names(&list), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn shifts_selected_tasks_past_unselected() {
+        let mut list = tasks(&["a", "b", "c", "d"]);
+        list[1].selected = true;
+        list[2].selected = true;
+        assert!(shift_selected(&mut list, true));
+        assert_eq!(names(&list), ["b", "c", "a", "d"]);
+        assert!(!shift_selected(&mut list, true));
+        assert!(shift_selected(&mut list, false));
+        assert_eq!(names(&list), ["a", "b", "c", "d"]);
+    }
+}
@@ -0,0 +1,285 @@
+use crate::history::UndoHistory;
+use crate::project::{Project, ProjectValidityError};
+use egui::{Color32, ScrollArea, Ui};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum RestructureMode {
+    /// Copy the project with every task reset to incomplete
+    Duplicate,
+    /// Move some of the tasks into a new project
+    Split,
+    /// Move every task of another project into this one
+    Merge,
+}
+
+impl RestructureMode {
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            RestructureMode::Duplicate => "Duplicate project",
+            RestructureMode::Split => "Split project",
+            RestructureMode::Merge => "Merge projects",
+        }
+    }
+}
+
+/// State for the window that duplicates, splits and merges projects
+pub struct RestructureWindow {
+    pub open: bool,
+    mode: RestructureMode,
+    /// The project being duplicated, split or merged into
+    project: Uuid,
+    new_name: String,
+    selected_tasks: HashSet<Uuid>,
+    merge_with: Option<Uuid>,
+    status: Option<String>,
+}
+
+impl Default for RestructureWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            mode: RestructureMode::Duplicate,
+            project: Uuid::nil(),
+            new_name: "".to_string(),
+            selected_tasks: HashSet::new(),
+            merge_with: None,
+            status: None,
+        }
+    }
+}
+
+impl RestructureWindow {
+    pub fn open(&mut self, mode: RestructureMode, project: &Project, projects: &[Project]) {
+        self.open = true;
+        self.mode = mode;
+        self.project = project.get_uuid();
+        self.selected_tasks.clear();
+        self.merge_with = None;
+        self.status = None;
+        self.new_name = match mode {
+            RestructureMode::Duplicate => {
+                Project::unique_name(&format!("{} (copy)", project.project_name), projects)
+            }
+            RestructureMode::Split => {
+                Project::unique_name(&format!("{} (split)", project.project_name), projects)
+            }
+            RestructureMode::Merge => "".to_string(),
+        };
+    }
+
+    /// Show the window, duplicating, splitting and merging are recorded in the undo history
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        projects: &mut Vec<Project>,
+        history: &mut UndoHistory,
+    ) {
+        if !self.open {
+            return;
+        }
+        let Some(project_index) = projects
+            .iter()
+            .position(|project| project.get_uuid() == self.project)
+        else {
+            self.open = false;
+            return;
+        };
+
+        let mut open = self.open;
+        egui::Window::new(self.mode.get_text())
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!("Project: {}", projects[project_index].project_name));
+                ui.separator();
+                match self.mode {
+                    RestructureMode::Duplicate => {
+                        if self.show_name_input(ui, projects, project_index)
+                            && ui.button("Duplicate").clicked()
+                        {
+                            if let Ok(duplicate) =
+                                projects[project_index].duplicate(self.new_name.clone(), projects)
+                            {
+                                history.record(
+                                    "Duplicate project",
+                                    projects,
+                                    &[self.project],
+                                    |projects| projects.push(duplicate),
+                                );
+                                self.open = false;
+                            }
+                        }
+                    }
+                    RestructureMode::Split => {
+                        ui.label("Tasks to move into the new project:");
+                        ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                            projects[project_index].tasks.iter().for_each(|task| {
+                                let uuid = task.get_uuid();
+                                let mut selected = self.selected_tasks.contains(&uuid);
+                                if ui.checkbox(&mut selected, &task.text).changed() {
+                                    if selected {
+                                        self.selected_tasks.insert(uuid);
+                                    } else {
+                                        self.selected_tasks.remove(&uuid);
+                                    }
+                                }
+                            });
+                        });
+                        ui.separator();
+                        if self.show_name_input(ui, projects, project_index)
+                            && ui
+                                .add_enabled(
+                                    !self.selected_tasks.is_empty(),
+                                    egui::Button::new(format!(
+                                        "Split {} tasks",
+                                        self.selected_tasks.len()
+                                    )),
+                                )
+                                .clicked()
+                        {
+                            if let Ok(split) = projects[project_index].split(
+                                &self.selected_tasks,
+                                self.new_name.clone(),
+                                projects,
+                            ) {
+                                history.record(
+                                    "Split project",
+                                    projects,
+                                    &[self.project],
+                                    |projects| {
+                                        projects[project_index].tasks.retain(|task| {
+                                            !self.selected_tasks.contains(&task.get_uuid())
+                                        });
+                                        projects.push(split);
+                                    },
+                                );
+                                self.open = false;
+                            }
+                        }
+                    }
+                    RestructureMode::Merge => {
+                        self.show_merge(ui, projects, project_index, history);
+                    }
+                }
+                if let Some(status) = &self.status {
+                    ui.label(status);
+                }
+            });
+        self.open &= open;
+    }
+
+    /// Show the name input for the new project, returns true when the name is usable. A name
+    /// that is taken comes with a button to rename it to the next free name.
+    fn show_name_input(&mut self, ui: &mut Ui, projects: &[Project], project_index: usize) -> bool {
+        ui.horizontal(|ui| {
+            ui.label("New project name:");
+            ui.text_edit_singleline(&mut self.new_name);
+        });
+        let source = &projects[project_index];
+        match Project::validity_check_new(
+            source.start_date,
+            source.end_date,
+            self.new_name.clone(),
+            projects,
+            "".to_string(),
+        ) {
+            Ok(_) => true,
+            Err(validity_error) => {
+                ui.horizontal(|ui| {
+                    ui.colored_label(Color32::LIGHT_RED, validity_error.get_text());
+                    if let ProjectValidityError::ProjectAlreadyExists = validity_error {
+                        let unique_name = Project::unique_name(&self.new_name, projects);
+                        if ui
+                            .button(format!("Rename to \"{}\"", unique_name))
+                            .clicked()
+                        {
+                            self.new_name = unique_name;
+                        }
+                    }
+                });
+                false
+            }
+        }
+    }
+
+    fn show_merge(
+        &mut self,
+        ui: &mut Ui,
+        projects: &mut Vec<Project>,
+        project_index: usize,
+        history: &mut UndoHistory,
+    ) {
+        let selected_text = self
+            .merge_with
+            .and_then(|uuid| projects.iter().find(|project| project.get_uuid() == uuid))
+            .map(|project| project.project_name.clone())
+            .unwrap_or("Select a project".to_string());
+        ui.horizontal(|ui| {
+            ui.label("Merge in:");
+            egui::ComboBox::from_id_source("merge_with")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    projects
+                        .iter()
+                        .filter(|project| project.get_uuid() != self.project)
+                        .for_each(|project| {
+                            ui.selectable_value(
+                                &mut self.merge_with,
+                                Some(project.get_uuid()),
+                                &project.project_name,
+                            );
+                        });
+                });
+        });
+
+        let Some(other_index) = self.merge_with.and_then(|uuid| {
+            projects
+                .iter()
+                .position(|project| project.get_uuid() == uuid)
+        }) else {
+            return;
+        };
+        let duplicates = projects[other_index]
+            .tasks
+            .iter()
+            .filter(|task| projects[project_index].tasks.contains(task))
+            .count();
+        ui.label(format!(
+            "{} tasks will be moved, {} duplicate tasks will be dropped. \"{}\" is removed afterwards.",
+            projects[other_index].tasks.len() - duplicates,
+            duplicates,
+            projects[other_index].project_name
+        ));
+        if ui
+            .button(format!(
+                "Merge into \"{}\"",
+                projects[project_index].project_name
+            ))
+            .on_hover_text("Double click to merge the projects")
+            .double_clicked()
+        {
+            let other_uuid = projects[other_index].get_uuid();
+            self.status = history.record(
+                "Merge projects",
+                projects,
+                &[self.project, other_uuid],
+                |projects| {
+                    let other = projects.remove(other_index);
+                    let other_name = other.project_name.clone();
+                    projects
+                        .iter_mut()
+                        .find(|project| project.get_uuid() == self.project)
+                        .map(|project| {
+                            let dropped = project.merge(other);
+                            format!(
+                                "Merged \"{}\", {} duplicate tasks dropped",
+                                other_name, dropped
+                            )
+                        })
+                },
+            );
+            self.merge_with = None;
+        }
+    }
+}
@@ -1,7 +1,17 @@
-use chrono::{DateTime, Datelike, Local, Timelike};
-use egui::{RichText, Ui};
+use super::activity::{ActivityEvent, ActivityKind, Comment};
+use super::ProjectContext;
+use crate::attachment::{show_attachment_list, Attachment};
+use crate::markdown::show_markdown;
+use crate::people::show_avatar_chips;
+use crate::recurrence::{Occurrence, Recurrence};
+use crate::reminder::Reminder;
+use crate::tag::show_tag_chips;
+use crate::time_tracking::{format_duration, TimeEntry};
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use egui::{Color32, RichText, Sense, Ui};
 
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(default)]
@@ -11,9 +21,102 @@ pub struct Task {
     pub description: String,
 
     completed: bool,
-    completed_date: Option<DateTime<Local>>,
+    completed_date: Option<DateTime<Utc>>,
     #[serde(skip)]
     pub marked_for_deletion: bool,
+
+    uuid: Uuid,
+
+    pub tags: Vec<String>,
+
+    pub time_entries: Vec<TimeEntry>,
+
+    timer_started: Option<DateTime<Utc>>,
+
+    /// Estimated effort, in the estimate unit of the project this task belongs to
+    pub estimate: Option<f32>,
+
+    pub due_date: Option<NaiveDate>,
+
+    pub recurrence: Option<Recurrence>,
+
+    /// Past completed occurrences of a recurring task, oldest first
+    pub occurrence_history: Vec<Occurrence>,
+
+    /// Reminders relative to the due date, tasks without a due date never remind
+    pub reminders: Vec<Reminder>,
+
+    #[serde(skip)]
+    pub selected: bool,
+
+    pub priority: Priority,
+
+    pub comments: Vec<Comment>,
+
+    /// Automatically recorded history of the task, oldest first
+    pub activity: Vec<ActivityEvent>,
+
+    pub attachments: Vec<Attachment>,
+
+    /// Uuids of the people in the people directory this task is assigned to
+    pub assignees: Vec<Uuid>,
+
+    /// Uuid of the milestone of its project this task counts towards
+    pub milestone: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default, Debug)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 4] = [
+        Priority::Low,
+        Priority::Normal,
+        Priority::High,
+        Priority::Urgent,
+    ];
+
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            Priority::Low => "Low",
+            Priority::Normal => "Normal",
+            Priority::High => "High",
+            Priority::Urgent => "Urgent",
+        }
+    }
+
+    pub fn get_color(&self) -> Color32 {
+        match self {
+            Priority::Low => Color32::GRAY,
+            Priority::Normal => Color32::LIGHT_GRAY,
+            Priority::High => Color32::from_rgb(255, 170, 60),
+            Priority::Urgent => Color32::LIGHT_RED,
+        }
+    }
+
+    /// Drop down for picking a priority
+    pub fn show_picker(&mut self, ui: &mut Ui, id: &str) {
+        egui::ComboBox::from_id_source(id)
+            .selected_text(self.get_text())
+            .show_ui(ui, |ui| {
+                Priority::ALL.iter().for_each(|priority| {
+                    ui.selectable_value(self, *priority, priority.get_text());
+                });
+            });
+    }
+}
+
+pub enum TaskDisplayAction {
+    TimerStarted,
+    /// The user started dragging this task by its handle
+    DragStarted,
+    None,
 }
 
 impl Task {
@@ -24,24 +127,206 @@ impl Task {
             completed: false,
             completed_date: None,
             marked_for_deletion: false,
+            uuid: Uuid::new_v4(),
+            tags: vec![],
+            time_entries: vec![],
+            timer_started: None,
+            estimate: None,
+            due_date: None,
+            recurrence: None,
+            occurrence_history: vec![],
+            reminders: vec![],
+            selected: false,
+            priority: Priority::default(),
+            comments: vec![],
+            activity: vec![ActivityEvent::new(ActivityKind::Created)],
+            attachments: vec![],
+            assignees: vec![],
+            milestone: None,
+        }
+    }
+
+    pub fn get_uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    /// A copy of this task with a new uuid, keeping its completion state and dates. The copy
+    /// starts without comments and with a fresh activity log.
+    pub fn copy(&self) -> Self {
+        let mut task = self.clone();
+        task.uuid = Uuid::new_v4();
+        task.timer_started = None;
+        task.selected = false;
+        task.comments = vec![];
+        task.activity = vec![ActivityEvent::new(ActivityKind::Created)];
+        task
+    }
+
+    /// True when the task is due before `start` or after `end`
+    pub fn is_due_outside(&self, start: NaiveDate, end: NaiveDate) -> bool {
+        self.due_date
+            .is_some_and(|due_date| due_date < start || due_date > end)
+    }
+
+    /// A copy of this task with a new uuid, reset to incomplete without any tracked time or
+    /// completion history
+    pub fn duplicate(&self) -> Self {
+        let mut task = Task::new(self.text.clone(), self.description.clone());
+        task.tags = self.tags.clone();
+        task.estimate = self.estimate;
+        task.priority = self.priority;
+        task.due_date = self.due_date;
+        task.recurrence = self.recurrence.clone();
+        task.attachments = self.attachments.clone();
+        task.assignees = self.assignees.clone();
+        task.milestone = self.milestone;
+        task.reminders = self
+            .reminders
+            .iter()
+            .map(|reminder| Reminder::new(reminder.offset))
+            .collect();
+        task
+    }
+
+    pub fn log_activity(&mut self, kind: ActivityKind) {
+        self.activity.push(ActivityEvent::new(kind));
+    }
+
+    /// Change the due date, recording the change in the activity log
+    pub fn set_due_date(&mut self, due_date: Option<NaiveDate>) {
+        if self.due_date != due_date {
+            self.log_activity(ActivityKind::DueDateChanged {
+                from: self.due_date,
+                to: due_date,
+            });
+            self.due_date = due_date;
+        }
+    }
+
+    /// Record in the activity log that the task was renamed from `from` to its current text
+    pub fn log_rename(&mut self, from: String) {
+        if self.text != from {
+            self.log_activity(ActivityKind::Renamed { from });
         }
     }
 
     pub fn get_completed(&self) -> bool {
         self.completed
     }
+    /// Mark the task as completed or incomplete.
+    ///
+    /// Completing a recurring task records the occurrence in its history and moves the task on to
+    /// its next occurrence instead, leaving it incomplete. Once the recurrence rule has ended the
+    /// task completes normally. Either way the activity log keeps every completion and reopening.
+    /// Nothing happens if the task already is in that state.
     pub fn set_completed(&mut self, completed: bool) {
+        if completed == self.completed {
+            return;
+        }
         self.completed = completed;
         if completed {
-            self.completed_date = Some(Local::now());
+            self.log_activity(ActivityKind::Completed);
+            self.stop_timer();
+            if let Some(recurrence) = &self.recurrence {
+                let previous = self.due_date.unwrap_or_else(|| Local::now().date_naive());
+                let start = self
+                    .occurrence_history
+                    .first()
+                    .and_then(|occurrence| occurrence.due_date)
+                    .unwrap_or(previous);
+                let occurrences = self.occurrence_history.len() as u32 + 1;
+                if let Some(next_due_date) =
+                    recurrence.next_occurrence(start, previous, occurrences)
+                {
+                    self.occurrence_history.push(Occurrence {
+                        due_date: self.due_date,
+                        completed_date: Utc::now(),
+                    });
+                    self.set_due_date(Some(next_due_date));
+                    self.completed = false;
+                    self.completed_date = None;
+                    return;
+                }
+            }
+            self.completed_date = Some(Utc::now());
         } else {
+            self.log_activity(ActivityKind::Reopened);
             self.completed_date = None;
         }
     }
 
-    pub fn show(&mut self, ui: &mut Ui) {
+    /// Mark the task as completed at a specific time, without advancing any recurrence
+    pub(crate) fn set_completed_on(&mut self, completed_date: DateTime<Utc>) {
+        self.completed = true;
+        self.completed_date = Some(completed_date);
+        self.activity.push(ActivityEvent {
+            time: completed_date,
+            kind: ActivityKind::Completed,
+        });
+    }
+
+    /// Every time this task was completed, including past occurrences of a recurring task
+    pub fn completion_dates(&self) -> impl Iterator<Item = &DateTime<Utc>> {
+        self.occurrence_history
+            .iter()
+            .map(|occurrence| &occurrence.completed_date)
+            .chain(self.completed_date.iter())
+    }
+
+    pub fn is_timer_running(&self) -> bool {
+        self.timer_started.is_some()
+    }
+
+    pub fn start_timer(&mut self) {
+        if self.timer_started.is_none() {
+            self.timer_started = Some(Utc::now());
+        }
+    }
+
+    /// Stop the running timer if there is one, recording the elapsed time as a time entry
+    pub fn stop_timer(&mut self) {
+        if let Some(start) = self.timer_started.take() {
+            self.time_entries.push(TimeEntry {
+                start,
+                end: Utc::now(),
+                note: "".to_string(),
+            });
+        }
+    }
+
+    /// Total time tracked on this task, including the currently running timer
+    pub fn tracked_time(&self) -> Duration {
+        let running = self
+            .timer_started
+            .map(|start| Utc::now().signed_duration_since(start))
+            .unwrap_or_else(Duration::zero);
+        self.time_entries
+            .iter()
+            .fold(running, |total, entry| total + entry.duration())
+    }
+
+    /// Show the task as a row in its project, `project_dates` being the start and end date of the
+    /// project
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        context: &ProjectContext,
+        project_dates: (NaiveDate, NaiveDate),
+    ) -> TaskDisplayAction {
+        let mut action = TaskDisplayAction::None;
         let mut completed = self.get_completed();
         ui.horizontal(|ui| {
+            let handle = ui
+                .selectable_label(self.selected, "☰")
+                .interact(Sense::click_and_drag())
+                .on_hover_text("Click to select, drag to reorder or onto another project to move");
+            if handle.clicked() {
+                self.selected = !self.selected;
+            }
+            if handle.drag_started() {
+                action = TaskDisplayAction::DragStarted;
+            }
+
             if self.completed {
                 ui.checkbox(&mut completed, RichText::new(&self.text).strikethrough());
             } else {
@@ -50,33 +335,86 @@ impl Task {
 
             if let Some(complete_date) = self.get_complete_date() {
                 ui.label(format!(
-                    "Completed on: {}-{}-{} {}:{} {}",
-                    complete_date.year(),
-                    complete_date.month(),
-                    complete_date.day(),
-                    complete_date.hour12().1,
-                    complete_date.minute(),
-                    if complete_date.hour12().0 { "PM" } else { "AM" }
+                    "Completed on: {}",
+                    context.time_zones.format_date_time(complete_date)
+                ));
+            }
+
+            if self.priority != Priority::Normal && !self.completed {
+                ui.colored_label(
+                    self.priority.get_color(),
+                    format!("!{}", self.priority.get_text()),
+                );
+            }
+
+            if let Some(due_date) = self.due_date {
+                if !self.completed {
+                    ui.label(format!("Due: {}", context.time_zones.format_date(due_date)));
+                }
+                if self.is_due_outside(project_dates.0, project_dates.1) {
+                    ui.colored_label(Color32::LIGHT_RED, "Outside project dates")
+                        .on_hover_text(format!(
+                            "The project runs from {} to {}",
+                            context.time_zones.format_date(project_dates.0),
+                            context.time_zones.format_date(project_dates.1)
+                        ));
+                }
+            }
+            if let Some(recurrence) = &self.recurrence {
+                ui.label(format!(
+                    "Repeats: {}",
+                    recurrence.describe(context.time_zones)
                 ));
             }
+
+            show_tag_chips(ui, &self.tags, context.tag_registry);
+            show_avatar_chips(ui, &self.assignees, context.people);
+
+            if !self.completed {
+                if self.is_timer_running() {
+                    if ui.button("Stop timer").clicked() {
+                        self.stop_timer();
+                    }
+                    ui.ctx()
+                        .request_repaint_after(std::time::Duration::from_secs(1));
+                } else if ui.button("Start timer").clicked() {
+                    self.start_timer();
+                    action = TaskDisplayAction::TimerStarted;
+                }
+            }
+            let tracked = self.tracked_time();
+            if tracked > Duration::zero() {
+                ui.label(format!("Tracked: {}", format_duration(tracked)));
+            }
+            if !self.attachments.is_empty() {
+                ui.label(format!("📎 {}", self.attachments.len()))
+                    .on_hover_ui(|ui| {
+                        show_attachment_list(ui, &self.attachments, context.attachments_dir);
+                    });
+            }
+            if let Some(last_comment) = self.comments.last() {
+                ui.label(format!("💬 {}", self.comments.len()))
+                    .on_hover_text(&last_comment.text);
+            }
         });
         if !self.description.is_empty() {
             ui.horizontal(|ui| {
                 ui.separator();
-                if self.completed {
-                    ui.label(RichText::new(self.description.to_string()).strikethrough());
-                } else {
-                    ui.label(&self.description);
-                }
+                // completed tasks show their description greyed out, with checklists locked
+                ui.add_enabled_ui(!self.completed, |ui| {
+                    show_markdown(ui, &mut self.description);
+                });
             });
         }
 
         if self.get_completed() != completed {
             self.set_completed(completed);
         }
+
+        action
     }
 
-    pub fn get_complete_date(&self) -> Option<&DateTime<Local>> {
+    pub fn get_complete_date(&self) -> Option<&DateTime<Utc>> {
         self.completed_date.as_ref()
     }
 }
@@ -89,6 +427,22 @@ impl Default for Task {
             completed: false,
             completed_date: None,
             marked_for_deletion: false,
+            uuid: Uuid::new_v4(),
+            tags: vec![],
+            time_entries: vec![],
+            timer_started: None,
+            estimate: None,
+            due_date: None,
+            recurrence: None,
+            occurrence_history: vec![],
+            reminders: vec![],
+            selected: false,
+            priority: Priority::default(),
+            comments: vec![],
+            activity: vec![],
+            attachments: vec![],
+            assignees: vec![],
+            milestone: None,
         }
     }
 }
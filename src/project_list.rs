@@ -0,0 +1,161 @@
+use crate::project::Project;
+use crate::timezone::TimeZoneSettings;
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Default, Debug)]
+pub enum ProjectSort {
+    /// The order projects were added in
+    #[default]
+    Manual,
+    EndDate,
+    StartDate,
+    Name,
+    CreationDate,
+    Progress,
+}
+
+impl ProjectSort {
+    pub const ALL: [ProjectSort; 6] = [
+        ProjectSort::Manual,
+        ProjectSort::EndDate,
+        ProjectSort::StartDate,
+        ProjectSort::Name,
+        ProjectSort::CreationDate,
+        ProjectSort::Progress,
+    ];
+
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            ProjectSort::Manual => "Order added",
+            ProjectSort::EndDate => "End date",
+            ProjectSort::StartDate => "Start date",
+            ProjectSort::Name => "Name",
+            ProjectSort::CreationDate => "Creation date",
+            ProjectSort::Progress => "Progress",
+        }
+    }
+
+    fn compare(&self, a: &Project, b: &Project) -> Ordering {
+        match self {
+            ProjectSort::Manual => Ordering::Equal,
+            ProjectSort::EndDate => a.end_date.cmp(&b.end_date),
+            ProjectSort::StartDate => a.start_date.cmp(&b.start_date),
+            ProjectSort::Name => a
+                .project_name
+                .to_lowercase()
+                .cmp(&b.project_name.to_lowercase()),
+            ProjectSort::CreationDate => a.get_creation_date().cmp(b.get_creation_date()),
+            ProjectSort::Progress => a.progress().total_cmp(&b.progress()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Default, Debug)]
+pub enum StatusFilter {
+    #[default]
+    All,
+    /// Started and not yet past the end date, with work left
+    Active,
+    /// Past the end date with work left
+    Overdue,
+    /// Not started yet
+    Upcoming,
+    /// Every task completed
+    Completed,
+}
+
+impl StatusFilter {
+    pub const ALL: [StatusFilter; 5] = [
+        StatusFilter::All,
+        StatusFilter::Active,
+        StatusFilter::Overdue,
+        StatusFilter::Upcoming,
+        StatusFilter::Completed,
+    ];
+
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            StatusFilter::All => "All",
+            StatusFilter::Active => "Active",
+            StatusFilter::Overdue => "Overdue",
+            StatusFilter::Upcoming => "Upcoming",
+            StatusFilter::Completed => "Completed",
+        }
+    }
+
+    pub fn matches(&self, project: &Project, time_zones: &TimeZoneSettings) -> bool {
+        let today = project.today(time_zones);
+        let completed = project.is_completed();
+        match self {
+            StatusFilter::All => true,
+            StatusFilter::Active => {
+                !completed && project.start_date <= today && today <= project.end_date
+            }
+            StatusFilter::Overdue => !completed && project.end_date < today,
+            StatusFilter::Upcoming => !completed && project.start_date > today,
+            StatusFilter::Completed => completed,
+        }
+    }
+}
+
+/// How the main project list is sorted and filtered
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct ProjectListSettings {
+    pub sort: ProjectSort,
+    pub descending: bool,
+    pub filter: StatusFilter,
+}
+
+impl ProjectListSettings {
+    /// Indices of the projects passing the status filter, pinned projects first and each part in
+    /// sort order
+    pub fn order(&self, projects: &[Project], time_zones: &TimeZoneSettings) -> Vec<usize> {
+        let mut indices: Vec<usize> = projects
+            .iter()
+            .enumerate()
+            .filter(|(_, project)| self.filter.matches(project, time_zones))
+            .map(|(index, _)| index)
+            .collect();
+        indices.sort_by(|a_index, b_index| {
+            let (a, b) = (&projects[*a_index], &projects[*b_index]);
+            // ties keep the manual order, so descending reverses the manual order too
+            let order = self.sort.compare(a, b).then(a_index.cmp(b_index));
+            b.pinned.cmp(&a.pinned).then(if self.descending {
+                order.reverse()
+            } else {
+                order
+            })
+        });
+        indices
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, projects: &[Project], time_zones: &TimeZoneSettings) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Sort by:");
+            egui::ComboBox::from_id_source("project_sort")
+                .selected_text(self.sort.get_text())
+                .show_ui(ui, |ui| {
+                    ProjectSort::ALL.iter().for_each(|sort| {
+                        ui.selectable_value(&mut self.sort, *sort, sort.get_text());
+                    });
+                });
+            ui.selectable_value(&mut self.descending, false, "Ascending");
+            ui.selectable_value(&mut self.descending, true, "Descending");
+            ui.separator();
+            StatusFilter::ALL.iter().for_each(|filter| {
+                let count = projects
+                    .iter()
+                    .filter(|project| filter.matches(project, time_zones))
+                    .count();
+                ui.selectable_value(
+                    &mut self.filter,
+                    *filter,
+                    format!("{} ({})", filter.get_text(), count),
+                );
+            });
+        });
+    }
+}
@@ -0,0 +1,361 @@
+use crate::project::task::{Priority, Task};
+use crate::project::Project;
+use crate::tag::add_tag;
+use crate::timezone::TimeZoneSettings;
+use chrono::{Datelike, Days, Month, Months, NaiveDate, Weekday};
+use egui::{Color32, Key, Ui};
+
+/// A task typed into the quick add box, broken into its parts.
+///
+/// `Write release notes @Website #docs !high due friday` is the task "Write release notes" in the
+/// project Website, tagged docs, with high priority and due on the coming Friday, which is today
+/// when typed on a Friday.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct QuickAdd {
+    pub text: String,
+    /// The project name as typed after the `@`
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    pub priority: Option<Priority>,
+    pub due_date: Option<NaiveDate>,
+}
+
+impl QuickAdd {
+    /// Split the input into its parts, words that are not part of anything else make up the task
+    /// text. Relative due dates count from `today`, with weeks starting on `first_day`.
+    pub fn parse(input: &str, today: NaiveDate, first_day: Weekday) -> Self {
+        let mut quick_add = QuickAdd::default();
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let mut text = vec![];
+        let mut index = 0;
+        while index < words.len() {
+            let word = words[index];
+            index += 1;
+            if let Some(project) = word.strip_prefix('@').filter(|name| !name.is_empty()) {
+                quick_add.project = Some(project.to_string());
+            } else if let Some(tag) = word.strip_prefix('#').filter(|tag| !tag.is_empty()) {
+                add_tag(&mut quick_add.tags, tag);
+            } else if let Some(priority) = word.strip_prefix('!').and_then(parse_priority) {
+                quick_add.priority = Some(priority);
+            } else if word.eq_ignore_ascii_case("due") {
+                match parse_date(&words[index..], today, first_day) {
+                    Some((date, used)) => {
+                        quick_add.due_date = Some(date);
+                        index += used;
+                    }
+                    None => text.push(word),
+                }
+            } else {
+                text.push(word);
+            }
+        }
+        quick_add.text = text.join(" ");
+        quick_add
+    }
+
+    /// Index of the project named after the `@`. Case, spaces and punctuation are ignored, and a
+    /// name can be shortened as long as only one project starts with it.
+    pub fn find_project(&self, projects: &[Project]) -> Result<usize, String> {
+        let Some(name) = &self.project else {
+            return Err("Name the project with @, like @Website".to_string());
+        };
+        let wanted = simplify(name);
+        let names: Vec<String> = projects
+            .iter()
+            .map(|project| simplify(&project.project_name))
+            .collect();
+        if let Some(index) = names.iter().position(|other| *other == wanted) {
+            return Ok(index);
+        }
+        let starting: Vec<usize> = names
+            .iter()
+            .enumerate()
+            .filter(|(_, other)| other.starts_with(&wanted))
+            .map(|(index, _)| index)
+            .collect();
+        match starting.as_slice() {
+            [index] => Ok(*index),
+            [] => Err(format!("No project matches @{}", name)),
+            _ => Err(format!("@{} matches {} projects", name, starting.len())),
+        }
+    }
+
+    pub fn into_task(self) -> Task {
+        let mut task = Task::new(self.text, String::new());
+        task.tags = self.tags;
+        task.priority = self.priority.unwrap_or_default();
+        task.due_date = self.due_date;
+        task
+    }
+}
+
+/// Lowercase letters and digits of a name, for matching project names loosely
+fn simplify(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+fn parse_priority(text: &str) -> Option<Priority> {
+    Priority::ALL
+        .into_iter()
+        .find(|priority| priority.get_text().eq_ignore_ascii_case(text))
+}
+
+/// The first date this weekday falls on, today included
+fn upcoming(weekday: Weekday, today: NaiveDate) -> NaiveDate {
+    let days = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    today + Days::new(days as u64)
+}
+
+/// The first date this month and day fall on, today included
+fn upcoming_day_of_month(month: Month, day: u32, today: NaiveDate) -> Option<NaiveDate> {
+    let this_year = NaiveDate::from_ymd_opt(today.year(), month.number_from_month(), day)?;
+    if this_year >= today {
+        return Some(this_year);
+    }
+    NaiveDate::from_ymd_opt(today.year() + 1, month.number_from_month(), day)
+}
+
+/// A date at the start of `words`, with the number of words it took up. Understands today,
+/// tomorrow, weekdays, a weekday of next week, next week, in N days/weeks/months, dates like
+/// 2024-05-17 and month and day in either order.
+fn parse_date(words: &[&str], today: NaiveDate, first_day: Weekday) -> Option<(NaiveDate, usize)> {
+    let lower: Vec<String> = words
+        .iter()
+        .take(3)
+        .map(|word| word.trim_end_matches([',', '.']).to_lowercase())
+        .collect();
+    let first = lower.first()?.as_str();
+    let second = lower.get(1).map(|word| word.as_str());
+
+    match first {
+        "today" => return Some((today, 1)),
+        "tomorrow" => return Some((today + Days::new(1), 1)),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(first, "%Y-%m-%d") {
+        return Some((date, 1));
+    }
+    if let Ok(weekday) = first.parse::<Weekday>() {
+        return Some((upcoming(weekday, today), 1));
+    }
+    if first == "next" {
+        let next_week = upcoming(first_day, today + Days::new(1));
+        if second == Some("week") {
+            return Some((next_week, 2));
+        }
+        if second == Some("month") {
+            return Some((today.checked_add_months(Months::new(1))?, 2));
+        }
+        let weekday = second?.parse::<Weekday>().ok()?;
+        let offset = (7 + weekday.num_days_from_monday() - first_day.num_days_from_monday()) % 7;
+        let date = next_week + Days::new(offset as u64);
+        return Some((date, 2));
+    }
+    if first == "in" {
+        let count: u32 = second?.parse().ok()?;
+        let date = match lower.get(2)?.trim_end_matches('s') {
+            "day" => today.checked_add_days(Days::new(count as u64))?,
+            "week" => today.checked_add_days(Days::new(count as u64 * 7))?,
+            "month" => today.checked_add_months(Months::new(count))?,
+            _ => return None,
+        };
+        return Some((date, 3));
+    }
+
+    let day_of = |word: &str| {
+        word.trim_end_matches(|c: char| c.is_alphabetic())
+            .parse::<u32>()
+            .ok()
+    };
+    if let (Ok(month), Some(day)) = (first.parse::<Month>(), second.and_then(day_of)) {
+        return Some((upcoming_day_of_month(month, day, today)?, 2));
+    }
+    if let (Some(day), Some(Ok(month))) = (day_of(first), second.map(str::parse::<Month>)) {
+        return Some((upcoming_day_of_month(month, day, today)?, 2));
+    }
+    None
+}
+
+/// Single line input that adds a task to any project
+#[derive(Default)]
+pub struct QuickAddBox {
+    input: String,
+    status: Option<Result<String, String>>,
+}
+
+impl QuickAddBox {
+    /// Show the input, returns the project index and the new task once the user presses enter on
+    /// input naming a project
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        projects: &[Project],
+        time_zones: &TimeZoneSettings,
+    ) -> Option<(usize, Task)> {
+        let mut added = None;
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.input)
+                .hint_text("Quick add: Task @project #tag !high due friday")
+                .desired_width(260.0),
+        );
+        let parsed = QuickAdd::parse(
+            &self.input,
+            time_zones.display_today(),
+            time_zones.display_format.week_start,
+        );
+        let response = response.on_hover_text(if self.input.trim().is_empty() {
+            "Type a task and press enter. @project picks the project, #tag adds tags, \
+             !low/!high/!urgent sets the priority and due today/tomorrow/friday/next week/\
+             in 3 days/may 17/2024-05-17 sets the due date"
+                .to_string()
+        } else {
+            describe(&parsed, time_zones)
+        });
+        if response.changed() {
+            self.status = None;
+        }
+
+        if response.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter)) {
+            let project = parsed.find_project(projects);
+            self.status = Some(match project {
+                Ok(_) if parsed.text.is_empty() => Err("The task needs a name".to_string()),
+                Ok(project_index) => {
+                    let message = format!(
+                        "Added \"{}\" to {}",
+                        parsed.text, projects[project_index].project_name
+                    );
+                    added = Some((project_index, parsed.into_task()));
+                    self.input.clear();
+                    response.request_focus();
+                    Ok(message)
+                }
+                Err(err) => Err(err),
+            });
+        }
+        match &self.status {
+            Some(Ok(message)) => {
+                ui.weak(message);
+            }
+            Some(Err(err)) => {
+                ui.colored_label(Color32::LIGHT_RED, err);
+            }
+            None => {}
+        }
+        added
+    }
+}
+
+/// The parts of a quick add input as text, for previewing what will be added
+fn describe(parsed: &QuickAdd, time_zones: &TimeZoneSettings) -> String {
+    let mut parts = vec![format!("Task: {}", parsed.text)];
+    if let Some(project) = &parsed.project {
+        parts.push(format!("Project: {}", project));
+    }
+    if !parsed.tags.is_empty() {
+        parts.push(format!("Tags: {}", parsed.tags.join(", ")));
+    }
+    if let Some(priority) = parsed.priority {
+        parts.push(format!("Priority: {}", priority.get_text()));
+    }
+    if let Some(due_date) = parsed.due_date {
+        parts.push(format!("Due: {}", time_zones.format_date(due_date)));
+    }
+    parts.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parses_every_part() {
+        // a Wednesday
+        let today = date(2024, 5, 15);
+        let parsed = QuickAdd::parse(
+            "Write release notes @Website #docs !high due friday",
+            today,
+            Weekday::Mon,
+        );
+        assert_eq!(
+            parsed,
+            QuickAdd {
+                text: "Write release notes".to_string(),
+                project: Some("Website".to_string()),
+                tags: vec!["docs".to_string()],
+                priority: Some(Priority::High),
+                due_date: Some(date(2024, 5, 17)),
+            }
+        );
+    }
+
+    #[test]
+    fn weekday_includes_today() {
+        // a Friday
+        let today = date(2024, 5, 17);
+        let parsed = QuickAdd::parse("Ship it due friday", today, Weekday::Mon);
+        assert_eq!(parsed.due_date, Some(today));
+        let parsed = QuickAdd::parse("Ship it due thursday", today, Weekday::Mon);
+        assert_eq!(parsed.due_date, Some(date(2024, 5, 23)));
+    }
+
+    #[test]
+    fn next_weekday_follows_week_start() {
+        // a Saturday
+        let today = date(2024, 5, 18);
+        let parsed = QuickAdd::parse("Plan due next sunday", today, Weekday::Mon);
+        assert_eq!(parsed.due_date, Some(date(2024, 5, 26)));
+        // with weeks starting on Sunday, tomorrow is already next week
+        let parsed = QuickAdd::parse("Plan due next sunday", today, Weekday::Sun);
+        assert_eq!(parsed.due_date, Some(date(2024, 5, 19)));
+        let parsed = QuickAdd::parse("Plan due next wednesday", today, Weekday::Sun);
+        assert_eq!(parsed.due_date, Some(date(2024, 5, 22)));
+        let parsed = QuickAdd::parse("Plan due next week", today, Weekday::Sun);
+        assert_eq!(parsed.due_date, Some(date(2024, 5, 19)));
+    }
+
+    #[test]
+    fn parses_other_dates() {
+        let today = date(2024, 5, 15);
+        let due = |input: &str| QuickAdd::parse(input, today, Weekday::Mon).due_date;
+        assert_eq!(due("a due tomorrow"), Some(date(2024, 5, 16)));
+        assert_eq!(due("a due in 2 weeks"), Some(date(2024, 5, 29)));
+        assert_eq!(due("a due may 3rd"), Some(date(2025, 5, 3)));
+        assert_eq!(due("a due 17 may"), Some(date(2024, 5, 17)));
+        assert_eq!(due("a due 2024-06-01"), Some(date(2024, 6, 1)));
+    }
+
+    #[test]
+    fn due_without_date_is_text() {
+        let parsed = QuickAdd::parse("Pay dues due soon", date(2024, 5, 15), Weekday::Mon);
+        assert_eq!(parsed.text, "Pay dues due soon");
+        assert_eq!(parsed.due_date, None);
+    }
+
+    #[test]
+    fn finds_project_by_prefix() {
+        let projects: Vec<Project> = ["Website", "Web shop", "Mobile app"]
+            .into_iter()
+            .map(|name| {
+                let mut project = Project::default();
+                project.project_name = name.to_string();
+                project
+            })
+            .collect();
+        let find = |input: &str| {
+            QuickAdd::parse(input, date(2024, 5, 15), Weekday::Mon).find_project(&projects)
+        };
+        assert_eq!(find("a @website"), Ok(0));
+        assert_eq!(find("a @mobile"), Ok(2));
+        assert_eq!(find("a @web-shop"), Ok(1));
+        assert!(find("a @webs").is_err());
+        assert!(find("a @desktop").is_err());
+        assert!(find("a").is_err());
+    }
+}
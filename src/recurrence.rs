@@ -0,0 +1,618 @@
+use crate::timezone::TimeZoneSettings;
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate, Utc, Weekday};
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "Daily",
+            Frequency::Weekly => "Weekly",
+            Frequency::Monthly => "Monthly",
+            Frequency::Yearly => "Yearly",
+        }
+    }
+
+    fn rrule_name(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+
+    fn unit_name(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "day",
+            Frequency::Weekly => "week",
+            Frequency::Monthly => "month",
+            Frequency::Yearly => "year",
+        }
+    }
+}
+
+/// A subset of the iCalendar RRULE recurrence rules, covering schedules like "every 2 weeks on
+/// monday and thursday" or "every month on the 2nd tuesday".
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// Repeat every n days, weeks, months or years
+    pub interval: u32,
+    /// Days of the week a weekly rule repeats on, the weekday of the previous occurrence is used if empty
+    pub weekdays: Vec<Weekday>,
+    /// For monthly rules, repeat on the nth weekday of the month, -1 is the last weekday of the month
+    pub monthly_weekday: Option<(i8, Weekday)>,
+    /// No occurrences are generated after this date
+    pub until: Option<NaiveDate>,
+    /// Total number of occurrences, including the first one
+    pub count: Option<u32>,
+}
+
+impl Default for Recurrence {
+    fn default() -> Self {
+        Self {
+            frequency: Frequency::Weekly,
+            interval: 1,
+            weekdays: vec![],
+            monthly_weekday: None,
+            until: None,
+            count: None,
+        }
+    }
+}
+
+/// A completed occurrence of a recurring task
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Occurrence {
+    pub due_date: Option<NaiveDate>,
+    pub completed_date: DateTime<Utc>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RecurrenceError {
+    MissingFrequency,
+    UnsupportedFrequency(String),
+    InvalidInterval(String),
+    InvalidWeekday(String),
+    InvalidDate(String),
+    InvalidCount(String),
+    /// BYDAY values the frequency has no use for, like plain weekdays on a monthly rule
+    UnsupportedWeekdays(String),
+}
+
+impl RecurrenceError {
+    pub fn get_text(&self) -> String {
+        match self {
+            RecurrenceError::MissingFrequency => "Recurrence rule has no FREQ".to_string(),
+            RecurrenceError::UnsupportedFrequency(frequency) => {
+                format!("Unsupported recurrence frequency: {}", frequency)
+            }
+            RecurrenceError::InvalidInterval(interval) => {
+                format!("Invalid recurrence interval: {}", interval)
+            }
+            RecurrenceError::InvalidWeekday(weekday) => {
+                format!("Invalid recurrence weekday: {}", weekday)
+            }
+            RecurrenceError::InvalidDate(date) => format!("Invalid recurrence date: {}", date),
+            RecurrenceError::InvalidCount(count) => {
+                format!("Invalid recurrence count: {}", count)
+            }
+            RecurrenceError::UnsupportedWeekdays(weekdays) => format!(
+                "Recurrence weekdays {} only work on weekly rules, or with an ordinal on monthly \
+                 rules",
+                weekdays
+            ),
+        }
+    }
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_weekday_code(code: &str) -> Option<Weekday> {
+    WEEKDAYS
+        .iter()
+        .find(|weekday| weekday_code(**weekday) == code)
+        .copied()
+}
+
+fn ordinal_text(ordinal: i8) -> String {
+    match ordinal {
+        -1 => "last".to_string(),
+        1 => "1st".to_string(),
+        2 => "2nd".to_string(),
+        3 => "3rd".to_string(),
+        n => format!("{}th", n),
+    }
+}
+
+/// Number of months from the month of `from` to the month of `to`
+fn months_between(from: NaiveDate, to: NaiveDate) -> i64 {
+    (to.year() as i64 - from.year() as i64) * 12 + to.month() as i64 - from.month() as i64
+}
+
+/// The date of the nth weekday in a month, counting from the end of the month for negative n
+fn nth_weekday_of_month(year: i32, month: u32, ordinal: i8, weekday: Weekday) -> Option<NaiveDate> {
+    if ordinal >= 0 {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, ordinal as u8)
+    } else {
+        let first_of_next =
+            NaiveDate::from_ymd_opt(year, month, 1)?.checked_add_months(Months::new(1))?;
+        let last_day = first_of_next.pred_opt()?;
+        let back =
+            (last_day.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        let last_weekday = last_day.checked_sub_days(Days::new(back as u64))?;
+        last_weekday
+            .checked_sub_days(Days::new(7 * (-(ordinal as i64) - 1) as u64))
+            .filter(|date| date.month() == month)
+    }
+}
+
+impl Recurrence {
+    /// The first occurrence after `previous`, where `start` is the first occurrence of the series
+    /// and `occurrences` is how many occurrences have happened so far including `previous`.
+    /// Returns None once the rule has ended.
+    pub fn next_occurrence(
+        &self,
+        start: NaiveDate,
+        previous: NaiveDate,
+        occurrences: u32,
+    ) -> Option<NaiveDate> {
+        if self.count.is_some_and(|count| occurrences >= count) {
+            return None;
+        }
+
+        let interval = self.interval.max(1);
+        let next = match self.frequency {
+            Frequency::Daily => previous.checked_add_days(Days::new(interval as u64)),
+            Frequency::Weekly => {
+                if self.weekdays.is_empty() {
+                    previous.checked_add_days(Days::new(7 * interval as u64))
+                } else {
+                    let week_start = |date: NaiveDate| {
+                        date.checked_sub_days(Days::new(
+                            date.weekday().num_days_from_monday() as u64
+                        ))
+                    };
+                    let previous_week = week_start(previous)?;
+                    previous
+                        .iter_days()
+                        .skip(1)
+                        .take(7 * interval as usize + 7)
+                        .find(|day| {
+                            let weeks_apart = week_start(*day)
+                                .map(|week| (week - previous_week).num_weeks())
+                                .unwrap_or_default();
+                            weeks_apart % interval as i64 == 0
+                                && self.weekdays.contains(&day.weekday())
+                        })
+                }
+            }
+            Frequency::Monthly => match self.monthly_weekday {
+                None => self.nth_month_after(start, previous, interval),
+                Some((ordinal, weekday)) => {
+                    // months of the series starting with the one `previous` is in, months
+                    // without an nth weekday, like a 5th monday, are skipped
+                    let first_of_start = NaiveDate::from_ymd_opt(start.year(), start.month(), 1)?;
+                    let first_step = months_between(start, previous).max(0) as u32 / interval;
+                    (first_step..=first_step + 12).find_map(|step| {
+                        let month =
+                            first_of_start.checked_add_months(Months::new(interval * step))?;
+                        nth_weekday_of_month(month.year(), month.month(), ordinal, weekday)
+                            .filter(|date| *date > previous)
+                    })
+                }
+            },
+            Frequency::Yearly => self.nth_month_after(start, previous, 12 * interval),
+        };
+
+        next.filter(|next| self.until.is_none_or(|until| *next <= until))
+    }
+
+    /// The first date after `previous` that is a multiple of `months` months after `start`.
+    /// Days past the end of a shorter month are clamped to its last day, counting from the start
+    /// keeps that from carrying over into the months after it.
+    fn nth_month_after(
+        &self,
+        start: NaiveDate,
+        previous: NaiveDate,
+        months: u32,
+    ) -> Option<NaiveDate> {
+        let first_step = months_between(start, previous).max(0) as u32 / months;
+        (first_step..=first_step + 1)
+            .filter_map(|step| start.checked_add_months(Months::new(months * step)))
+            .find(|date| *date > previous)
+    }
+
+    /// Short human readable description, e.g. "Every 2 weeks on Mon, Thu"
+    pub fn describe(&self, time_zones: &TimeZoneSettings) -> String {
+        let mut text = if self.interval <= 1 {
+            format!("Every {}", self.frequency.unit_name())
+        } else {
+            format!("Every {} {}s", self.interval, self.frequency.unit_name())
+        };
+        if self.frequency == Frequency::Weekly && !self.weekdays.is_empty() {
+            let days: Vec<String> = self.weekdays.iter().map(|day| day.to_string()).collect();
+            text.push_str(&format!(" on {}", days.join(", ")));
+        }
+        if let (Frequency::Monthly, Some((ordinal, weekday))) =
+            (self.frequency, self.monthly_weekday)
+        {
+            text.push_str(&format!(" on the {} {}", ordinal_text(ordinal), weekday));
+        }
+        if let Some(until) = self.until {
+            text.push_str(&format!(" until {}", time_zones.format_date(until)));
+        }
+        if let Some(count) = self.count {
+            text.push_str(&format!(", {} times", count));
+        }
+        text
+    }
+
+    /// Format the rule as the value of an iCalendar RRULE property
+    pub fn to_rrule(&self) -> String {
+        let mut parts = vec![format!("FREQ={}", self.frequency.rrule_name())];
+        if self.interval > 1 {
+            parts.push(format!("INTERVAL={}", self.interval));
+        }
+        match (self.frequency, self.monthly_weekday) {
+            (Frequency::Monthly, Some((ordinal, weekday))) => {
+                parts.push(format!("BYDAY={}{}", ordinal, weekday_code(weekday)));
+            }
+            (Frequency::Weekly, _) if !self.weekdays.is_empty() => {
+                let days: Vec<&str> = self.weekdays.iter().map(|day| weekday_code(*day)).collect();
+                parts.push(format!("BYDAY={}", days.join(",")));
+            }
+            _ => {}
+        }
+        if let Some(until) = self.until {
+            parts.push(format!("UNTIL={}", until.format("%Y%m%d")));
+        }
+        if let Some(count) = self.count {
+            parts.push(format!("COUNT={}", count));
+        }
+        parts.join(";")
+    }
+
+    /// Parse the value of an iCalendar RRULE property, the inverse of `to_rrule`
+    pub fn from_rrule(rrule: &str) -> Result<Self, RecurrenceError> {
+        let rrule = rrule.trim().trim_start_matches("RRULE:");
+        let mut frequency = None;
+        let mut recurrence = Recurrence::default();
+        let mut byday = String::new();
+
+        for part in rrule.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part.split_once('=').unwrap_or((part, ""));
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        other => {
+                            return Err(RecurrenceError::UnsupportedFrequency(other.to_string()))
+                        }
+                    })
+                }
+                "INTERVAL" => {
+                    recurrence.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(|| RecurrenceError::InvalidInterval(value.to_string()))?;
+                }
+                "BYDAY" => {
+                    byday = value.to_string();
+                    for day in value.split(',') {
+                        let day = day.trim().to_ascii_uppercase();
+                        // the weekday code is the last two characters, anything before it the
+                        // ordinal
+                        let split = day
+                            .char_indices()
+                            .rev()
+                            .nth(1)
+                            .map_or(0, |(index, _)| index);
+                        let (ordinal, code) = day.split_at(split);
+                        let weekday = parse_weekday_code(code)
+                            .ok_or_else(|| RecurrenceError::InvalidWeekday(day.clone()))?;
+                        if ordinal.is_empty() {
+                            recurrence.weekdays.push(weekday);
+                        } else {
+                            let ordinal: i8 = ordinal
+                                .trim_start_matches('+')
+                                .parse()
+                                .ok()
+                                .filter(|ordinal: &i8| (-5..=5).contains(ordinal) && *ordinal != 0)
+                                .ok_or_else(|| RecurrenceError::InvalidWeekday(day.clone()))?;
+                            recurrence.monthly_weekday = Some((ordinal, weekday));
+                        }
+                    }
+                }
+                "UNTIL" => {
+                    let date = value.get(..8).unwrap_or(value);
+                    recurrence.until = Some(
+                        NaiveDate::parse_from_str(date, "%Y%m%d")
+                            .map_err(|_| RecurrenceError::InvalidDate(value.to_string()))?,
+                    );
+                }
+                "COUNT" => {
+                    recurrence.count = Some(
+                        value
+                            .parse()
+                            .map_err(|_| RecurrenceError::InvalidCount(value.to_string()))?,
+                    );
+                }
+                // other rule parts are not supported and ignored
+                _ => {}
+            }
+        }
+
+        recurrence.frequency = frequency.ok_or(RecurrenceError::MissingFrequency)?;
+        // weekdays the frequency does not use would be lost when writing the rule back out
+        let unused_weekdays = match recurrence.frequency {
+            Frequency::Weekly => recurrence.monthly_weekday.is_some(),
+            Frequency::Monthly => !recurrence.weekdays.is_empty(),
+            Frequency::Daily | Frequency::Yearly => !byday.is_empty(),
+        };
+        if unused_weekdays {
+            return Err(RecurrenceError::UnsupportedWeekdays(byday));
+        }
+        Ok(recurrence)
+    }
+
+    pub fn show_editor(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Repeat:");
+            egui::ComboBox::from_id_source("recurrence_frequency")
+                .selected_text(self.frequency.get_text())
+                .show_ui(ui, |ui| {
+                    [
+                        Frequency::Daily,
+                        Frequency::Weekly,
+                        Frequency::Monthly,
+                        Frequency::Yearly,
+                    ]
+                    .iter()
+                    .for_each(|frequency| {
+                        ui.selectable_value(&mut self.frequency, *frequency, frequency.get_text());
+                    });
+                });
+            ui.label("every");
+            ui.add(egui::DragValue::new(&mut self.interval).clamp_range(1..=365));
+            ui.label(format!("{}(s)", self.frequency.unit_name()));
+        });
+
+        match self.frequency {
+            Frequency::Weekly => {
+                ui.horizontal(|ui| {
+                    ui.label("On:");
+                    WEEKDAYS.iter().for_each(|weekday| {
+                        let mut selected = self.weekdays.contains(weekday);
+                        if ui.checkbox(&mut selected, weekday.to_string()).changed() {
+                            if selected {
+                                self.weekdays.push(*weekday);
+                                self.weekdays.sort_by_key(|day| day.num_days_from_monday());
+                            } else {
+                                self.weekdays.retain(|day| day != weekday);
+                            }
+                        }
+                    });
+                });
+            }
+            Frequency::Monthly => {
+                ui.horizontal(|ui| {
+                    let mut by_weekday = self.monthly_weekday.is_some();
+                    if ui.checkbox(&mut by_weekday, "On the").changed() {
+                        self.monthly_weekday = by_weekday.then_some((1, Weekday::Mon));
+                    }
+                    if let Some((ordinal, weekday)) = self.monthly_weekday.as_mut() {
+                        egui::ComboBox::from_id_source("recurrence_ordinal")
+                            .selected_text(ordinal_text(*ordinal))
+                            .show_ui(ui, |ui| {
+                                [1, 2, 3, 4, -1].iter().for_each(|option| {
+                                    ui.selectable_value(ordinal, *option, ordinal_text(*option));
+                                });
+                            });
+                        egui::ComboBox::from_id_source("recurrence_weekday")
+                            .selected_text(weekday.to_string())
+                            .show_ui(ui, |ui| {
+                                WEEKDAYS.iter().for_each(|option| {
+                                    ui.selectable_value(weekday, *option, option.to_string());
+                                });
+                            });
+                    } else {
+                        ui.label("weekday of the month, otherwise the same day of the month");
+                    }
+                });
+            }
+            Frequency::Daily | Frequency::Yearly => {}
+        }
+
+        ui.horizontal(|ui| {
+            let mut has_until = self.until.is_some();
+            if ui.checkbox(&mut has_until, "Until").changed() {
+                self.until = has_until.then(|| Local::now().date_naive());
+            }
+            if let Some(until) = self.until.as_mut() {
+                ui.push_id("recurrence_until", |ui| {
+                    ui.add(egui_extras::DatePickerButton::new(until));
+                });
+            }
+            let mut has_count = self.count.is_some();
+            if ui.checkbox(&mut has_count, "Occurrences").changed() {
+                self.count = has_count.then_some(10);
+            }
+            if let Some(count) = self.count.as_mut() {
+                ui.add(egui::DragValue::new(count).clamp_range(1..=9999));
+            }
+        });
+
+        ui.weak(format!("RRULE:{}", self.to_rrule()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parses_weekly_rule() {
+        let recurrence =
+            Recurrence::from_rrule("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;COUNT=5").unwrap();
+        assert_eq!(recurrence.frequency, Frequency::Weekly);
+        assert_eq!(recurrence.interval, 2);
+        assert_eq!(recurrence.weekdays, vec![Weekday::Mon, Weekday::Thu]);
+        assert_eq!(recurrence.count, Some(5));
+        assert_eq!(
+            recurrence.to_rrule(),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;COUNT=5"
+        );
+    }
+
+    #[test]
+    fn parses_monthly_weekday() {
+        let recurrence =
+            Recurrence::from_rrule("FREQ=MONTHLY;BYDAY=-1FR;UNTIL=20241231T000000Z").unwrap();
+        assert_eq!(recurrence.monthly_weekday, Some((-1, Weekday::Fri)));
+        assert_eq!(recurrence.until, Some(date(2024, 12, 31)));
+        let recurrence = Recurrence::from_rrule("FREQ=MONTHLY;BYDAY=+2TU").unwrap();
+        assert_eq!(recurrence.monthly_weekday, Some((2, Weekday::Tue)));
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        assert_eq!(
+            Recurrence::from_rrule("INTERVAL=2"),
+            Err(RecurrenceError::MissingFrequency)
+        );
+        assert_eq!(
+            Recurrence::from_rrule("FREQ=HOURLY"),
+            Err(RecurrenceError::UnsupportedFrequency("HOURLY".to_string()))
+        );
+        assert_eq!(
+            Recurrence::from_rrule("FREQ=DAILY;INTERVAL=0"),
+            Err(RecurrenceError::InvalidInterval("0".to_string()))
+        );
+        for byday in ["ÉA", "X", "-128MO", "0MO", "6MO"] {
+            assert_eq!(
+                Recurrence::from_rrule(&format!("FREQ=MONTHLY;BYDAY={}", byday)),
+                Err(RecurrenceError::InvalidWeekday(byday.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn next_weekly_occurrence_skips_weeks() {
+        let recurrence = Recurrence::from_rrule("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH").unwrap();
+        // a Monday
+        let first = date(2024, 5, 13);
+        let second = recurrence.next_occurrence(first, first, 1).unwrap();
+        assert_eq!(second, date(2024, 5, 16));
+        assert_eq!(
+            recurrence.next_occurrence(first, second, 2),
+            Some(date(2024, 5, 27))
+        );
+    }
+
+    #[test]
+    fn monthly_day_is_not_clamped_for_good() {
+        let recurrence = Recurrence::from_rrule("FREQ=MONTHLY").unwrap();
+        let start = date(2024, 1, 31);
+        let february = recurrence.next_occurrence(start, start, 1).unwrap();
+        assert_eq!(february, date(2024, 2, 29));
+        assert_eq!(
+            recurrence.next_occurrence(start, february, 2),
+            Some(date(2024, 3, 31))
+        );
+        let recurrence = Recurrence::from_rrule("FREQ=YEARLY").unwrap();
+        let start = date(2024, 2, 29);
+        let next_year = recurrence.next_occurrence(start, start, 1).unwrap();
+        assert_eq!(next_year, date(2025, 2, 28));
+        assert_eq!(
+            recurrence.next_occurrence(start, date(2027, 2, 28), 4),
+            Some(date(2028, 2, 29))
+        );
+    }
+
+    #[test]
+    fn next_monthly_weekday_can_be_in_the_same_month() {
+        let recurrence = Recurrence::from_rrule("FREQ=MONTHLY;BYDAY=2TU").unwrap();
+        // the first occurrence was set to the 1st, before the 2nd tuesday of May
+        let start = date(2024, 5, 1);
+        assert_eq!(
+            recurrence.next_occurrence(start, start, 1),
+            Some(date(2024, 5, 14))
+        );
+        assert_eq!(
+            recurrence.next_occurrence(start, date(2024, 5, 14), 2),
+            Some(date(2024, 6, 11))
+        );
+        let recurrence = Recurrence::from_rrule("FREQ=MONTHLY;INTERVAL=2;BYDAY=2TU").unwrap();
+        assert_eq!(
+            recurrence.next_occurrence(start, date(2024, 5, 14), 2),
+            Some(date(2024, 7, 9))
+        );
+    }
+
+    #[test]
+    fn rejects_weekdays_the_frequency_does_not_use() {
+        for rrule in [
+            "FREQ=MONTHLY;BYDAY=MO,WE",
+            "BYDAY=MO;FREQ=DAILY",
+            "FREQ=WEEKLY;BYDAY=1MO",
+        ] {
+            assert!(matches!(
+                Recurrence::from_rrule(rrule),
+                Err(RecurrenceError::UnsupportedWeekdays(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn next_monthly_occurrence_on_last_weekday() {
+        let recurrence = Recurrence::from_rrule("FREQ=MONTHLY;BYDAY=-1FR;COUNT=2").unwrap();
+        let start = date(2024, 5, 31);
+        assert_eq!(
+            recurrence.next_occurrence(start, start, 1),
+            Some(date(2024, 6, 28))
+        );
+        assert_eq!(
+            recurrence.next_occurrence(start, date(2024, 6, 28), 2),
+            None
+        );
+    }
+}
@@ -0,0 +1,379 @@
+use crate::project::Project;
+use crate::timezone::{self, TimeZoneSettings};
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use egui::{RichText, Ui};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Hour of the day reminders fire at
+const REMINDER_HOUR: u32 = 9;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub enum ReminderOffset {
+    /// The morning of the given number of days before the due date
+    DaysBefore(u32),
+    /// The morning of the due date
+    MorningOf,
+}
+
+impl ReminderOffset {
+    pub fn get_text(&self) -> String {
+        match self {
+            ReminderOffset::DaysBefore(1) => "1 day before".to_string(),
+            ReminderOffset::DaysBefore(days) => format!("{} days before", days),
+            ReminderOffset::MorningOf => "Morning of due date".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct Reminder {
+    pub offset: ReminderOffset,
+    /// The due date this reminder was last acknowledged for, a reminder fires again if the due
+    /// date moves
+    acknowledged_for: Option<NaiveDate>,
+}
+
+impl Default for Reminder {
+    fn default() -> Self {
+        Self {
+            offset: ReminderOffset::DaysBefore(1),
+            acknowledged_for: None,
+        }
+    }
+}
+
+impl Reminder {
+    pub fn new(offset: ReminderOffset) -> Self {
+        Self {
+            offset,
+            acknowledged_for: None,
+        }
+    }
+
+    /// When this reminder fires for an item due on the given date in a time zone, None being the
+    /// system time zone
+    pub fn trigger_time(&self, due_date: NaiveDate, zone: Option<Tz>) -> Option<DateTime<Utc>> {
+        let day = match self.offset {
+            ReminderOffset::DaysBefore(days) => {
+                due_date.checked_sub_days(Days::new(days as u64))?
+            }
+            ReminderOffset::MorningOf => due_date,
+        };
+        let time = NaiveTime::from_hms_opt(REMINDER_HOUR, 0, 0)?;
+        timezone::at_time(day, time, zone)
+    }
+
+    pub fn is_due(&self, due_date: NaiveDate, zone: Option<Tz>, now: DateTime<Utc>) -> bool {
+        self.acknowledged_for != Some(due_date)
+            && self
+                .trigger_time(due_date, zone)
+                .is_some_and(|trigger| trigger <= now)
+    }
+
+    pub fn acknowledge(&mut self, due_date: NaiveDate) {
+        self.acknowledged_for = Some(due_date);
+    }
+}
+
+/// Editor for a list of reminders, used in the project and task editors
+pub fn show_reminder_editor(ui: &mut Ui, reminders: &mut Vec<Reminder>) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Reminders:");
+        let mut removed = None;
+        reminders
+            .iter_mut()
+            .enumerate()
+            .for_each(|(index, reminder)| {
+                ui.group(|ui| {
+                    let mut morning_of = reminder.offset == ReminderOffset::MorningOf;
+                    if let ReminderOffset::DaysBefore(days) = &mut reminder.offset {
+                        ui.add(egui::DragValue::new(days).clamp_range(1..=365));
+                        ui.label("days before");
+                    }
+                    if ui.checkbox(&mut morning_of, "Morning of").changed() {
+                        reminder.offset = if morning_of {
+                            ReminderOffset::MorningOf
+                        } else {
+                            ReminderOffset::DaysBefore(1)
+                        };
+                    }
+                    if ui.small_button("x").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            });
+        if let Some(index) = removed {
+            reminders.remove(index);
+        }
+        if ui.button("Add reminder").clicked() {
+            reminders.push(Reminder::default());
+        }
+    });
+}
+
+/// Identifies a single reminder on a project or one of its tasks
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct ReminderKey {
+    pub project: Uuid,
+    pub task: Option<Uuid>,
+    pub reminder_index: usize,
+    pub due_date: NaiveDate,
+}
+
+pub struct Notification {
+    pub key: ReminderKey,
+    pub title: String,
+    pub detail: String,
+}
+
+/// Every reminder that has fired and not been acknowledged yet, skipping completed projects and
+/// tasks. Reminders fire in the morning of the time zone of their project.
+pub fn pending_notifications(
+    projects: &[Project],
+    time_zones: &TimeZoneSettings,
+    now: DateTime<Utc>,
+) -> Vec<Notification> {
+    let mut notifications = vec![];
+    projects.iter().for_each(|project| {
+        let zone = project.zone(time_zones);
+        project
+            .reminders
+            .iter()
+            .enumerate()
+            .filter(|_| !project.is_completed())
+            .filter(|(_, reminder)| reminder.is_due(project.end_date, zone, now))
+            .for_each(|(reminder_index, reminder)| {
+                notifications.push(Notification {
+                    key: ReminderKey {
+                        project: project.get_uuid(),
+                        task: None,
+                        reminder_index,
+                        due_date: project.end_date,
+                    },
+                    title: format!("Project due: {}", project.project_name),
+                    detail: format!(
+                        "Due {} ({})",
+                        time_zones.format_date(project.end_date),
+                        reminder.offset.get_text()
+                    ),
+                });
+            });
+
+        project
+            .tasks
+            .iter()
+            .filter(|task| !task.get_completed())
+            .for_each(|task| {
+                let Some(due_date) = task.due_date else {
+                    return;
+                };
+                task.reminders
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, reminder)| reminder.is_due(due_date, zone, now))
+                    .for_each(|(reminder_index, reminder)| {
+                        notifications.push(Notification {
+                            key: ReminderKey {
+                                project: project.get_uuid(),
+                                task: Some(task.get_uuid()),
+                                reminder_index,
+                                due_date,
+                            },
+                            title: format!("Task due: {}", task.text),
+                            detail: format!(
+                                "{}, due {} ({})",
+                                project.project_name,
+                                time_zones.format_date(due_date),
+                                reminder.offset.get_text()
+                            ),
+                        });
+                    });
+            });
+    });
+    notifications
+}
+
+pub fn acknowledge(projects: &mut [Project], key: &ReminderKey) {
+    let Some(project) = projects
+        .iter_mut()
+        .find(|project| project.get_uuid() == key.project)
+    else {
+        return;
+    };
+    let reminders = match key.task {
+        None => &mut project.reminders,
+        Some(task_uuid) => {
+            match project
+                .tasks
+                .iter_mut()
+                .find(|task| task.get_uuid() == task_uuid)
+            {
+                None => return,
+                Some(task) => &mut task.reminders,
+            }
+        }
+    };
+    if let Some(reminder) = reminders.get_mut(key.reminder_index) {
+        reminder.acknowledge(key.due_date);
+    }
+}
+
+/// Send a desktop notification through the freedesktop notification spec, using notify-send
+#[cfg(target_os = "linux")]
+fn send_desktop_notification(notification: &Notification) {
+    let _ = std::process::Command::new("notify-send")
+        .arg("--app-name=Project Planner")
+        .arg(&notification.title)
+        .arg(&notification.detail)
+        .spawn();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn send_desktop_notification(_notification: &Notification) {}
+
+pub enum NotificationCenterAction {
+    /// The user asked to be taken to the project with this uuid
+    GoToProject(Uuid),
+    None,
+}
+
+/// State for the notification center window
+#[derive(Default)]
+pub struct NotificationCenter {
+    pub open: bool,
+    /// Reminders a desktop notification was already sent for during this session
+    sent: HashSet<ReminderKey>,
+}
+
+impl NotificationCenter {
+    /// Send desktop notifications for reminders that have not had one yet
+    pub fn send_desktop_notifications(&mut self, notifications: &[Notification]) {
+        notifications.iter().for_each(|notification| {
+            if self.sent.insert(notification.key) {
+                send_desktop_notification(notification);
+            }
+        });
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        projects: &mut [Project],
+        notifications: &[Notification],
+        desktop_notifications: &mut bool,
+    ) -> NotificationCenterAction {
+        let mut action = NotificationCenterAction::None;
+        if !self.open {
+            return action;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Notifications")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if notifications.is_empty() {
+                    ui.label("No reminders right now.");
+                }
+                notifications.iter().for_each(|notification| {
+                    ui.horizontal(|ui| {
+                        ui.vertical(|ui| {
+                            ui.label(RichText::new(&notification.title).strong());
+                            ui.label(&notification.detail);
+                        });
+                        if ui.button("Go to").clicked() {
+                            action =
+                                NotificationCenterAction::GoToProject(notification.key.project);
+                        }
+                        if ui.button("Acknowledge").clicked() {
+                            acknowledge(projects, &notification.key);
+                        }
+                    });
+                    ui.separator();
+                });
+                if notifications.len() > 1 && ui.button("Acknowledge all").clicked() {
+                    notifications
+                        .iter()
+                        .for_each(|notification| acknowledge(projects, &notification.key));
+                }
+                ui.separator();
+                ui.checkbox(desktop_notifications, "Show desktop notifications")
+                    .on_hover_text("Uses notify-send on Linux");
+            });
+        self.open = open;
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::task::Task;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn project_with_reminder() -> Project {
+        let mut project = Project::default();
+        project.project_name = "Launch".to_string();
+        project.end_date = date(2024, 6, 10);
+        project.reminders = vec![Reminder::new(ReminderOffset::DaysBefore(1))];
+        let mut task = Task::new("Write copy".to_string(), "".to_string());
+        task.due_date = Some(date(2024, 6, 10));
+        task.reminders = vec![Reminder::new(ReminderOffset::MorningOf)];
+        project.tasks.push(task);
+        project
+    }
+
+    fn time_zones() -> TimeZoneSettings {
+        TimeZoneSettings {
+            workspace: Some(chrono_tz::UTC),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reminders_fire_in_the_morning() {
+        let projects = vec![project_with_reminder()];
+        let before = timezone::at_time(
+            date(2024, 6, 9),
+            NaiveTime::from_hms_opt(8, 59, 0).unwrap(),
+            Some(chrono_tz::UTC),
+        )
+        .unwrap();
+        assert!(pending_notifications(&projects, &time_zones(), before).is_empty());
+        let after = before + chrono::Duration::days(1) + chrono::Duration::minutes(1);
+        assert_eq!(
+            pending_notifications(&projects, &time_zones(), after).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn completed_projects_do_not_notify() {
+        let mut project = project_with_reminder();
+        project.tasks[0].set_completed(true);
+        let now = timezone::end_of_day(date(2024, 6, 10), Some(chrono_tz::UTC)).unwrap();
+        assert!(pending_notifications(&[project], &time_zones(), now).is_empty());
+    }
+
+    #[test]
+    fn acknowledged_reminders_fire_again_when_the_date_moves() {
+        let mut projects = vec![project_with_reminder()];
+        let now = timezone::end_of_day(date(2024, 6, 10), Some(chrono_tz::UTC)).unwrap();
+        let notifications = pending_notifications(&projects, &time_zones(), now);
+        notifications
+            .iter()
+            .for_each(|notification| acknowledge(&mut projects, &notification.key));
+        assert!(pending_notifications(&projects, &time_zones(), now).is_empty());
+        projects[0].end_date = date(2024, 6, 9);
+        assert_eq!(
+            pending_notifications(&projects, &time_zones(), now).len(),
+            1
+        );
+    }
+}
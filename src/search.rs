@@ -0,0 +1,475 @@
+use crate::project::Project;
+use crate::tag::TagFilter;
+use egui::text::LayoutJob;
+use egui::{Color32, Event, FontId, TextFormat, Ui};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+use uuid::Uuid;
+
+/// Maximum number of results shown in the search result list
+const MAX_SEARCH_RESULTS: usize = 50;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SearchField {
+    ProjectName,
+    ProjectDescription,
+    TaskText,
+    TaskDescription,
+}
+
+impl SearchField {
+    /// How strongly a match in this field counts towards a results ranking
+    fn weight(&self) -> u32 {
+        match self {
+            SearchField::ProjectName => 8,
+            SearchField::TaskText => 4,
+            SearchField::ProjectDescription => 2,
+            SearchField::TaskDescription => 1,
+        }
+    }
+
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            SearchField::ProjectName => "Project name",
+            SearchField::ProjectDescription => "Project description",
+            SearchField::TaskText => "Task",
+            SearchField::TaskDescription => "Task description",
+        }
+    }
+}
+
+/// A single searchable piece of text, identified by where it lives in the project list
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DocumentKey {
+    pub project: Uuid,
+    pub task: Option<Uuid>,
+    pub field: SearchField,
+}
+
+pub struct SearchResult {
+    pub key: DocumentKey,
+    pub score: u32,
+    pub text: String,
+    pub highlights: Vec<Range<usize>>,
+}
+
+/// Inverted index over every project and task text field.
+///
+/// The index is updated incrementally by `sync`, which only looks at the projects after
+/// `mark_dirty` was called and only re-tokenizes documents whose text changed, so it can be
+/// called every frame the search bar is in use.
+pub struct SearchIndex {
+    documents: HashMap<DocumentKey, String>,
+    postings: BTreeMap<String, HashSet<DocumentKey>>,
+    /// Whether the projects may have changed since the last sync
+    dirty: bool,
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self {
+            documents: HashMap::new(),
+            postings: BTreeMap::new(),
+            dirty: true,
+        }
+    }
+}
+
+impl SearchIndex {
+    /// Note that the projects may have changed, the next sync brings the index up to date
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn sync(&mut self, projects: &[Project]) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        let mut seen: HashSet<DocumentKey> = HashSet::with_capacity(self.documents.len());
+
+        projects.iter().for_each(|project| {
+            let project_uuid = project.get_uuid();
+            self.update_document(
+                DocumentKey {
+                    project: project_uuid,
+                    task: None,
+                    field: SearchField::ProjectName,
+                },
+                &project.project_name,
+                &mut seen,
+            );
+            self.update_document(
+                DocumentKey {
+                    project: project_uuid,
+                    task: None,
+                    field: SearchField::ProjectDescription,
+                },
+                &project.description,
+                &mut seen,
+            );
+            project.tasks.iter().for_each(|task| {
+                self.update_document(
+                    DocumentKey {
+                        project: project_uuid,
+                        task: Some(task.get_uuid()),
+                        field: SearchField::TaskText,
+                    },
+                    &task.text,
+                    &mut seen,
+                );
+                self.update_document(
+                    DocumentKey {
+                        project: project_uuid,
+                        task: Some(task.get_uuid()),
+                        field: SearchField::TaskDescription,
+                    },
+                    &task.description,
+                    &mut seen,
+                );
+            });
+        });
+
+        if seen.len() != self.documents.len() {
+            let removed: Vec<DocumentKey> = self
+                .documents
+                .keys()
+                .filter(|key| !seen.contains(key))
+                .copied()
+                .collect();
+            removed.iter().for_each(|key| self.remove_document(key));
+        }
+    }
+
+    fn update_document(&mut self, key: DocumentKey, text: &str, seen: &mut HashSet<DocumentKey>) {
+        seen.insert(key);
+        if self
+            .documents
+            .get(&key)
+            .is_some_and(|indexed_text| indexed_text == text)
+        {
+            return;
+        }
+
+        self.remove_document(&key);
+        tokenize(text).for_each(|token| {
+            self.postings.entry(token).or_default().insert(key);
+        });
+        self.documents.insert(key, text.to_string());
+    }
+
+    fn remove_document(&mut self, key: &DocumentKey) {
+        if let Some(old_text) = self.documents.remove(key) {
+            tokenize(&old_text).for_each(|token| {
+                if let Some(keys) = self.postings.get_mut(&token) {
+                    keys.remove(key);
+                    if keys.is_empty() {
+                        self.postings.remove(&token);
+                    }
+                }
+            });
+        }
+    }
+
+    /// Find every document containing all words of the query, either as whole words or prefixes,
+    /// that is shown with the tag filter. Results are sorted best match first.
+    pub fn search(
+        &self,
+        query: &str,
+        projects: &[Project],
+        tag_filter: &TagFilter,
+    ) -> Vec<SearchResult> {
+        let query_tokens: Vec<String> = tokenize(query).collect();
+        if query_tokens.is_empty() {
+            return vec![];
+        }
+
+        let mut scores: Option<HashMap<DocumentKey, u32>> = None;
+        for query_token in &query_tokens {
+            let mut token_scores: HashMap<DocumentKey, u32> = HashMap::new();
+            self.postings
+                .range(query_token.clone()..)
+                .take_while(|(token, _)| token.starts_with(query_token.as_str()))
+                .for_each(|(token, keys)| {
+                    let exactness = if token == query_token { 2 } else { 1 };
+                    keys.iter().for_each(|key| {
+                        let score = token_scores.entry(*key).or_default();
+                        *score = (*score).max(key.field.weight() * exactness);
+                    });
+                });
+
+            scores = Some(match scores {
+                None => token_scores,
+                Some(previous) => previous
+                    .into_iter()
+                    .filter_map(|(key, score)| {
+                        token_scores
+                            .get(&key)
+                            .map(|token_score| (key, score + token_score))
+                    })
+                    .collect(),
+            });
+        }
+
+        let mut results: Vec<SearchResult> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(key, _)| is_shown(key, projects, tag_filter))
+            .filter_map(|(key, score)| {
+                self.documents.get(&key).map(|text| SearchResult {
+                    key,
+                    score,
+                    highlights: find_highlights(text, &query_tokens),
+                    text: text.clone(),
+                })
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.text.cmp(&b.text)));
+        results.truncate(MAX_SEARCH_RESULTS);
+        results
+    }
+}
+
+/// Whether an input event can lead to an edit. Projects only change in response to input, so the
+/// index needs a sync only after frames with these events.
+pub fn is_editing_input(event: &Event) -> bool {
+    !matches!(
+        event,
+        Event::PointerMoved(_)
+            | Event::PointerGone
+            | Event::Scroll(_)
+            | Event::Zoom(_)
+            | Event::MouseWheel { .. }
+            | Event::WindowFocused(_)
+            | Event::Screenshot { .. }
+    )
+}
+
+/// Whether the project or task a document belongs to is shown with the tag filter
+fn is_shown(key: &DocumentKey, projects: &[Project], tag_filter: &TagFilter) -> bool {
+    let Some(project) = projects
+        .iter()
+        .find(|project| project.get_uuid() == key.project)
+    else {
+        return false;
+    };
+    match key.task {
+        None => tag_filter.matches_project(project),
+        Some(task_uuid) => project
+            .tasks
+            .iter()
+            .find(|task| task.get_uuid() == task_uuid)
+            .is_some_and(|task| tag_filter.matches_task(project, task)),
+    }
+}
+
+/// The words of a text with their byte ranges, lowercased. Indexing, searching and highlighting
+/// all split text this way.
+fn words(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut words = vec![];
+    let mut word_start = None;
+    let ends = text.char_indices().map(Some).chain([None]);
+    for end in ends {
+        let is_word_char = end.is_some_and(|(_, c)| c.is_alphanumeric());
+        let index = end.map_or(text.len(), |(index, _)| index);
+        match (is_word_char, word_start) {
+            (true, None) => word_start = Some(index),
+            (false, Some(start)) => {
+                words.push((start..index, text[start..index].to_lowercase()));
+                word_start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+fn tokenize(text: &str) -> impl Iterator<Item = String> {
+    words(text).into_iter().map(|(_, token)| token)
+}
+
+/// Byte ranges of `text` that made it match, the start of every word one of the query tokens is
+/// a prefix of. In order and not overlapping.
+fn find_highlights(text: &str, query_tokens: &[String]) -> Vec<Range<usize>> {
+    words(text)
+        .into_iter()
+        .filter_map(|(range, token)| {
+            let matched_chars = query_tokens
+                .iter()
+                .filter(|query_token| token.starts_with(query_token.as_str()))
+                .map(|query_token| query_token.chars().count())
+                .max()?;
+            let word = &text[range.clone()];
+            // lowercasing can change the number of characters, highlight the whole word then
+            if token.chars().count() != word.chars().count() {
+                return Some(range);
+            }
+            let end = word
+                .char_indices()
+                .nth(matched_chars)
+                .map_or(range.end, |(index, _)| range.start + index);
+            Some(range.start..end)
+        })
+        .collect()
+}
+
+/// Lay out text with the given byte ranges drawn highlighted
+pub fn highlighted_text(ui: &Ui, text: &str, highlights: &[Range<usize>]) -> LayoutJob {
+    let font_id = FontId::default();
+    let normal = TextFormat {
+        font_id: font_id.clone(),
+        color: ui.visuals().text_color(),
+        ..Default::default()
+    };
+    let highlighted = TextFormat {
+        font_id,
+        color: Color32::BLACK,
+        background: Color32::YELLOW,
+        ..Default::default()
+    };
+
+    let mut job = LayoutJob::default();
+    let mut position = 0;
+    highlights.iter().for_each(|range| {
+        job.append(&text[position..range.start], 0.0, normal.clone());
+        job.append(&text[range.clone()], 0.0, highlighted.clone());
+        position = range.end;
+    });
+    job.append(&text[position..], 0.0, normal);
+    job
+}
+
+/// Show the results of a search, returns the result the user activated if any
+pub fn show_search_results(
+    ui: &mut Ui,
+    results: &[SearchResult],
+    projects: &[Project],
+) -> Option<DocumentKey> {
+    let mut activated = None;
+
+    let visible_results: Vec<(&SearchResult, &Project)> = results
+        .iter()
+        .filter_map(|result| {
+            let project = projects
+                .iter()
+                .find(|project| project.get_uuid() == result.key.project)?;
+            Some((result, project))
+        })
+        .collect();
+
+    if visible_results.is_empty() {
+        ui.label("No matches found.");
+    }
+
+    visible_results.iter().for_each(|(result, project)| {
+        let project_name = &project.project_name;
+
+        ui.horizontal(|ui| {
+            let text = highlighted_text(ui, &result.text, &result.highlights);
+            if ui.link(text).clicked() {
+                activated = Some(result.key);
+            }
+            ui.weak(format!(
+                "{} in {}",
+                result.key.field.get_text(),
+                project_name
+            ));
+        });
+    });
+
+    activated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::task::Task;
+
+    fn project_with_tasks(name: &str, tasks: &[(&str, &[&str])]) -> Project {
+        let mut project = Project::default();
+        project.project_name = name.to_string();
+        project.tasks = tasks
+            .iter()
+            .map(|(text, tags)| {
+                let mut task = Task::new(text.to_string(), String::new());
+                task.tags = tags.iter().map(|tag| tag.to_string()).collect();
+                task
+            })
+            .collect();
+        project
+    }
+
+    #[test]
+    fn filters_before_cutting_results() {
+        let untagged: Vec<(&str, &[&str])> = (0..MAX_SEARCH_RESULTS + 10)
+            .map(|_| ("Report draft", [].as_slice()))
+            .collect();
+        let mut project = project_with_tasks("Website", &untagged);
+        project
+            .tasks
+            .push(Task::new("Report final".to_string(), String::new()));
+        project.tasks.last_mut().unwrap().tags = vec!["urgent".to_string()];
+        let projects = vec![project];
+        let mut index = SearchIndex::default();
+        index.sync(&projects);
+
+        let tag_filter = TagFilter {
+            selected: ["urgent".to_string()].into(),
+        };
+        let results = index.search("report", &projects, &tag_filter);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].text, "Report final");
+        let results = index.search("report", &projects, &TagFilter::default());
+        assert_eq!(results.len(), MAX_SEARCH_RESULTS);
+    }
+
+    #[test]
+    fn ranks_project_names_first() {
+        let projects = vec![
+            project_with_tasks("Website", &[("Plan the launch", &[])]),
+            project_with_tasks("Launch", &[]),
+        ];
+        let mut index = SearchIndex::default();
+        index.sync(&projects);
+        let results = index.search("launch", &projects, &TagFilter::default());
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].key.field, SearchField::ProjectName);
+        assert!(index
+            .search("launch website", &projects, &TagFilter::default())
+            .is_empty());
+    }
+
+    #[test]
+    fn syncs_only_when_dirty() {
+        let mut projects = vec![project_with_tasks("Website", &[("Write copy", &[])])];
+        let mut index = SearchIndex::default();
+        index.sync(&projects);
+        projects[0].tasks[0].text = "Write docs".to_string();
+        index.sync(&projects);
+        assert_eq!(
+            index.search("copy", &projects, &TagFilter::default()).len(),
+            1
+        );
+        index.mark_dirty();
+        index.sync(&projects);
+        assert!(index
+            .search("copy", &projects, &TagFilter::default())
+            .is_empty());
+        assert_eq!(
+            index.search("docs", &projects, &TagFilter::default()).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn highlights_word_prefixes_only() {
+        let tokens = vec!["re".to_string()];
+        // "re" inside "more" and "are" did not cause the match and is not highlighted
+        assert_eq!(
+            find_highlights("More reports are ready", &tokens),
+            vec![5..7, 17..19]
+        );
+        let tokens = vec!["über".to_string()];
+        assert_eq!(find_highlights("Das Übersicht", &tokens), vec![4..9]);
+    }
+}
@@ -0,0 +1,468 @@
+use crate::attachment;
+use crate::command::Keymap;
+use crate::timezone::{DisplayFormat, TimeZoneSettings};
+use chrono::{DateTime, Duration, Utc, Weekday};
+use egui::{Color32, Ui};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Storage key the preferences are saved under, apart from the project data
+pub const PREFERENCES_KEY: &str = "preferences";
+
+/// Folder the app keeps its files in inside a chosen data directory, so nothing else in the
+/// chosen directory is ever touched
+const OWN_DIR_NAME: &str = "cr_project_planner";
+
+/// File the project data is saved to inside the app folder of a chosen data directory
+const PROJECT_FILE_NAME: &str = "projects.json";
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Default, Debug)]
+pub enum Theme {
+    /// Follow the light or dark mode of the operating system
+    #[default]
+    System,
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Dark, Theme::Light];
+
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            Theme::System => "System",
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+        }
+    }
+
+    /// The visuals to use, `system_theme` being the mode of the operating system if known
+    pub fn visuals(&self, system_theme: Option<eframe::Theme>) -> egui::Visuals {
+        match self {
+            Theme::System => system_theme.unwrap_or(eframe::Theme::Dark).egui_visuals(),
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Default, Debug)]
+pub enum DateFormat {
+    /// 2024-05-17
+    #[default]
+    Iso,
+    /// 05/17/2024
+    UnitedStates,
+    /// 17.05.2024
+    European,
+    /// May 17, 2024
+    Written,
+}
+
+impl DateFormat {
+    pub const ALL: [DateFormat; 4] = [
+        DateFormat::Iso,
+        DateFormat::UnitedStates,
+        DateFormat::European,
+        DateFormat::Written,
+    ];
+
+    /// chrono format string
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            DateFormat::Iso => "%Y-%m-%d",
+            DateFormat::UnitedStates => "%m/%d/%Y",
+            DateFormat::European => "%d.%m.%Y",
+            DateFormat::Written => "%b %-d, %Y",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Default, Debug)]
+pub enum TimeFormat {
+    #[default]
+    TwentyFourHour,
+    TwelveHour,
+}
+
+impl TimeFormat {
+    pub const ALL: [TimeFormat; 2] = [TimeFormat::TwentyFourHour, TimeFormat::TwelveHour];
+
+    /// chrono format string
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            TimeFormat::TwentyFourHour => "%H:%M",
+            TimeFormat::TwelveHour => "%-I:%M %p",
+        }
+    }
+}
+
+/// How often to look for a new release on startup
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Default, Debug)]
+pub enum UpdateCheck {
+    Never,
+    EveryLaunch,
+    #[default]
+    Daily,
+    Weekly,
+}
+
+impl UpdateCheck {
+    pub const ALL: [UpdateCheck; 4] = [
+        UpdateCheck::Never,
+        UpdateCheck::EveryLaunch,
+        UpdateCheck::Daily,
+        UpdateCheck::Weekly,
+    ];
+
+    pub fn get_text(&self) -> &'static str {
+        match self {
+            UpdateCheck::Never => "Never",
+            UpdateCheck::EveryLaunch => "Every launch",
+            UpdateCheck::Daily => "Once a day",
+            UpdateCheck::Weekly => "Once a week",
+        }
+    }
+
+    /// Whether to check for updates now given when updates were last checked for
+    pub fn is_due(&self, last_check: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        let interval = match self {
+            UpdateCheck::Never => return false,
+            UpdateCheck::EveryLaunch => return true,
+            UpdateCheck::Daily => Duration::days(1),
+            UpdateCheck::Weekly => Duration::weeks(1),
+        };
+        last_check.is_none_or(|last_check| now.signed_duration_since(last_check) >= interval)
+    }
+}
+
+/// User preferences, saved separately from the project data
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Preferences {
+    pub theme: Theme,
+    pub date_format: DateFormat,
+    pub time_format: TimeFormat,
+    /// First day of the week for timesheets, the workload view and quick add dates
+    pub week_start: Weekday,
+    /// Working days between the start and end date a new project is given
+    pub default_project_days: u64,
+    pub update_check: UpdateCheck,
+    pub last_update_check: Option<DateTime<Utc>>,
+    /// Directory chosen to keep the project data and attached files in, the app stores them in
+    /// its own folder inside it. None keeps them in the app storage.
+    pub data_dir: Option<PathBuf>,
+    pub keymap: Keymap,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            date_format: DateFormat::default(),
+            time_format: TimeFormat::default(),
+            week_start: Weekday::Mon,
+            default_project_days: 1,
+            update_check: UpdateCheck::default(),
+            last_update_check: None,
+            data_dir: None,
+            keymap: Keymap::default(),
+        }
+    }
+}
+
+impl Preferences {
+    pub fn display_format(&self) -> DisplayFormat {
+        DisplayFormat {
+            date: self.date_format.pattern(),
+            time: self.time_format.pattern(),
+            week_start: self.week_start,
+        }
+    }
+
+    /// The folder of the app inside the chosen data directory
+    fn own_dir(&self) -> Option<PathBuf> {
+        self.data_dir.as_ref().map(|dir| dir.join(OWN_DIR_NAME))
+    }
+
+    /// File the project data is saved to, None if it is kept in the app storage
+    pub fn project_file(&self) -> Option<PathBuf> {
+        self.own_dir().map(|dir| dir.join(PROJECT_FILE_NAME))
+    }
+
+    /// Directory attachments are stored in, `default_dir` being the one in the app storage
+    pub fn attachments_dir_or(&self, default_dir: Option<PathBuf>) -> Option<PathBuf> {
+        self.own_dir()
+            .map(|dir| dir.join("attachments"))
+            .or(default_dir)
+    }
+}
+
+/// Read data saved with [`write_data_file`], None if the file is missing or cannot be read
+pub fn read_data_file<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let json = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Save data as JSON. It is written to a temporary file first, so an interrupted save leaves the
+/// previous file intact.
+pub fn write_data_file<T: Serialize>(path: &Path, value: &T) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string(value)?;
+    let temporary = path.with_extension("json.tmp");
+    fs::write(&temporary, json)?;
+    fs::rename(temporary, path)
+}
+
+/// Window for editing the preferences
+#[derive(Default)]
+pub struct SettingsWindow {
+    pub open: bool,
+    data_dir: String,
+    data_dir_status: Option<Result<String, String>>,
+}
+
+impl SettingsWindow {
+    /// Show the window, returns true if a preference changed that needs applying.
+    /// `default_attachments_dir` is the attachments directory in the app storage.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        preferences: &mut Preferences,
+        default_attachments_dir: Option<&Path>,
+        time_zones: &TimeZoneSettings,
+    ) -> bool {
+        if !self.open {
+            return false;
+        }
+        let before = (
+            preferences.theme,
+            preferences.display_format(),
+            preferences.data_dir.clone(),
+        );
+
+        let mut open = self.open;
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("settings_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Theme:");
+                        ui.horizontal(|ui| {
+                            Theme::ALL.iter().for_each(|theme| {
+                                ui.selectable_value(
+                                    &mut preferences.theme,
+                                    *theme,
+                                    theme.get_text(),
+                                );
+                            });
+                        });
+                        ui.end_row();
+
+                        let example = Utc::now();
+                        ui.label("Date format:");
+                        egui::ComboBox::from_id_source("date_format")
+                            .selected_text(
+                                example
+                                    .format(preferences.date_format.pattern())
+                                    .to_string(),
+                            )
+                            .show_ui(ui, |ui| {
+                                DateFormat::ALL.iter().for_each(|format| {
+                                    ui.selectable_value(
+                                        &mut preferences.date_format,
+                                        *format,
+                                        example.format(format.pattern()).to_string(),
+                                    );
+                                });
+                            });
+                        ui.end_row();
+
+                        ui.label("Time format:");
+                        ui.horizontal(|ui| {
+                            TimeFormat::ALL.iter().for_each(|format| {
+                                ui.selectable_value(
+                                    &mut preferences.time_format,
+                                    *format,
+                                    example.format(format.pattern()).to_string(),
+                                );
+                            });
+                        });
+                        ui.end_row();
+
+                        ui.label("Week starts on:");
+                        egui::ComboBox::from_id_source("week_start")
+                            .selected_text(preferences.week_start.to_string())
+                            .show_ui(ui, |ui| {
+                                WEEKDAYS.iter().for_each(|weekday| {
+                                    ui.selectable_value(
+                                        &mut preferences.week_start,
+                                        *weekday,
+                                        weekday.to_string(),
+                                    );
+                                });
+                            });
+                        ui.end_row();
+
+                        ui.label("New projects last:");
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::DragValue::new(&mut preferences.default_project_days)
+                                    .clamp_range(1..=3650),
+                            );
+                            ui.label("working days");
+                        });
+                        ui.end_row();
+
+                        ui.label("Check for updates:");
+                        egui::ComboBox::from_id_source("update_check")
+                            .selected_text(preferences.update_check.get_text())
+                            .show_ui(ui, |ui| {
+                                UpdateCheck::ALL.iter().for_each(|policy| {
+                                    ui.selectable_value(
+                                        &mut preferences.update_check,
+                                        *policy,
+                                        policy.get_text(),
+                                    );
+                                });
+                            });
+                        ui.end_row();
+                    });
+                if let Some(last_check) = preferences.last_update_check {
+                    ui.weak(format!(
+                        "Last checked {}",
+                        time_zones.format_date_time(&last_check)
+                    ));
+                }
+
+                ui.separator();
+                self.show_data_dir(ui, preferences, default_attachments_dir);
+            });
+        self.open = open;
+
+        before
+            != (
+                preferences.theme,
+                preferences.display_format(),
+                preferences.data_dir.clone(),
+            )
+    }
+
+    fn show_data_dir(
+        &mut self,
+        ui: &mut Ui,
+        preferences: &mut Preferences,
+        default_attachments_dir: Option<&Path>,
+    ) {
+        ui.label("Data directory:");
+        match &preferences.own_dir() {
+            Some(dir) => ui.monospace(dir.display().to_string()),
+            None => ui.weak("The app storage"),
+        };
+        let mut new_dir = None;
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.data_dir).hint_text("/path/to/directory"));
+            if ui
+                .add_enabled(!self.data_dir.trim().is_empty(), egui::Button::new("Use"))
+                .clicked()
+            {
+                new_dir = Some(Some(PathBuf::from(self.data_dir.trim())));
+            }
+            if preferences.data_dir.is_some() && ui.button("Reset to default").clicked() {
+                new_dir = Some(None);
+            }
+        });
+        ui.weak(format!(
+            "Project data and attached files are kept in a {} folder inside it and moved over on \
+             a switch, project data already in that folder is replaced",
+            OWN_DIR_NAME
+        ));
+
+        if let Some(new_dir) = new_dir {
+            let default_dir = default_attachments_dir.map(Path::to_path_buf);
+            let current = preferences.attachments_dir_or(default_dir.clone());
+            let switched = Preferences {
+                data_dir: new_dir.clone(),
+                ..Default::default()
+            };
+            let copied = match (&current, &switched.attachments_dir_or(default_dir)) {
+                (Some(current), Some(target)) => attachment::copy_attachments(current, target),
+                _ => Ok(0),
+            };
+            self.data_dir_status = Some(
+                copied
+                    .map(|copied| {
+                        preferences.data_dir = new_dir;
+                        self.data_dir.clear();
+                        format!(
+                            "Switched the data directory, copied {} attached files",
+                            copied
+                        )
+                    })
+                    .map_err(|err| format!("Could not use the directory: {}", err)),
+            );
+        }
+        match &self.data_dir_status {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(err)) => {
+                ui.colored_label(Color32::LIGHT_RED, err);
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_stay_in_own_folder_of_data_dir() {
+        let preferences = Preferences {
+            data_dir: Some(PathBuf::from("/home/user/Documents")),
+            ..Default::default()
+        };
+        let own_dir = Path::new("/home/user/Documents").join(OWN_DIR_NAME);
+        assert_eq!(
+            preferences.project_file(),
+            Some(own_dir.join(PROJECT_FILE_NAME))
+        );
+        assert_eq!(
+            preferences.attachments_dir_or(Some(PathBuf::from("/default"))),
+            Some(own_dir.join("attachments"))
+        );
+        let preferences = Preferences::default();
+        assert_eq!(preferences.project_file(), None);
+        assert_eq!(
+            preferences.attachments_dir_or(Some(PathBuf::from("/default"))),
+            Some(PathBuf::from("/default"))
+        );
+    }
+
+    #[test]
+    fn data_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("data-dir-test-{}", uuid::Uuid::new_v4()));
+        let file = dir.join(OWN_DIR_NAME).join(PROJECT_FILE_NAME);
+        assert_eq!(read_data_file::<Vec<u32>>(&file), None);
+        write_data_file(&file, &vec![1, 2, 3]).unwrap();
+        assert_eq!(read_data_file::<Vec<u32>>(&file), Some(vec![1, 2, 3]));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
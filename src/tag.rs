@@ -0,0 +1,385 @@
+use crate::project::task::Task;
+use crate::project::Project;
+use egui::ecolor::Hsva;
+use egui::{Color32, Response, RichText, Ui};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Colors assigned to tags, tags without an assigned color get one derived from their name
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct TagRegistry {
+    colors: BTreeMap<String, [u8; 3]>,
+}
+
+impl TagRegistry {
+    pub fn get_color(&self, tag: &str) -> Color32 {
+        match self.colors.get(tag) {
+            None => default_tag_color(tag),
+            Some([r, g, b]) => Color32::from_rgb(*r, *g, *b),
+        }
+    }
+
+    pub fn set_color(&mut self, tag: &str, color: [u8; 3]) {
+        self.colors.insert(tag.to_string(), color);
+    }
+
+    /// Every tag used by any project or task, in alphabetical order
+    pub fn all_tags(projects: &[Project]) -> BTreeSet<String> {
+        projects
+            .iter()
+            .flat_map(|project| {
+                project
+                    .tags
+                    .iter()
+                    .chain(project.tasks.iter().flat_map(|task| task.tags.iter()))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Rename a tag everywhere it is used, renaming to an existing tag merges the two
+    pub fn rename(&mut self, projects: &mut [Project], from: &str, to: &str) {
+        let to = to.trim();
+        if to.is_empty() || from == to {
+            return;
+        }
+
+        projects.iter_mut().for_each(|project| {
+            rename_in(&mut project.tags, from, to);
+            project
+                .tasks
+                .iter_mut()
+                .for_each(|task| rename_in(&mut task.tags, from, to));
+        });
+
+        if let Some(color) = self.colors.remove(from) {
+            self.colors.entry(to.to_string()).or_insert(color);
+        }
+    }
+
+    /// Remove a tag from every project and task
+    pub fn delete(&mut self, projects: &mut [Project], tag: &str) {
+        projects.iter_mut().for_each(|project| {
+            project.tags.retain(|project_tag| project_tag != tag);
+            project
+                .tasks
+                .iter_mut()
+                .for_each(|task| task.tags.retain(|task_tag| task_tag != tag));
+        });
+        self.colors.remove(tag);
+    }
+}
+
+fn rename_in(tags: &mut Vec<String>, from: &str, to: &str) {
+    if let Some(index) = tags.iter().position(|tag| tag == from) {
+        if tags.iter().any(|tag| tag == to) {
+            tags.remove(index);
+        } else {
+            tags[index] = to.to_string();
+        }
+    }
+}
+
+/// Pick a stable color for a tag based on a hash of its name
+pub(crate) fn default_tag_color(tag: &str) -> Color32 {
+    let hash = tag.bytes().fold(2166136261u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(16777619)
+    });
+    let hue = (hash % 360) as f32 / 360.0;
+    Hsva::new(hue, 0.55, 0.65, 1.0).into()
+}
+
+/// Add a tag to a list, ignoring empty and duplicate tags. Returns true if the tag was added.
+pub fn add_tag(tags: &mut Vec<String>, tag: &str) -> bool {
+    let tag = tag.trim();
+    if tag.is_empty() || tags.iter().any(|existing| existing == tag) {
+        return false;
+    }
+    tags.push(tag.to_string());
+    true
+}
+
+pub fn tag_chip(ui: &mut Ui, tag: &str, registry: &TagRegistry) -> Response {
+    let color = registry.get_color(tag);
+    ui.add(
+        egui::Button::new(RichText::new(tag).small().color(Color32::WHITE))
+            .fill(color)
+            .rounding(8.0),
+    )
+}
+
+pub fn show_tag_chips(ui: &mut Ui, tags: &[String], registry: &TagRegistry) {
+    tags.iter().for_each(|tag| {
+        tag_chip(ui, tag, registry);
+    });
+}
+
+/// Editable list of tags, chips can be clicked to remove them
+pub fn show_tag_editor(
+    ui: &mut Ui,
+    tags: &mut Vec<String>,
+    next_tag: &mut String,
+    registry: &TagRegistry,
+) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Tags:");
+        let mut removed = None;
+        tags.iter().enumerate().for_each(|(index, tag)| {
+            if tag_chip(ui, tag, registry)
+                .on_hover_text("Click to remove tag")
+                .clicked()
+            {
+                removed = Some(index);
+            }
+        });
+        if let Some(index) = removed {
+            tags.remove(index);
+        }
+
+        let response = ui.add(
+            egui::TextEdit::singleline(next_tag)
+                .hint_text("New tag")
+                .desired_width(80.0),
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if (ui.button("Add tag").clicked() || submitted) && add_tag(tags, next_tag) {
+            next_tag.clear();
+        }
+    });
+}
+
+/// Narrows visible projects and tasks down to those carrying every selected tag.
+/// Tasks inherit the tags of the project they belong to.
+#[derive(Default, Clone)]
+pub struct TagFilter {
+    pub selected: BTreeSet<String>,
+}
+
+impl TagFilter {
+    pub fn is_active(&self) -> bool {
+        !self.selected.is_empty()
+    }
+
+    pub fn matches_task(&self, project: &Project, task: &Task) -> bool {
+        self.selected
+            .iter()
+            .all(|selected| project.tags.contains(selected) || task.tags.contains(selected))
+    }
+
+    pub fn matches_project(&self, project: &Project) -> bool {
+        self.selected
+            .iter()
+            .all(|selected| project.tags.contains(selected))
+            || project
+                .tasks
+                .iter()
+                .any(|task| self.matches_task(project, task))
+    }
+
+    pub fn show_menu(&mut self, ui: &mut Ui, projects: &[Project], registry: &TagRegistry) {
+        let title = if self.is_active() {
+            format!("Tags ({})", self.selected.len())
+        } else {
+            "Tags".to_string()
+        };
+        ui.menu_button(title, |ui| {
+            let all_tags = TagRegistry::all_tags(projects);
+            if all_tags.is_empty() {
+                ui.label("No tags in use");
+            }
+            all_tags.iter().for_each(|tag| {
+                let mut checked = self.selected.contains(tag);
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut checked, "").changed() {
+                        if checked {
+                            self.selected.insert(tag.clone());
+                        } else {
+                            self.selected.remove(tag);
+                        }
+                    }
+                    tag_chip(ui, tag, registry);
+                });
+            });
+            if self.is_active() && ui.button("Clear filter").clicked() {
+                self.selected.clear();
+                ui.close_menu();
+            }
+        });
+        // drop tags from the filter that no longer exist
+        if self.is_active() {
+            let all_tags = TagRegistry::all_tags(projects);
+            self.selected.retain(|tag| all_tags.contains(tag));
+        }
+    }
+}
+
+/// State for the tag management window
+#[derive(Default)]
+pub struct TagManager {
+    pub open: bool,
+    rename_text: BTreeMap<String, String>,
+    merge_target: BTreeMap<String, String>,
+}
+
+impl TagManager {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        registry: &mut TagRegistry,
+        projects: &mut [Project],
+    ) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Tag manager")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let all_tags = TagRegistry::all_tags(projects);
+                if all_tags.is_empty() {
+                    ui.label("No tags in use, add tags from the project or task editors.");
+                }
+
+                let mut rename: Option<(String, String)> = None;
+                let mut delete: Option<String> = None;
+
+                egui::Grid::new("tag_manager_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        all_tags.iter().for_each(|tag| {
+                            tag_chip(ui, tag, registry);
+
+                            let color = registry.get_color(tag);
+                            let mut rgb = [color.r(), color.g(), color.b()];
+                            if ui.color_edit_button_srgb(&mut rgb).changed() {
+                                registry.set_color(tag, rgb);
+                            }
+
+                            let rename_text = self
+                                .rename_text
+                                .entry(tag.clone())
+                                .or_insert_with(|| tag.clone());
+                            ui.add(egui::TextEdit::singleline(rename_text).desired_width(100.0));
+                            if ui.button("Rename").clicked() {
+                                rename = Some((tag.clone(), rename_text.clone()));
+                            }
+
+                            let merge_target = self.merge_target.entry(tag.clone()).or_default();
+                            egui::ComboBox::from_id_source(("merge_tag", tag))
+                                .selected_text(if merge_target.is_empty() {
+                                    "Merge into..."
+                                } else {
+                                    merge_target.as_str()
+                                })
+                                .show_ui(ui, |ui| {
+                                    all_tags.iter().filter(|other| *other != tag).for_each(
+                                        |other| {
+                                            ui.selectable_value(merge_target, other.clone(), other);
+                                        },
+                                    );
+                                });
+                            if ui
+                                .add_enabled(!merge_target.is_empty(), egui::Button::new("Merge"))
+                                .clicked()
+                            {
+                                rename = Some((tag.clone(), merge_target.clone()));
+                            }
+
+                            if ui
+                                .button("Delete")
+                                .on_hover_text("Double click to remove this tag everywhere")
+                                .double_clicked()
+                            {
+                                delete = Some(tag.clone());
+                            }
+                            ui.end_row();
+                        });
+                    });
+
+                if let Some((from, to)) = rename {
+                    registry.rename(projects, &from, &to);
+                    self.rename_text.clear();
+                    self.merge_target.clear();
+                }
+                if let Some(tag) = delete {
+                    registry.delete(projects, &tag);
+                    self.rename_text.remove(&tag);
+                    self.merge_target.remove(&tag);
+                }
+            });
+        self.open = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn project_with_tags(project_tags: &[&str], task_tags: &[&str]) -> Project {
+        let mut project = Project::default();
+        project.tags = tags(project_tags);
+        let mut task = Task::new("Write copy".to_string(), "".to_string());
+        task.tags = tags(task_tags);
+        project.tasks.push(task);
+        project
+    }
+
+    #[test]
+    fn renaming_changes_every_use() {
+        let mut registry = TagRegistry::default();
+        let mut projects = vec![project_with_tags(&["web", "urgent"], &["web"])];
+        registry.rename(&mut projects, "web", " website ");
+        assert_eq!(projects[0].tags, tags(&["website", "urgent"]));
+        assert_eq!(projects[0].tasks[0].tags, tags(&["website"]));
+    }
+
+    #[test]
+    fn renaming_into_an_existing_tag_merges_them() {
+        let mut registry = TagRegistry::default();
+        registry.set_color("web", [1, 2, 3]);
+        registry.set_color("website", [4, 5, 6]);
+        let mut projects = vec![project_with_tags(&["web", "website"], &["web"])];
+
+        registry.rename(&mut projects, "web", "website");
+        assert_eq!(projects[0].tags, tags(&["website"]));
+        assert_eq!(projects[0].tasks[0].tags, tags(&["website"]));
+        assert_eq!(
+            TagRegistry::all_tags(&projects),
+            BTreeSet::from(["website".to_string()])
+        );
+        // The color of the tag merged into is kept
+        assert_eq!(registry.get_color("website"), Color32::from_rgb(4, 5, 6));
+    }
+
+    #[test]
+    fn renaming_to_nothing_is_ignored() {
+        let mut registry = TagRegistry::default();
+        let mut projects = vec![project_with_tags(&["web"], &[])];
+        registry.rename(&mut projects, "web", "  ");
+        assert_eq!(projects[0].tags, tags(&["web"]));
+    }
+
+    #[test]
+    fn deleting_removes_every_use() {
+        let mut registry = TagRegistry::default();
+        let mut projects = vec![project_with_tags(&["web", "urgent"], &["web"])];
+        registry.delete(&mut projects, "web");
+        assert_eq!(projects[0].tags, tags(&["urgent"]));
+        assert!(projects[0].tasks[0].tags.is_empty());
+    }
+
+    #[test]
+    fn added_tags_are_trimmed_and_unique() {
+        let mut list = tags(&["web"]);
+        assert!(!add_tag(&mut list, " web "));
+        assert!(!add_tag(&mut list, ""));
+        assert!(add_tag(&mut list, " urgent "));
+        assert_eq!(list, tags(&["web", "urgent"]));
+    }
+}
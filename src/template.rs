@@ -0,0 +1,423 @@
+use crate::calendar::WorkCalendar;
+use crate::estimate::EstimateUnit;
+use crate::project::task::{Priority, Task};
+use crate::project::Project;
+use crate::recurrence::Recurrence;
+use crate::reminder::Reminder;
+use chrono::{Days, NaiveDate};
+use chrono_tz::Tz;
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A task as stored in a template, with its due date relative to the project start date
+#[derive(Serialize, Deserialize, PartialEq, Clone, Default, Debug)]
+#[serde(default)]
+pub struct TemplateTask {
+    pub text: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub estimate: Option<f32>,
+    pub priority: Priority,
+    /// Calendar days from the project start date to the task due date, working days if the
+    /// template counts in working days
+    pub due_offset: Option<i64>,
+    pub recurrence: Option<Recurrence>,
+    pub reminders: Vec<Reminder>,
+}
+
+/// A reusable project outline. Text fields may contain `{{placeholder}}`s that are filled in when
+/// a project is created from the template.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct ProjectTemplate {
+    /// Name of the template itself, shown when picking a template
+    pub name: String,
+    pub project_name: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    /// Calendar days from the project start date to its end date, working days if the template
+    /// counts in working days
+    pub duration_days: u64,
+    /// Whether the duration and due offsets are in working days of the template calendar. Only
+    /// templates saved while offsets were counted in working days set this.
+    pub working_days: bool,
+    pub estimate_unit: EstimateUnit,
+    pub reminders: Vec<Reminder>,
+    pub calendar_override: Option<WorkCalendar>,
+    pub time_zone: Option<Tz>,
+    pub tasks: Vec<TemplateTask>,
+}
+
+impl Default for ProjectTemplate {
+    fn default() -> Self {
+        Self {
+            name: "Unnamed template".to_string(),
+            project_name: "".to_string(),
+            description: "".to_string(),
+            tags: vec![],
+            duration_days: 1,
+            working_days: false,
+            estimate_unit: EstimateUnit::default(),
+            reminders: vec![],
+            calendar_override: None,
+            time_zone: None,
+            tasks: vec![],
+        }
+    }
+}
+
+/// Copy reminders without their acknowledgement state
+fn fresh_reminders(reminders: &[Reminder]) -> Vec<Reminder> {
+    reminders
+        .iter()
+        .map(|reminder| Reminder::new(reminder.offset))
+        .collect()
+}
+
+impl ProjectTemplate {
+    /// Capture a project as a template, completion state and tracked time are left out. Dates are
+    /// kept as calendar days from the start date, so every task lands on the same weekday again.
+    pub fn from_project(name: String, project: &Project) -> Self {
+        Self {
+            name,
+            project_name: project.project_name.clone(),
+            description: project.description.clone(),
+            tags: project.tags.clone(),
+            duration_days: (project.end_date - project.start_date).num_days().max(1) as u64,
+            working_days: false,
+            estimate_unit: project.estimate_unit,
+            reminders: fresh_reminders(&project.reminders),
+            calendar_override: project.calendar_override.clone(),
+            time_zone: project.time_zone,
+            tasks: project
+                .tasks
+                .iter()
+                .map(|task| TemplateTask {
+                    text: task.text.clone(),
+                    description: task.description.clone(),
+                    tags: task.tags.clone(),
+                    estimate: task.estimate,
+                    priority: task.priority,
+                    due_offset: task
+                        .due_date
+                        .map(|due_date| (due_date - project.start_date).num_days()),
+                    recurrence: task.recurrence.clone(),
+                    reminders: fresh_reminders(&task.reminders),
+                })
+                .collect(),
+        }
+    }
+
+    /// The calendar projects created from this template count working days in
+    pub fn calendar<'a>(&'a self, workspace_calendar: &'a WorkCalendar) -> &'a WorkCalendar {
+        self.calendar_override
+            .as_ref()
+            .unwrap_or(workspace_calendar)
+    }
+
+    /// The date a number of days after the start date, counting working days of `calendar` if
+    /// the template is in working days
+    fn offset_date(&self, start_date: NaiveDate, days: i64, calendar: &WorkCalendar) -> NaiveDate {
+        if self.working_days {
+            calendar.add_working_days(start_date, days)
+        } else if days >= 0 {
+            start_date
+                .checked_add_days(Days::new(days as u64))
+                .unwrap_or(start_date)
+        } else {
+            start_date
+                .checked_sub_days(Days::new(days.unsigned_abs()))
+                .unwrap_or(start_date)
+        }
+    }
+
+    /// End date of a project created from this template starting on the given date
+    pub fn end_date(&self, start_date: NaiveDate, workspace_calendar: &WorkCalendar) -> NaiveDate {
+        self.offset_date(
+            start_date,
+            self.duration_days as i64,
+            self.calendar(workspace_calendar),
+        )
+    }
+
+    /// Every placeholder used anywhere in the template, sorted and without duplicates
+    pub fn placeholders(&self) -> BTreeSet<String> {
+        let mut placeholders = BTreeSet::new();
+        let mut collect = |text: &str| placeholders.extend(find_placeholders(text));
+        collect(&self.project_name);
+        collect(&self.description);
+        self.tags.iter().for_each(|tag| collect(tag));
+        self.tasks.iter().for_each(|task| {
+            collect(&task.text);
+            collect(&task.description);
+            task.tags.iter().for_each(|tag| collect(tag));
+        });
+        placeholders
+    }
+
+    /// Fill a freshly created project with the contents of this template. Task due dates are
+    /// shifted to be relative to the project start date.
+    pub fn apply_to(
+        &self,
+        project: &mut Project,
+        values: &BTreeMap<String, String>,
+        workspace_calendar: &WorkCalendar,
+    ) {
+        let fill = |text: &str| fill_placeholders(text, values);
+        project.tags = self.tags.iter().map(|tag| fill(tag)).collect();
+        project.estimate_unit = self.estimate_unit;
+        project.reminders = fresh_reminders(&self.reminders);
+        project.calendar_override = self.calendar_override.clone();
+        project.time_zone = self.time_zone;
+        let start_date = project.start_date;
+        let calendar = self.calendar(workspace_calendar);
+        project.tasks = self
+            .tasks
+            .iter()
+            .map(|template_task| {
+                let mut task =
+                    Task::new(fill(&template_task.text), fill(&template_task.description));
+                task.tags = template_task.tags.iter().map(|tag| fill(tag)).collect();
+                task.estimate = template_task.estimate;
+                task.priority = template_task.priority;
+                task.due_date = template_task
+                    .due_offset
+                    .map(|offset| self.offset_date(start_date, offset, calendar));
+                task.recurrence = template_task.recurrence.clone();
+                task.reminders = fresh_reminders(&template_task.reminders);
+                task
+            })
+            .collect();
+    }
+}
+
+/// Names of the `{{placeholder}}`s in a piece of text, in order of appearance
+pub fn find_placeholders(text: &str) -> Vec<String> {
+    let mut placeholders = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            break;
+        };
+        let name = after_open[..end].trim();
+        if !name.is_empty() && !name.contains('{') {
+            placeholders.push(name.to_string());
+        }
+        rest = &after_open[end + 2..];
+    }
+    placeholders
+}
+
+/// Replace every `{{placeholder}}` that has a value, unknown or empty placeholders are left as they
+/// are
+pub fn fill_placeholders(text: &str, values: &BTreeMap<String, String>) -> String {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            break;
+        };
+        filled.push_str(&rest[..start]);
+        match values.get(after_open[..end].trim()) {
+            Some(value) if !value.is_empty() => filled.push_str(value),
+            _ => filled.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after_open[end + 2..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+pub enum TemplatePickerAction {
+    /// A template was picked, the new project form should be filled from it
+    Selected(usize),
+    None,
+}
+
+/// Template selection and placeholder values for the new project window
+#[derive(Default)]
+pub struct TemplatePicker {
+    pub selected: Option<usize>,
+    pub values: BTreeMap<String, String>,
+}
+
+impl TemplatePicker {
+    pub fn selected_template<'a>(
+        &self,
+        templates: &'a [ProjectTemplate],
+    ) -> Option<&'a ProjectTemplate> {
+        self.selected.and_then(|index| templates.get(index))
+    }
+
+    /// Show the template picker, along with an input for every placeholder in the selected
+    /// template and in the extra texts, which are the project name and description being entered
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        templates: &mut Vec<ProjectTemplate>,
+        extra_texts: &[&str],
+    ) -> TemplatePickerAction {
+        let mut action = TemplatePickerAction::None;
+        if self.selected.is_some_and(|index| index >= templates.len()) {
+            self.selected = None;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Template:");
+            let selected_text = self
+                .selected_template(templates)
+                .map(|template| template.name.clone())
+                .unwrap_or("None".to_string());
+            egui::ComboBox::from_id_source("project_template")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    if ui
+                        .selectable_label(self.selected.is_none(), "None")
+                        .clicked()
+                    {
+                        self.selected = None;
+                    }
+                    templates.iter().enumerate().for_each(|(index, template)| {
+                        if ui
+                            .selectable_label(self.selected == Some(index), &template.name)
+                            .clicked()
+                        {
+                            self.selected = Some(index);
+                            action = TemplatePickerAction::Selected(index);
+                        }
+                    });
+                });
+            if let Some(index) = self.selected {
+                if ui
+                    .button("Delete template")
+                    .on_hover_text("Double click to delete this template")
+                    .double_clicked()
+                {
+                    templates.remove(index);
+                    self.selected = None;
+                }
+            }
+        });
+
+        let mut placeholders = self
+            .selected_template(templates)
+            .map(|template| template.placeholders())
+            .unwrap_or_default();
+        extra_texts
+            .iter()
+            .for_each(|text| placeholders.extend(find_placeholders(text)));
+        if let Some(template) = self.selected_template(templates) {
+            ui.label(format!(
+                "{} tasks, {} {}days long",
+                template.tasks.len(),
+                template.duration_days,
+                if template.working_days {
+                    "working "
+                } else {
+                    ""
+                }
+            ));
+        }
+        placeholders.iter().for_each(|placeholder| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{{{{{}}}}}:", placeholder));
+                ui.text_edit_singleline(self.values.entry(placeholder.clone()).or_default());
+            });
+        });
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn values(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn template_with_task(due_offset: i64) -> ProjectTemplate {
+        ProjectTemplate {
+            tasks: vec![TemplateTask {
+                text: "Kickoff with {{client}}".to_string(),
+                due_offset: Some(due_offset),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn placeholders_are_found_and_filled() {
+        assert_eq!(
+            find_placeholders("{{client}} audit for {{ year }} by {{client}}"),
+            vec!["client", "year", "client"]
+        );
+        assert_eq!(
+            fill_placeholders(
+                "{{client}} audit for {{year}} {{unclosed",
+                &values(&[("client", "Acme"), ("year", "")])
+            ),
+            "Acme audit for {{year}} {{unclosed"
+        );
+    }
+
+    #[test]
+    fn offsets_are_calendar_days_from_the_start_date() {
+        let template = template_with_task(5);
+        let mut project = Project::default();
+        // Monday, the task lands on saturday
+        project.start_date = date(2024, 6, 3);
+        template.apply_to(
+            &mut project,
+            &values(&[("client", "Acme")]),
+            &WorkCalendar::default(),
+        );
+        assert_eq!(project.tasks[0].text, "Kickoff with Acme");
+        assert_eq!(project.tasks[0].due_date, Some(date(2024, 6, 8)));
+    }
+
+    #[test]
+    fn working_day_offsets_skip_non_working_days() {
+        let template = ProjectTemplate {
+            working_days: true,
+            ..template_with_task(5)
+        };
+        let mut project = Project::default();
+        project.start_date = date(2024, 6, 3);
+        template.apply_to(&mut project, &values(&[]), &WorkCalendar::default());
+        assert_eq!(project.tasks[0].due_date, Some(date(2024, 6, 10)));
+    }
+
+    #[test]
+    fn saved_projects_keep_their_dates() {
+        let mut project = Project::default();
+        project.start_date = date(2024, 6, 3);
+        project.end_date = date(2024, 6, 16);
+        let mut task = Task::new("Review".to_string(), "".to_string());
+        task.due_date = Some(date(2024, 6, 8));
+        project.tasks.push(task);
+
+        let template = ProjectTemplate::from_project("Release".to_string(), &project);
+        assert!(!template.working_days);
+        let start_date = date(2024, 7, 1);
+        assert_eq!(
+            template.end_date(start_date, &WorkCalendar::default()),
+            date(2024, 7, 14)
+        );
+        let mut copy = Project::default();
+        copy.start_date = start_date;
+        template.apply_to(&mut copy, &values(&[]), &WorkCalendar::default());
+        assert_eq!(copy.tasks[0].due_date, Some(date(2024, 7, 6)));
+    }
+}
@@ -0,0 +1,345 @@
+use crate::project::Project;
+use crate::timezone::TimeZoneSettings;
+use chrono::{DateTime, Days, Duration, Local, NaiveDate, NaiveTime, Utc};
+use egui::{Color32, RichText, Ui};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+/// A block of time spent working on a task
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct TimeEntry {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub note: String,
+}
+
+impl TimeEntry {
+    pub fn duration(&self) -> Duration {
+        self.end.signed_duration_since(self.start)
+    }
+}
+
+impl Default for TimeEntry {
+    fn default() -> Self {
+        Self {
+            start: Utc::now(),
+            end: Utc::now(),
+            note: "".to_string(),
+        }
+    }
+}
+
+/// Format a duration as hours and minutes, e.g. "2h 05m"
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+/// Stop every running timer except the one on the task with the given uuid
+pub fn stop_other_timers(projects: &mut [Project], running_task: Uuid) {
+    projects
+        .iter_mut()
+        .flat_map(|project| project.tasks.iter_mut())
+        .filter(|task| task.get_uuid() != running_task && task.is_timer_running())
+        .for_each(|task| task.stop_timer());
+}
+
+/// Form used in the task editor to record time manually
+#[derive(Clone)]
+pub struct TimeEntryForm {
+    date: NaiveDate,
+    start_time: String,
+    end_time: String,
+    note: String,
+    error: Option<String>,
+}
+
+impl Default for TimeEntryForm {
+    fn default() -> Self {
+        Self {
+            date: Local::now().date_naive(),
+            start_time: "09:00".to_string(),
+            end_time: "10:00".to_string(),
+            note: "".to_string(),
+            error: None,
+        }
+    }
+}
+
+impl TimeEntryForm {
+    /// Show the form, returns a new time entry when the user adds a valid one. Times are entered
+    /// in the display time zone.
+    pub fn show(&mut self, ui: &mut Ui, time_zones: &TimeZoneSettings) -> Option<TimeEntry> {
+        let mut entry = None;
+        ui.horizontal(|ui| {
+            ui.push_id("time_entry_date", |ui| {
+                ui.add(egui_extras::DatePickerButton::new(&mut self.date));
+            });
+            ui.add(egui::TextEdit::singleline(&mut self.start_time).desired_width(50.0))
+                .on_hover_text("Start time, HH:MM");
+            ui.label("to");
+            ui.add(egui::TextEdit::singleline(&mut self.end_time).desired_width(50.0))
+                .on_hover_text("End time, HH:MM");
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.note).hint_text("Note"));
+            if ui.button("Add time entry").clicked() {
+                match self.build_entry(time_zones) {
+                    Ok(new_entry) => {
+                        entry = Some(new_entry);
+                        self.note.clear();
+                        self.error = None;
+                    }
+                    Err(error) => self.error = Some(error),
+                }
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::LIGHT_RED, error);
+        }
+        entry
+    }
+
+    fn build_entry(&self, time_zones: &TimeZoneSettings) -> Result<TimeEntry, String> {
+        let parse = |text: &str| {
+            NaiveTime::parse_from_str(text.trim(), "%H:%M")
+                .map_err(|_| format!("\"{}\" is not a valid time, use HH:MM", text))
+                .and_then(|time| {
+                    time_zones.from_display(self.date, time).ok_or_else(|| {
+                        format!(
+                            "\"{}\" does not exist on {}",
+                            text,
+                            time_zones.format_date(self.date)
+                        )
+                    })
+                })
+        };
+        let start = parse(&self.start_time)?;
+        let end = parse(&self.end_time)?;
+        if end <= start {
+            return Err("End time needs to be after the start time".to_string());
+        }
+        Ok(TimeEntry {
+            start,
+            end,
+            note: self.note.clone(),
+        })
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum TimesheetPeriod {
+    Day,
+    Week,
+}
+
+/// One row of a timesheet, a time entry along with where it was recorded
+pub struct TimesheetRow {
+    pub date: NaiveDate,
+    pub project_name: String,
+    pub task_text: String,
+    pub duration: Duration,
+    pub note: String,
+}
+
+/// State for the timesheet window
+pub struct Timesheet {
+    pub open: bool,
+    period: TimesheetPeriod,
+    anchor_date: NaiveDate,
+    export_path: String,
+    export_status: Option<Result<String, String>>,
+}
+
+impl Default for Timesheet {
+    fn default() -> Self {
+        Self {
+            open: false,
+            period: TimesheetPeriod::Week,
+            anchor_date: Local::now().date_naive(),
+            export_path: "timesheet.csv".to_string(),
+            export_status: None,
+        }
+    }
+}
+
+impl Timesheet {
+    /// First and last day (inclusive) covered by the selected period, weeks starting on the
+    /// preferred week start
+    fn date_range(&self, time_zones: &TimeZoneSettings) -> (NaiveDate, NaiveDate) {
+        match self.period {
+            TimesheetPeriod::Day => (self.anchor_date, self.anchor_date),
+            TimesheetPeriod::Week => {
+                let first_day = time_zones.week_start(self.anchor_date);
+                let last_day = first_day
+                    .checked_add_days(Days::new(6))
+                    .unwrap_or(first_day);
+                (first_day, last_day)
+            }
+        }
+    }
+
+    /// Every time entry started within the selected period, in chronological order. Entries are
+    /// grouped by the date they started on in the display time zone.
+    pub fn rows(&self, projects: &[Project], time_zones: &TimeZoneSettings) -> Vec<TimesheetRow> {
+        let (first_day, last_day) = self.date_range(time_zones);
+        let mut rows: Vec<(DateTime<Utc>, TimesheetRow)> = projects
+            .iter()
+            .flat_map(|project| {
+                project.tasks.iter().flat_map(move |task| {
+                    task.time_entries.iter().map(move |entry| {
+                        (
+                            entry.start,
+                            TimesheetRow {
+                                date: time_zones.display_date(&entry.start),
+                                project_name: project.project_name.clone(),
+                                task_text: task.text.clone(),
+                                duration: entry.duration(),
+                                note: entry.note.clone(),
+                            },
+                        )
+                    })
+                })
+            })
+            .filter(|(_, row)| row.date >= first_day && row.date <= last_day)
+            .collect();
+        rows.sort_by_key(|(start, _)| *start);
+        rows.into_iter().map(|(_, row)| row).collect()
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        projects: &[Project],
+        time_zones: &TimeZoneSettings,
+    ) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Timesheet")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.period, TimesheetPeriod::Day, "Day");
+                    ui.selectable_value(&mut self.period, TimesheetPeriod::Week, "Week");
+                    ui.push_id("timesheet_date", |ui| {
+                        ui.add(egui_extras::DatePickerButton::new(&mut self.anchor_date));
+                    });
+                });
+                let (first_day, last_day) = self.date_range(time_zones);
+                ui.label(format!(
+                    "{} to {}",
+                    time_zones.format_date(first_day),
+                    time_zones.format_date(last_day)
+                ));
+                ui.separator();
+
+                let rows = self.rows(projects, time_zones);
+                if rows.is_empty() {
+                    ui.label("No time recorded in this period.");
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("timesheet_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Date");
+                                ui.strong("Project");
+                                ui.strong("Task");
+                                ui.strong("Time");
+                                ui.strong("Note");
+                                ui.end_row();
+
+                                let mut current_day = None;
+                                let mut day_total = Duration::zero();
+                                rows.iter().for_each(|row| {
+                                    if current_day.is_some_and(|day| day != row.date) {
+                                        show_day_total(ui, day_total);
+                                        day_total = Duration::zero();
+                                    }
+                                    current_day = Some(row.date);
+                                    day_total = day_total + row.duration;
+
+                                    ui.label(time_zones.format_date(row.date));
+                                    ui.label(&row.project_name);
+                                    ui.label(&row.task_text);
+                                    ui.label(format_duration(row.duration));
+                                    ui.label(&row.note);
+                                    ui.end_row();
+                                });
+                                if current_day.is_some() {
+                                    show_day_total(ui, day_total);
+                                }
+                            });
+                    });
+
+                let total = rows
+                    .iter()
+                    .fold(Duration::zero(), |total, row| total + row.duration);
+                ui.separator();
+                ui.label(RichText::new(format!("Total: {}", format_duration(total))).strong());
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Export to:");
+                    ui.text_edit_singleline(&mut self.export_path);
+                    if ui.button("Export CSV").clicked() {
+                        self.export_status = Some(
+                            export_csv(Path::new(&self.export_path), &rows)
+                                .map(|_| format!("Exported {} entries", rows.len()))
+                                .map_err(|err| format!("Export failed: {}", err)),
+                        );
+                    }
+                });
+                match &self.export_status {
+                    None => {}
+                    Some(Ok(message)) => {
+                        ui.label(message);
+                    }
+                    Some(Err(message)) => {
+                        ui.colored_label(Color32::LIGHT_RED, message);
+                    }
+                }
+            });
+        self.open = open;
+    }
+}
+
+fn show_day_total(ui: &mut Ui, total: Duration) {
+    ui.label("");
+    ui.label("");
+    ui.label(RichText::new("Day total").weak());
+    ui.label(RichText::new(format_duration(total)).strong());
+    ui.label("");
+    ui.end_row();
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn export_csv(path: &Path, rows: &[TimesheetRow]) -> std::io::Result<()> {
+    let mut csv = String::from("date,project,task,hours,note\n");
+    rows.iter().for_each(|row| {
+        csv.push_str(&format!(
+            "{},{},{},{:.2},{}\n",
+            row.date,
+            csv_field(&row.project_name),
+            csv_field(&row.task_text),
+            row.duration.num_minutes() as f64 / 60.0,
+            csv_field(&row.note)
+        ));
+    });
+    fs::write(path, csv)
+}
@@ -0,0 +1,222 @@
+use chrono::{
+    DateTime, Datelike, Days, Duration, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime,
+    NaiveTime, Offset, TimeZone, Utc, Weekday,
+};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use egui::Ui;
+use serde::{Deserialize, Serialize};
+
+/// Time zone preferences. Timestamps are always stored in UTC, these decide which zone project
+/// deadlines are in and which zone timestamps are displayed in.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(default)]
+pub struct TimeZoneSettings {
+    /// Zone the deadlines of projects without their own zone are in, None uses the system zone
+    pub workspace: Option<Tz>,
+    /// Zone timestamps are displayed in, None uses the system zone
+    pub display: Option<Tz>,
+    /// How dates and times are written, set from the preferences
+    #[serde(skip)]
+    pub display_format: DisplayFormat,
+}
+
+/// How dates and times are written and which day weeks start on
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DisplayFormat {
+    /// chrono format string for dates
+    pub date: &'static str,
+    /// chrono format string for times of day
+    pub time: &'static str,
+    pub week_start: Weekday,
+}
+
+impl Default for DisplayFormat {
+    fn default() -> Self {
+        Self {
+            date: "%Y-%m-%d",
+            time: "%H:%M",
+            week_start: Weekday::Mon,
+        }
+    }
+}
+
+impl TimeZoneSettings {
+    pub fn to_display(&self, timestamp: &DateTime<Utc>) -> DateTime<FixedOffset> {
+        to_zone(timestamp, self.display)
+    }
+
+    /// Format a timestamp in the display zone using a chrono format string
+    pub fn format(&self, timestamp: &DateTime<Utc>, format: &str) -> String {
+        self.to_display(timestamp).format(format).to_string()
+    }
+
+    /// A timestamp in the display zone with the preferred date and time format
+    pub fn format_date_time(&self, timestamp: &DateTime<Utc>) -> String {
+        self.format(
+            timestamp,
+            &format!("{} {}", self.display_format.date, self.display_format.time),
+        )
+    }
+
+    /// The time of day of a timestamp in the display zone with the preferred format
+    pub fn format_time(&self, timestamp: &DateTime<Utc>) -> String {
+        self.format(timestamp, self.display_format.time)
+    }
+
+    /// A date with the preferred format
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        date.format(self.display_format.date).to_string()
+    }
+
+    /// The first day of the week a date is in, by the preferred week start
+    pub fn week_start(&self, date: NaiveDate) -> NaiveDate {
+        week_start(date, self.display_format.week_start)
+    }
+
+    /// The calendar date a timestamp falls on in the display zone
+    pub fn display_date(&self, timestamp: &DateTime<Utc>) -> NaiveDate {
+        self.to_display(timestamp).date_naive()
+    }
+
+    pub fn display_today(&self) -> NaiveDate {
+        today_in(self.display)
+    }
+
+    /// Interpret a date and time entered by the user in the display zone
+    pub fn from_display(&self, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+        at_time(date, time, self.display)
+    }
+
+    pub fn show_editor(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Workspace time zone:");
+            time_zone_picker(ui, "workspace_time_zone", &mut self.workspace);
+        })
+        .response
+        .on_hover_text("Project deadlines are in this zone unless the project sets its own");
+        ui.horizontal(|ui| {
+            ui.label("Display time zone:");
+            time_zone_picker(ui, "display_time_zone", &mut self.display);
+        });
+    }
+}
+
+/// The first day of the week a date is in, weeks starting on `first_day`
+pub fn week_start(date: NaiveDate, first_day: Weekday) -> NaiveDate {
+    let offset = (7 + date.weekday().num_days_from_monday() - first_day.num_days_from_monday()) % 7;
+    date.checked_sub_days(Days::new(offset as u64))
+        .unwrap_or(date)
+}
+
+/// Convert a timestamp into a zone, None is the system zone
+pub fn to_zone(timestamp: &DateTime<Utc>, zone: Option<Tz>) -> DateTime<FixedOffset> {
+    match zone {
+        None => timestamp.with_timezone(&Local).fixed_offset(),
+        Some(zone) => timestamp.with_timezone(&zone).fixed_offset(),
+    }
+}
+
+/// The current date in a zone, None is the system zone
+pub fn today_in(zone: Option<Tz>) -> NaiveDate {
+    to_zone(&Utc::now(), zone).date_naive()
+}
+
+/// The instant a wall clock time on a date happens in a zone. Repeated times resolve to their
+/// first occurrence, times skipped by daylight saving changes move forward past the gap.
+pub fn at_time(date: NaiveDate, time: NaiveTime, zone: Option<Tz>) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::new(date, time);
+    match zone {
+        None => resolve_local(&Local, naive),
+        Some(zone) => resolve_local(&zone, naive),
+    }
+}
+
+fn resolve_local<Z: TimeZone>(zone: &Z, naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+    match zone.from_local_datetime(&naive) {
+        LocalResult::Single(local) | LocalResult::Ambiguous(local, _) => {
+            Some(local.with_timezone(&Utc))
+        }
+        LocalResult::None => {
+            // Read the time with the offset from before the gap, which lands just as far past it
+            let before = zone
+                .from_local_datetime(&naive.checked_sub_days(Days::new(1))?)
+                .earliest()?;
+            let offset = Duration::seconds(before.offset().fix().local_minus_utc() as i64);
+            let utc = naive.checked_sub_signed(offset)?;
+            Some(DateTime::from_naive_utc_and_offset(utc, Utc))
+        }
+    }
+}
+
+/// The last moment of a date in a zone
+pub fn end_of_day(date: NaiveDate, zone: Option<Tz>) -> Option<DateTime<Utc>> {
+    at_time(date, NaiveTime::from_hms_opt(23, 59, 59)?, zone)
+}
+
+pub fn zone_name(zone: Option<Tz>) -> String {
+    match zone {
+        None => "System time zone".to_string(),
+        Some(zone) => zone.name().to_string(),
+    }
+}
+
+/// Combo box for choosing an IANA time zone, or the system time zone
+pub fn time_zone_picker(ui: &mut Ui, id: &str, zone: &mut Option<Tz>) {
+    egui::ComboBox::from_id_source(id)
+        .selected_text(zone_name(*zone))
+        .width(200.0)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(zone, None, zone_name(None));
+            TZ_VARIANTS.iter().for_each(|variant| {
+                ui.selectable_value(zone, Some(*variant), variant.name());
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&NaiveDateTime::new(
+            date(year, month, day),
+            time(hour, minute),
+        ))
+    }
+
+    #[test]
+    fn ordinary_times_use_the_zone_offset() {
+        let zone = Some(chrono_tz::America::New_York);
+        assert_eq!(
+            at_time(date(2024, 1, 15), time(9, 0), zone),
+            Some(utc(2024, 1, 15, 14, 0))
+        );
+    }
+
+    #[test]
+    fn skipped_times_move_past_the_gap() {
+        let zone = Some(chrono_tz::America::New_York);
+        // 02:30 does not exist on this day, it is read as 03:30 daylight time
+        assert_eq!(
+            at_time(date(2024, 3, 10), time(2, 30), zone),
+            Some(utc(2024, 3, 10, 7, 30))
+        );
+    }
+
+    #[test]
+    fn repeated_times_use_the_first_occurrence() {
+        let zone = Some(chrono_tz::America::New_York);
+        assert_eq!(
+            at_time(date(2024, 11, 3), time(1, 30), zone),
+            Some(utc(2024, 11, 3, 5, 30))
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use crate::project::activity::ActivityKind;
+use crate::project::task::Task;
+use crate::project::Project;
+use egui::{Id, Order, RichText};
+use uuid::Uuid;
+
+/// Whether tasks keep their identity and leave their project, or are copied with a new uuid
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TransferKind {
+    Move,
+    Copy,
+}
+
+/// Identifies a task along with the project it is in
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct TaskRef {
+    pub project: Uuid,
+    pub task: Uuid,
+}
+
+/// A task being dragged out of its project
+#[derive(Clone, Copy, Debug)]
+pub struct TaskDragPayload {
+    pub from: TaskRef,
+}
+
+fn drag_payload_id() -> Id {
+    Id::new("task_drag_payload")
+}
+
+pub fn start_drag(ctx: &egui::Context, from: TaskRef) {
+    ctx.data_mut(|data| data.insert_temp(drag_payload_id(), TaskDragPayload { from }));
+}
+
+pub fn drag_payload(ctx: &egui::Context) -> Option<TaskDragPayload> {
+    ctx.data(|data| data.get_temp(drag_payload_id()))
+}
+
+/// Forget the dragged task once the pointer is released, call after every drop target was shown
+pub fn end_drag_if_released(ctx: &egui::Context) {
+    if ctx.input(|input| input.pointer.any_released()) {
+        ctx.data_mut(|data| data.remove::<TaskDragPayload>(drag_payload_id()));
+    }
+}
+
+/// Every selected task across all projects
+pub fn selected_tasks(projects: &[Project]) -> Vec<TaskRef> {
+    projects
+        .iter()
+        .flat_map(|project| {
+            project
+                .tasks
+                .iter()
+                .filter(|task| task.selected)
+                .map(|task| TaskRef {
+                    project: project.get_uuid(),
+                    task: task.get_uuid(),
+                })
+        })
+        .collect()
+}
+
+pub fn clear_selection(projects: &mut [Project]) {
+    projects
+        .iter_mut()
+        .flat_map(|project| project.tasks.iter_mut())
+        .for_each(|task| task.selected = false);
+}
+
+/// The tasks a drag applies to, every selected task when the dragged one is selected and
+/// otherwise just the dragged task
+pub fn dragged_tasks(projects: &[Project], payload: &TaskDragPayload) -> Vec<TaskRef> {
+    let selected = selected_tasks(projects);
+    if selected.contains(&payload.from) {
+        selected
+    } else {
+        vec![payload.from]
+    }
+}
+
+/// Take tasks out of their projects
+pub fn remove_tasks(projects: &mut [Project], tasks: &[TaskRef]) -> Vec<Task> {
+    let mut removed = vec![];
+    projects.iter_mut().for_each(|project| {
+        let project_uuid = project.get_uuid();
+        let (taken, kept) = project.tasks.drain(..).partition(|task| {
+            tasks.contains(&TaskRef {
+                project: project_uuid,
+                task: task.get_uuid(),
+            })
+        });
+        project.tasks = kept;
+        removed.extend::<Vec<Task>>(taken);
+    });
+    removed
+}
+
+/// Copies of tasks, each with a new uuid
+pub fn copy_tasks(projects: &[Project], tasks: &[TaskRef]) -> Vec<Task> {
+    projects
+        .iter()
+        .flat_map(|project| {
+            project.tasks.iter().filter(move |task| {
+                tasks.contains(&TaskRef {
+                    project: project.get_uuid(),
+                    task: task.get_uuid(),
+                })
+            })
+        })
+        .map(Task::copy)
+        .collect()
+}
+
+/// Tasks added to a project whose due date falls outside the project start and end date
+pub struct TransferWarning {
+    pub project_name: String,
+    pub task_text: String,
+}
+
+/// Add tasks to a project, returns a warning for every task due outside the project dates. Moved
+/// tasks record the move in their activity log.
+pub fn insert_tasks(
+    project: &mut Project,
+    tasks: Vec<Task>,
+    kind: TransferKind,
+) -> Vec<TransferWarning> {
+    let mut warnings = vec![];
+    tasks.into_iter().for_each(|mut task| {
+        task.selected = false;
+        // milestones belong to a project, a link into another project would dangle
+        if !project
+            .milestones
+            .iter()
+            .any(|milestone| Some(milestone.get_uuid()) == task.milestone)
+        {
+            task.milestone = None;
+        }
+        if kind == TransferKind::Move {
+            task.log_activity(ActivityKind::MovedToProject {
+                project_name: project.project_name.clone(),
+            });
+        }
+        if task.is_due_outside(project.start_date, project.end_date) {
+            warnings.push(TransferWarning {
+                project_name: project.project_name.clone(),
+                task_text: task.text.clone(),
+            });
+        }
+        project.tasks.push(task);
+    });
+    warnings
+}
+
+/// Move or copy tasks into the project with the given uuid. Tasks already in that project are
+/// left alone when moving.
+pub fn transfer_tasks(
+    projects: &mut [Project],
+    tasks: &[TaskRef],
+    destination: Uuid,
+    kind: TransferKind,
+) -> Vec<TransferWarning> {
+    let tasks: Vec<TaskRef> = match kind {
+        TransferKind::Move => tasks
+            .iter()
+            .filter(|task| task.project != destination)
+            .copied()
+            .collect(),
+        TransferKind::Copy => tasks.to_vec(),
+    };
+    let transferred = match kind {
+        TransferKind::Move => remove_tasks(projects, &tasks),
+        TransferKind::Copy => copy_tasks(projects, &tasks),
+    };
+    match projects
+        .iter_mut()
+        .find(|project| project.get_uuid() == destination)
+    {
+        Some(project) => insert_tasks(project, transferred, kind),
+        None => vec![],
+    }
+}
+
+/// Tasks that were cut or copied, waiting to be pasted into a project
+#[derive(Default)]
+pub struct TaskClipboard {
+    tasks: Vec<Task>,
+    /// Cut tasks keep their uuid the first time they are pasted
+    cut: bool,
+}
+
+impl TaskClipboard {
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Take the selected tasks out of their projects into the clipboard
+    pub fn cut(&mut self, projects: &mut [Project]) {
+        let selected = selected_tasks(projects);
+        self.tasks = remove_tasks(projects, &selected);
+        self.cut = true;
+    }
+
+    /// Copy the selected tasks into the clipboard
+    pub fn copy(&mut self, projects: &[Project]) {
+        self.tasks = copy_tasks(projects, &selected_tasks(projects));
+        self.cut = false;
+    }
+
+    /// The tasks to paste, cut tasks come out as they were the first time and as copies after.
+    /// Cut tasks that are back in a project, like after undoing the cut, are pasted as copies so
+    /// no uuid is in use twice.
+    pub fn paste(&mut self, projects: &[Project]) -> (Vec<Task>, TransferKind) {
+        let cut_tasks_exist = self.tasks.iter().any(|cut_task| {
+            projects
+                .iter()
+                .flat_map(|project| project.tasks.iter())
+                .any(|task| task.get_uuid() == cut_task.get_uuid())
+        });
+        if self.cut && !cut_tasks_exist {
+            self.cut = false;
+            (self.tasks.clone(), TransferKind::Move)
+        } else {
+            (
+                self.tasks.iter().map(Task::copy).collect(),
+                TransferKind::Copy,
+            )
+        }
+    }
+}
+
+/// Label following the pointer while tasks are dragged
+pub fn show_drag_preview(ctx: &egui::Context, task_count: usize) {
+    let Some(pointer) = ctx.pointer_hover_pos() else {
+        return;
+    };
+    let text = if task_count == 1 {
+        "Moving 1 task".to_string()
+    } else {
+        format!("Moving {} tasks", task_count)
+    };
+    egui::Area::new("task_drag_preview")
+        .order(Order::Tooltip)
+        .interactable(false)
+        .fixed_pos(pointer + egui::vec2(12.0, 12.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(text);
+                ui.label(RichText::new("Hold ctrl to copy").weak());
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project_with_tasks(texts: &[&str]) -> Project {
+        let mut project = Project::default();
+        project.tasks = texts
+            .iter()
+            .map(|text| Task::new(text.to_string(), String::new()))
+            .collect();
+        project
+    }
+
+    #[test]
+    fn cut_tasks_move_once_then_copy() {
+        let mut projects = vec![project_with_tasks(&["a", "b"])];
+        projects[0].tasks[0].selected = true;
+        let cut_uuid = projects[0].tasks[0].get_uuid();
+        let mut clipboard = TaskClipboard::default();
+        clipboard.cut(&mut projects);
+        assert_eq!(projects[0].tasks.len(), 1);
+
+        let (tasks, kind) = clipboard.paste(&projects);
+        assert_eq!(kind, TransferKind::Move);
+        assert_eq!(tasks[0].get_uuid(), cut_uuid);
+        let (tasks, kind) = clipboard.paste(&projects);
+        assert_eq!(kind, TransferKind::Copy);
+        assert_ne!(tasks[0].get_uuid(), cut_uuid);
+    }
+
+    #[test]
+    fn cut_tasks_back_in_a_project_paste_as_copies() {
+        let mut projects = vec![project_with_tasks(&["a", "b"])];
+        projects[0].tasks[0].selected = true;
+        let before = projects[0].tasks.clone();
+        let mut clipboard = TaskClipboard::default();
+        clipboard.cut(&mut projects);
+        // undoing the cut puts the task back
+        projects[0].tasks = before;
+
+        let (tasks, kind) = clipboard.paste(&projects);
+        assert_eq!(kind, TransferKind::Copy);
+        assert!(projects[0]
+            .tasks
+            .iter()
+            .all(|task| task.get_uuid() != tasks[0].get_uuid()));
+    }
+
+    #[test]
+    fn moving_logs_the_move_and_flags_dates_outside_the_project() {
+        let mut projects = vec![project_with_tasks(&["a"]), project_with_tasks(&[])];
+        projects[1].project_name = "Launch".to_string();
+        projects[0].tasks[0].due_date = projects[1].end_date.succ_opt();
+        let task = TaskRef {
+            project: projects[0].get_uuid(),
+            task: projects[0].tasks[0].get_uuid(),
+        };
+        let destination = projects[1].get_uuid();
+        let warnings = transfer_tasks(&mut projects, &[task], destination, TransferKind::Move);
+        assert_eq!(warnings.len(), 1);
+        assert!(projects[0].tasks.is_empty());
+        assert_eq!(projects[1].tasks[0].get_uuid(), task.task);
+        assert!(projects[1].tasks[0].activity.iter().any(|event| matches!(
+            &event.kind,
+            ActivityKind::MovedToProject { project_name } if project_name == "Launch"
+        )));
+    }
+}
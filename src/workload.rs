@@ -0,0 +1,584 @@
+use crate::calendar::WorkCalendar;
+use crate::estimate::EstimateUnit;
+use crate::people::{avatar_chip, remaining_hours_per_assignee, PeopleDirectory};
+use crate::project::task::Priority;
+use crate::project::Project;
+use crate::timezone::{week_start, TimeZoneSettings};
+use crate::transfer::TaskRef;
+use chrono::{Days, NaiveDate, Weekday};
+use egui::{Align2, Color32, FontId, Sense, Stroke, Ui};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Most leveling suggestions shown at once
+const MAX_SUGGESTIONS: usize = 10;
+
+/// Longest a task is ever shifted when looking for a leveling suggestion
+const MAX_SHIFT_WEEKS: u64 = 8;
+
+/// Planned work of one person in one week
+#[derive(Default, Clone)]
+pub struct WeekLoad {
+    pub hours: f32,
+    /// Tasks the hours come from, with the hours each contributes
+    pub tasks: Vec<(TaskRef, f32)>,
+}
+
+/// Hours a person can work in the week starting at `week`, their weekly capacity scaled down by
+/// the holidays that week
+pub fn week_capacity(weekly_capacity: f32, calendar: &WorkCalendar, week: NaiveDate) -> f32 {
+    let working_per_week = calendar.workweek.iter().filter(|worked| **worked).count();
+    if working_per_week == 0 {
+        return 0.0;
+    }
+    let week_end = week.checked_add_days(Days::new(6)).unwrap_or(week);
+    weekly_capacity * calendar.count_working_days(week, week_end) as f32 / working_per_week as f32
+}
+
+/// Spread the remaining estimated hours of every open, assigned task in an hours project evenly
+/// over the working days from today (or the project start) until the task is due (or the project
+/// ends). Overdue tasks land entirely on today. `shifted` plans one task as if it was due on
+/// another date. Weeks start on `first_day`.
+///
+/// People work on the workspace calendar, the same one their capacity is counted in, so hours are
+/// spread over its working days whichever calendar the project counts its deadlines in.
+pub fn plan_work(
+    projects: &[Project],
+    workspace_calendar: &WorkCalendar,
+    today: NaiveDate,
+    first_day: Weekday,
+    shifted: Option<(TaskRef, NaiveDate)>,
+) -> HashMap<(Uuid, NaiveDate), WeekLoad> {
+    let mut plan: HashMap<(Uuid, NaiveDate), WeekLoad> = HashMap::new();
+    projects
+        .iter()
+        .filter(|project| project.estimate_unit == EstimateUnit::Hours)
+        .for_each(|project| {
+            project
+                .tasks
+                .iter()
+                .filter(|task| !task.get_completed() && !task.assignees.is_empty())
+                .for_each(|task| {
+                    let Some(hours) = remaining_hours_per_assignee(task) else {
+                        return;
+                    };
+                    let task_ref = TaskRef {
+                        project: project.get_uuid(),
+                        task: task.get_uuid(),
+                    };
+                    let due_date = match shifted {
+                        Some((shifted_task, due_date)) if shifted_task == task_ref => due_date,
+                        _ => task.due_date.unwrap_or(project.end_date),
+                    };
+                    let start = today.max(project.start_date);
+                    let working_days: Vec<NaiveDate> = start
+                        .iter_days()
+                        .take_while(|day| *day <= due_date)
+                        .filter(|day| workspace_calendar.is_working_day(*day))
+                        .collect();
+                    let days = if working_days.is_empty() {
+                        vec![start]
+                    } else {
+                        working_days
+                    };
+                    let hours_per_day = hours / days.len() as f32;
+                    task.assignees.iter().for_each(|person| {
+                        days.iter().for_each(|day| {
+                            let load = plan
+                                .entry((*person, week_start(*day, first_day)))
+                                .or_default();
+                            load.hours += hours_per_day;
+                            match load.tasks.iter_mut().find(|(task, _)| *task == task_ref) {
+                                Some((_, task_hours)) => *task_hours += hours_per_day,
+                                None => load.tasks.push((task_ref, hours_per_day)),
+                            }
+                        });
+                    });
+                });
+        });
+    plan
+}
+
+/// Hours planned beyond capacity for the given people over the given weeks
+fn overload(
+    plan: &HashMap<(Uuid, NaiveDate), WeekLoad>,
+    people: &PeopleDirectory,
+    assignees: &[Uuid],
+    calendar: &WorkCalendar,
+    weeks: &[NaiveDate],
+) -> f32 {
+    assignees
+        .iter()
+        .filter_map(|assignee| people.get(*assignee))
+        .flat_map(|person| {
+            weeks.iter().map(move |week| {
+                let hours = plan
+                    .get(&(person.get_uuid(), *week))
+                    .map(|load| load.hours)
+                    .unwrap_or_default();
+                (hours - week_capacity(person.weekly_capacity, calendar, *week)).max(0.0)
+            })
+        })
+        .sum()
+}
+
+/// Moving the due date of a task later, within the slack its project leaves it, to take load off
+/// an over-allocated week
+#[derive(Clone, Debug)]
+pub struct LevelingSuggestion {
+    pub task: TaskRef,
+    pub task_text: String,
+    pub project_name: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Hours of over-allocation the shift removes
+    pub relief: f32,
+}
+
+/// Suggest due date shifts for low and normal priority tasks that contribute to an
+/// over-allocated week and are due before their project ends. Each task is shifted by whole
+/// weeks, and only when that lowers the over-allocation of its assignees.
+pub fn leveling_suggestions(
+    projects: &[Project],
+    people: &PeopleDirectory,
+    workspace_calendar: &WorkCalendar,
+    today: NaiveDate,
+    first_day: Weekday,
+    weeks: &[NaiveDate],
+) -> Vec<LevelingSuggestion> {
+    let plan = plan_work(projects, workspace_calendar, today, first_day, None);
+    // shifts can move work past the last shown week, count over-allocation there as well
+    let horizon: Vec<NaiveDate> = weeks
+        .first()
+        .map(|first| {
+            (0..weeks.len() as u64 + MAX_SHIFT_WEEKS)
+                .filter_map(|week| first.checked_add_days(Days::new(week * 7)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut candidates: Vec<TaskRef> = vec![];
+    people.people.iter().for_each(|person| {
+        weeks.iter().for_each(|week| {
+            let Some(load) = plan.get(&(person.get_uuid(), *week)) else {
+                return;
+            };
+            if load.hours > week_capacity(person.weekly_capacity, workspace_calendar, *week) {
+                load.tasks.iter().for_each(|(task, _)| {
+                    if !candidates.contains(task) {
+                        candidates.push(*task);
+                    }
+                });
+            }
+        });
+    });
+
+    let mut suggestions: Vec<LevelingSuggestion> = candidates
+        .into_iter()
+        .filter_map(|task_ref| {
+            let project = projects
+                .iter()
+                .find(|project| project.get_uuid() == task_ref.project)?;
+            let task = project
+                .tasks
+                .iter()
+                .find(|task| task.get_uuid() == task_ref.task)?;
+            let from = task.due_date?;
+            if task.priority >= Priority::High || from >= project.end_date {
+                return None;
+            }
+            let current = overload(&plan, people, &task.assignees, workspace_calendar, &horizon);
+            (1..=MAX_SHIFT_WEEKS)
+                .map_while(|shift_weeks| {
+                    let to = from.checked_add_days(Days::new(shift_weeks * 7))?;
+                    (to <= project.end_date || shift_weeks == 1).then(|| to.min(project.end_date))
+                })
+                .map(|to| {
+                    let shifted_plan = plan_work(
+                        projects,
+                        workspace_calendar,
+                        today,
+                        first_day,
+                        Some((task_ref, to)),
+                    );
+                    let shifted = overload(
+                        &shifted_plan,
+                        people,
+                        &task.assignees,
+                        workspace_calendar,
+                        &horizon,
+                    );
+                    (to, current - shifted)
+                })
+                .filter(|(_, relief)| *relief > 0.01)
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(to, relief)| LevelingSuggestion {
+                    task: task_ref,
+                    task_text: task.text.clone(),
+                    project_name: project.project_name.clone(),
+                    from,
+                    to,
+                    relief,
+                })
+        })
+        .collect();
+    suggestions.sort_by(|a, b| b.relief.total_cmp(&a.relief));
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+/// Fill color of a heatmap cell, green while there is room and red once over capacity
+fn utilization_color(hours: f32, capacity: f32) -> Color32 {
+    if hours <= 0.0 {
+        return Color32::from_gray(60);
+    }
+    if capacity <= 0.0 {
+        return Color32::from_rgb(200, 60, 60);
+    }
+    let utilization = hours / capacity;
+    if utilization > 1.0 {
+        Color32::from_rgb(200, 60, 60)
+    } else if utilization > 0.8 {
+        Color32::from_rgb(200, 150, 50)
+    } else {
+        Color32::from_rgb(60, 140, 70)
+    }
+}
+
+/// State for the workload heatmap window
+pub struct WorkloadWindow {
+    pub open: bool,
+    weeks: u64,
+    /// Planned work for the shown weeks, kept until the projects or the weeks change
+    plan: HashMap<(Uuid, NaiveDate), WeekLoad>,
+    suggestions: Option<Vec<LevelingSuggestion>>,
+    /// Today, the week start and the number of weeks the plan was made for, None once the
+    /// projects may have changed
+    planned_for: Option<(NaiveDate, Weekday, u64)>,
+}
+
+impl Default for WorkloadWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            weeks: 8,
+            plan: HashMap::new(),
+            suggestions: None,
+            planned_for: None,
+        }
+    }
+}
+
+impl WorkloadWindow {
+    /// Note that the projects may have changed, the plan and suggestions are made again the next
+    /// time the window is shown
+    pub fn mark_dirty(&mut self) {
+        self.planned_for = None;
+    }
+
+    /// Show the heatmap, returns a leveling suggestion the user chose to apply
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        projects: &[Project],
+        people: &PeopleDirectory,
+        workspace_calendar: &WorkCalendar,
+        time_zones: &TimeZoneSettings,
+    ) -> Option<LevelingSuggestion> {
+        if !self.open {
+            return None;
+        }
+        let today = time_zones.display_today();
+        let first_day = time_zones.display_format.week_start;
+
+        let mut applied = None;
+        let mut open = self.open;
+        egui::Window::new("Workload")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if people.people.is_empty() {
+                    ui.label("Add people and assign them tasks to see their workload.");
+                    return;
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Weeks:");
+                    ui.add(egui::DragValue::new(&mut self.weeks).clamp_range(1..=26));
+                });
+                ui.weak("Remaining estimates of tasks in hours projects, spread over the working days until each task is due");
+
+                let first_week = week_start(today, first_day);
+                let weeks: Vec<NaiveDate> = (0..self.weeks)
+                    .filter_map(|week| first_week.checked_add_days(Days::new(week * 7)))
+                    .collect();
+                if self.planned_for != Some((today, first_day, self.weeks)) {
+                    self.plan = plan_work(projects, workspace_calendar, today, first_day, None);
+                    if self.suggestions.is_some() {
+                        self.suggestions = Some(leveling_suggestions(
+                            projects,
+                            people,
+                            workspace_calendar,
+                            today,
+                            first_day,
+                            &weeks,
+                        ));
+                    }
+                    self.planned_for = Some((today, first_day, self.weeks));
+                }
+                egui::ScrollArea::horizontal().show(ui, |ui| {
+                    show_heatmap(
+                        ui,
+                        &self.plan,
+                        projects,
+                        people,
+                        workspace_calendar,
+                        &weeks,
+                        time_zones,
+                    );
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Suggest leveling")
+                        .on_hover_text("Find low and normal priority tasks that can be due later without passing their project end date")
+                        .clicked()
+                    {
+                        self.suggestions = Some(leveling_suggestions(
+                            projects,
+                            people,
+                            workspace_calendar,
+                            today,
+                            first_day,
+                            &weeks,
+                        ));
+                    }
+                    if self.suggestions.is_some() && ui.button("Clear").clicked() {
+                        self.suggestions = None;
+                    }
+                });
+                if let Some(suggestions) = &self.suggestions {
+                    if suggestions.is_empty() {
+                        ui.label("Nothing to level, or no task has slack left to shift.");
+                    }
+                    suggestions.iter().for_each(|suggestion| {
+                        ui.horizontal(|ui| {
+                            ui.label(format!(
+                                "Move \"{}\" ({}) from {} to {}, {:.1}h less over capacity",
+                                suggestion.task_text,
+                                suggestion.project_name,
+                                time_zones.format_date(suggestion.from),
+                                time_zones.format_date(suggestion.to),
+                                suggestion.relief
+                            ));
+                            if ui.button("Apply").clicked() {
+                                applied = Some(suggestion.clone());
+                            }
+                        });
+                    });
+                }
+            });
+        self.open = open;
+        if applied.is_some() {
+            self.suggestions = None;
+        }
+        applied
+    }
+}
+
+fn show_heatmap(
+    ui: &mut Ui,
+    plan: &HashMap<(Uuid, NaiveDate), WeekLoad>,
+    projects: &[Project],
+    people: &PeopleDirectory,
+    workspace_calendar: &WorkCalendar,
+    weeks: &[NaiveDate],
+    time_zones: &TimeZoneSettings,
+) {
+    let task_text = |task_ref: &TaskRef| {
+        projects
+            .iter()
+            .find(|project| project.get_uuid() == task_ref.project)
+            .and_then(|project| {
+                project
+                    .tasks
+                    .iter()
+                    .find(|task| task.get_uuid() == task_ref.task)
+                    .map(|task| format!("{} ({})", task.text, project.project_name))
+            })
+            .unwrap_or_default()
+    };
+
+    egui::Grid::new("workload_heatmap").show(ui, |ui| {
+        ui.label("");
+        weeks.iter().for_each(|week| {
+            let week_end = week.checked_add_days(Days::new(6)).unwrap_or(*week);
+            ui.label(time_zones.format_date(*week))
+                .on_hover_text(format!(
+                    "Week of {} to {}",
+                    time_zones.format_date(*week),
+                    time_zones.format_date(week_end)
+                ));
+        });
+        ui.end_row();
+
+        people.people.iter().for_each(|person| {
+            ui.horizontal(|ui| {
+                avatar_chip(ui, person);
+                ui.label(&person.name);
+            });
+            weeks.iter().for_each(|week| {
+                let capacity = week_capacity(person.weekly_capacity, workspace_calendar, *week);
+                let load = plan
+                    .get(&(person.get_uuid(), *week))
+                    .cloned()
+                    .unwrap_or_default();
+                let (rect, response) =
+                    ui.allocate_exact_size(egui::vec2(56.0, 22.0), Sense::hover());
+                let painter = ui.painter();
+                painter.rect_filled(rect, 3.0, utilization_color(load.hours, capacity));
+                if load.hours > capacity {
+                    painter.rect_stroke(rect, 3.0, Stroke::new(2.0, Color32::WHITE));
+                }
+                painter.text(
+                    rect.center(),
+                    Align2::CENTER_CENTER,
+                    format!("{:.0}h", load.hours),
+                    FontId::proportional(12.0),
+                    Color32::WHITE,
+                );
+                response.on_hover_ui(|ui| {
+                    ui.label(format!(
+                        "{:.1}h planned of {:.1}h capacity",
+                        load.hours, capacity
+                    ));
+                    if load.hours > capacity {
+                        ui.colored_label(
+                            Color32::LIGHT_RED,
+                            format!("Over-allocated by {:.1}h", load.hours - capacity),
+                        );
+                    }
+                    load.tasks.iter().for_each(|(task, hours)| {
+                        ui.label(format!("{:.1}h {}", hours, task_text(task)));
+                    });
+                });
+            });
+            ui.end_row();
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::people::Person;
+    use crate::project::task::Task;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn hours_project(tasks: Vec<Task>) -> Project {
+        let mut project = Project::default();
+        project.estimate_unit = EstimateUnit::Hours;
+        project.start_date = date(2024, 6, 1);
+        project.end_date = date(2024, 6, 30);
+        project.tasks = tasks;
+        project
+    }
+
+    fn task(person: Uuid, hours: f32, due_date: NaiveDate, priority: Priority) -> Task {
+        let mut task = Task::new(format!("{}h", hours), "".to_string());
+        task.assignees = vec![person];
+        task.estimate = Some(hours);
+        task.due_date = Some(due_date);
+        task.priority = priority;
+        task
+    }
+
+    fn planned_hours(
+        plan: &HashMap<(Uuid, NaiveDate), WeekLoad>,
+        person: Uuid,
+        week: NaiveDate,
+    ) -> f32 {
+        plan.get(&(person, week))
+            .map(|load| load.hours)
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn overdue_hours_land_on_today() {
+        let person = Uuid::new_v4();
+        let projects = vec![hours_project(vec![task(
+            person,
+            6.0,
+            date(2024, 6, 5),
+            Priority::Normal,
+        )])];
+        // Wednesday
+        let today = date(2024, 6, 12);
+        let plan = plan_work(
+            &projects,
+            &WorkCalendar::default(),
+            today,
+            Weekday::Mon,
+            None,
+        );
+        assert_eq!(plan.len(), 1);
+        assert_eq!(planned_hours(&plan, person, date(2024, 6, 10)), 6.0);
+    }
+
+    #[test]
+    fn hours_are_spread_over_working_days() {
+        let person = Uuid::new_v4();
+        let projects = vec![hours_project(vec![task(
+            person,
+            8.0,
+            date(2024, 6, 21),
+            Priority::Normal,
+        )])];
+        let plan = plan_work(
+            &projects,
+            &WorkCalendar::default(),
+            date(2024, 6, 12),
+            Weekday::Mon,
+            None,
+        );
+        assert_eq!(planned_hours(&plan, person, date(2024, 6, 10)), 3.0);
+        assert_eq!(planned_hours(&plan, person, date(2024, 6, 17)), 5.0);
+    }
+
+    #[test]
+    fn holidays_lower_capacity() {
+        let mut calendar = WorkCalendar::default();
+        calendar.add_holiday(date(2024, 6, 12), "Holiday".to_string());
+        assert_eq!(week_capacity(40.0, &calendar, date(2024, 6, 10)), 32.0);
+        assert_eq!(week_capacity(40.0, &calendar, date(2024, 6, 17)), 40.0);
+    }
+
+    #[test]
+    fn leveling_moves_normal_priority_work_later() {
+        let mut person = Person::new("Ada Lovelace");
+        person.weekly_capacity = 10.0;
+        let uuid = person.get_uuid();
+        let people = PeopleDirectory {
+            people: vec![person],
+            me: None,
+        };
+        let friday = date(2024, 6, 14);
+        let projects = vec![hours_project(vec![
+            task(uuid, 8.0, friday, Priority::High),
+            task(uuid, 8.0, friday, Priority::Normal),
+        ])];
+        let suggestions = leveling_suggestions(
+            &projects,
+            &people,
+            &WorkCalendar::default(),
+            date(2024, 6, 10),
+            Weekday::Mon,
+            &[date(2024, 6, 10), date(2024, 6, 17)],
+        );
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].task.task, projects[0].tasks[1].get_uuid());
+        assert_eq!(suggestions[0].from, friday);
+        assert_eq!(suggestions[0].to, date(2024, 6, 28));
+    }
+}